@@ -0,0 +1,48 @@
+//! Lexes generated scripts of growing size, run with `cargo bench --bench lexer`.
+//!
+//! The time per byte stays flat when lexing is linear in the size of the input.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use tsykr::Lexer;
+
+const CHUNK: &str = r#"
+/// Sums the squares of the numbers below `n`.
+fn sum_squares(n) {
+    let total = 0;
+    for i in 0..n {
+        total += i * i; // accumulate
+    }
+    return total;
+}
+let names = ["ä", "b", "ç"];
+let config = {"depth": 0x1F, "ratio": 1_000.5e-3, "label": "run ${names[0]}"};
+/* block /* nested */ comment */
+let report = """
+    total: ${sum_squares(10)}
+    """;
+"#;
+
+fn lex(source: &str) -> Duration {
+    let start = Instant::now();
+    black_box(Lexer::new(source.to_string()).count());
+    start.elapsed()
+}
+
+fn main() {
+    println!("{:>10} {:>12} {:>10}", "bytes", "time", "ns/byte");
+
+    for copies in [250, 500, 1_000, 2_000, 4_000, 8_000] {
+        let source = CHUNK.repeat(copies);
+        // best of a few runs, to leave out noise
+        let time = (0..5)
+            .map(|_| lex(&source))
+            .min()
+            .expect("runs at least once");
+        let per_byte = time.as_nanos() as f64 / source.len() as f64;
+        println!("{:>10} {:>12.2?} {:>10.2}", source.len(), time, per_byte);
+    }
+}
@@ -0,0 +1,268 @@
+use std::{fmt::Write, rc::Rc};
+
+use crate::{
+    eval::EvalError,
+    lexer::{Position, Span},
+    parser::ParserError,
+};
+
+/// Error report that can be rendered against the source it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub span: Option<Span>,
+    pub notes: Vec<String>,
+    pub help: Option<String>,
+    /// Source the span points into, when it isn't the one the diagnostic is rendered with.
+    pub source: Option<Rc<str>>,
+}
+
+impl Diagnostic {
+    pub fn new(message: String) -> Self {
+        Self {
+            message,
+            span: None,
+            notes: vec![],
+            help: None,
+            source: None,
+        }
+    }
+
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn with_note(mut self, note: &str) -> Self {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    pub fn with_help(mut self, help: &str) -> Self {
+        self.help = Some(help.to_string());
+        self
+    }
+
+    pub fn with_source(mut self, source: Rc<str>) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    pub fn from_parser_error(err: &ParserError) -> Self {
+        let mut diagnostic = Self::new(err.to_string());
+        if let Some(token) = err.token() {
+            diagnostic = diagnostic.with_span(token.span());
+        }
+
+        match err {
+            ParserError::OutsideOfLoop(_) => diagnostic.with_help(
+                "`break` and `continue` can only be used inside `while` and `for` loops",
+            ),
+            ParserError::InvalidAssignmentTarget(_) => {
+                diagnostic.with_help("only variables and index expressions can be assigned to")
+            }
+            ParserError::AlternativeBinding(_) => {
+                diagnostic.with_help("every alternative of `|` must bind the same names")
+            }
+            _ => diagnostic,
+        }
+    }
+
+    /// Errors that left the virtual machine know the span and the source of the failed
+    /// instruction, other ones only point at the position where it starts.
+    pub fn from_eval_error(err: &EvalError) -> Self {
+        let span = err
+            .location()
+            .map(|location| location.span)
+            .unwrap_or(Span::point(err.position()));
+        let mut diagnostic = Self::new(err.to_string()).with_span(span);
+        if let Some(source) = err.location().and_then(|location| location.source.clone()) {
+            diagnostic = diagnostic.with_source(source);
+        }
+        // deep recursion repeats the same call many times, it's shown once
+        let mut stack = err.stack().iter().peekable();
+        while let Some(frame) = stack.next() {
+            let mut repeated = 1;
+            while stack.next_if_eq(&frame).is_some() {
+                repeated += 1;
+            }
+            diagnostic = match repeated {
+                1 => diagnostic.with_note(&frame.to_string()),
+                _ => diagnostic.with_note(&format!("{frame} ({repeated} times)")),
+            };
+        }
+
+        match err.root() {
+            EvalError::NotDefined(name, _) => {
+                diagnostic.with_help(&format!("declare it before use: `let {name} = ...;`"))
+            }
+            EvalError::NonBooleanCondition(_, _) => {
+                diagnostic.with_note("conditions are not converted to booleans implicitly")
+            }
+            EvalError::InvalidMapKey(_, _) => {
+                diagnostic.with_note("map keys must be booleans, integers or strings")
+            }
+            EvalError::NonExhaustiveMatch(_, _) => {
+                diagnostic.with_help("add a `_ => ...` arm for the values no pattern covers")
+            }
+            _ => diagnostic,
+        }
+    }
+
+    /// Renders the message, the offending source line with the span underlined,
+    /// and the notes and help, if any. The diagnostic's own source is preferred to `source`.
+    pub fn render(&self, source: &str) -> String {
+        let mut out = format!("error: {}\n", self.message);
+        let source = self.source.as_deref().unwrap_or(source);
+
+        let line = self.span.and_then(|span| {
+            source
+                .lines()
+                .nth(span.start.0.checked_sub(1)?)
+                .map(|line| (span, line))
+        });
+
+        let gutter = match line {
+            Some((span, text)) => {
+                let number = span.start.0.to_string();
+                let gutter = " ".repeat(number.len());
+                let Position(_, start) = span.start;
+
+                // multiline spans are underlined to the end of their first line
+                let end = if span.end.0 == span.start.0 {
+                    span.end.1.max(start)
+                } else {
+                    text.chars().count().max(start)
+                };
+
+                // tabs are kept so the carets line up with the text above them
+                let indent: String = text
+                    .chars()
+                    .take(start.saturating_sub(1))
+                    .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+                    .collect();
+
+                writeln!(out, "{gutter}--> {}", span.start).unwrap();
+                writeln!(out, "{gutter} |").unwrap();
+                writeln!(out, "{number} | {text}").unwrap();
+                writeln!(out, "{gutter} | {indent}{}", "^".repeat(end - start + 1)).unwrap();
+                gutter
+            }
+            None => String::new(),
+        };
+
+        for note in &self.notes {
+            writeln!(out, "{gutter} = note: {note}").unwrap();
+        }
+        if let Some(help) = &self.help {
+            writeln!(out, "{gutter} = help: {help}").unwrap();
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        eval::{Location, StackFrame},
+        lexer::Lexer,
+        parser::Parser,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_render_parser_error() {
+        let source = "let a = 1;\nlet = 2;\n";
+        let errors = Parser::new(Lexer::new(source.to_string()))
+            .parse()
+            .unwrap_err();
+
+        assert_eq!(
+            Diagnostic::from_parser_error(&errors[0]).render(source),
+            "error: Unexpected token: = - line:2, col:5\n \
+             --> line:2, col:5\n  \
+             |\n\
+             2 | let = 2;\n  \
+             |     ^\n"
+        );
+    }
+
+    #[test]
+    fn test_render_eval_error() {
+        let source = "let total = 1;\n\tprint(totl + 1);";
+        let err = EvalError::NotDefined("totl".to_string(), Position(2, 8)).at(Location {
+            span: Span::new(Position(2, 8), Position(2, 11)),
+            source: None,
+        });
+
+        assert_eq!(
+            Diagnostic::from_eval_error(&err).render(source),
+            "error: Not defined: totl at line:2, col:8\n \
+             --> line:2, col:8\n  \
+             |\n\
+             2 | \tprint(totl + 1);\n  \
+             | \t      ^^^^\n  \
+             = help: declare it before use: `let totl = ...;`\n"
+        );
+    }
+
+    #[test]
+    fn test_render_eval_error_in_other_source() {
+        let err = EvalError::InvalidInfixOperation(
+            "1".to_string(),
+            "+".to_string(),
+            "true".to_string(),
+            Position(1, 22),
+        )
+        .at(Location {
+            span: Span::point(Position(1, 22)),
+            source: Some(Rc::from("fn f(x) { return x + true; }")),
+        });
+
+        assert_eq!(
+            Diagnostic::from_eval_error(&err).render("f(1)"),
+            "error: Invalid infix operation: 1+true at line:1, col:22\n \
+             --> line:1, col:22\n  \
+             |\n\
+             1 | fn f(x) { return x + true; }\n  \
+             |                      ^\n"
+        );
+    }
+
+    #[test]
+    fn test_render_without_span() {
+        let diagnostic = Diagnostic::new("Unexpected end of input".to_string())
+            .with_note("the input ended early")
+            .with_help("finish the statement");
+
+        assert_eq!(
+            diagnostic.render("let a ="),
+            "error: Unexpected end of input\n \
+             = note: the input ended early\n \
+             = help: finish the statement\n"
+        );
+    }
+
+    #[test]
+    fn test_render_stack() {
+        let frame = |call_site| StackFrame {
+            name: "f".to_string(),
+            call_site,
+            builtin: false,
+        };
+        let mut err =
+            EvalError::StackOverflow(3, Position(1, 19)).with_frame(frame(Position(1, 19)));
+        err = err.with_frame(frame(Position(1, 19)));
+        err = err.with_frame(frame(Position(2, 2)));
+
+        assert_eq!(
+            Diagnostic::from_eval_error(&err).notes,
+            vec![
+                "in f called at line:1, col:19 (2 times)",
+                "in f called at line:2, col:2"
+            ]
+        );
+    }
+}
@@ -0,0 +1,433 @@
+use std::{cell::RefCell, fmt::Debug, rc::Rc};
+
+use thiserror::Error;
+
+use crate::{
+    diagnostic::Diagnostic,
+    eval::{
+        Builtin, BuiltinCall, Compiler, Environment, EvalError, ExecutionEnvironment,
+        FunctionProto, IntoBuiltin, Value, Vm, DEFAULT_MAX_CALL_DEPTH,
+    },
+    lexer::Lexer,
+    parser::{Parser, ParserError},
+};
+
+#[derive(Debug, Error)]
+pub enum EngineError {
+    #[error("Parser errors:\n\t{}", .0.iter().map(|err| err.to_string()).collect::<Vec<String>>().join("\n\t"))]
+    Parse(Vec<ParserError>),
+    #[error("Runtime error: {0}")]
+    Eval(#[from] EvalError),
+    #[error("Script was compiled by another engine")]
+    ForeignScript,
+}
+
+impl EngineError {
+    /// Diagnostics for every error, to be rendered against the source that failed.
+    /// Runtime errors raised in code compiled from an earlier source carry that source.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        match self {
+            EngineError::Parse(errors) => {
+                errors.iter().map(Diagnostic::from_parser_error).collect()
+            }
+            EngineError::Eval(err) => vec![Diagnostic::from_eval_error(err)],
+            EngineError::ForeignScript => vec![Diagnostic::new(self.to_string())],
+        }
+    }
+}
+
+/// Compiled program, ready to be run by the engine that compiled it.
+#[derive(Debug, Clone)]
+pub struct Script {
+    proto: Rc<FunctionProto>,
+    /// Globals of the compiling engine, the slots of the script index into them.
+    globals: Rc<RefCell<ExecutionEnvironment>>,
+}
+
+/// Embeddable interpreter.
+///
+/// Every script run by an engine shares the same global environment,
+/// so definitions made by one script are visible to the next one.
+pub struct Engine {
+    globals: Rc<RefCell<ExecutionEnvironment>>,
+    max_call_depth: usize,
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Engine {
+            globals: ExecutionEnvironment::new_global(),
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+        }
+    }
+
+    /// Sets the number of nested function calls after which scripts fail
+    /// with `EvalError::StackOverflow`.
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.max_call_depth = depth;
+    }
+
+    /// Parses and compiles the source against the globals of this engine.
+    pub fn compile(&self, source: &str) -> Result<Script, EngineError> {
+        let mut parser = Parser::new(Lexer::new(source.to_string()));
+        let program = parser.parse().map_err(EngineError::Parse)?;
+
+        let proto = Compiler::new(Rc::clone(&self.globals))
+            .with_source(Rc::from(source))
+            .compile_program(&program);
+        Ok(Script {
+            proto: Rc::new(proto),
+            globals: Rc::clone(&self.globals),
+        })
+    }
+
+    /// Runs a compiled script and returns the value of its last statement.
+    /// Scripts compiled by another engine are rejected.
+    pub fn run(&self, script: &Script) -> Result<Rc<Value>, EngineError> {
+        if !Rc::ptr_eq(&script.globals, &self.globals) {
+            return Err(EngineError::ForeignScript);
+        }
+        Ok(self.vm().run(Rc::clone(&script.proto))?)
+    }
+
+    /// Compiles and runs the source.
+    pub fn eval(&self, source: &str) -> Result<Rc<Value>, EngineError> {
+        let script = self.compile(source)?;
+        self.run(&script)
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Rc<Value>> {
+        self.globals.borrow().get(name)
+    }
+
+    /// Defines or overwrites a global variable.
+    pub fn set_global(&self, name: &str, value: Rc<Value>) {
+        self.globals.borrow_mut().set(name.to_string(), value);
+    }
+
+    /// Registers a native function as a global builtin.
+    /// The closure may capture host state, errors built with `BuiltinCall` carry the given name.
+    pub fn register_fn<F>(&self, name: &str, func: F)
+    where
+        F: Fn(Vec<Rc<Value>>, &BuiltinCall) -> Result<Rc<Value>, EvalError> + 'static,
+    {
+        self.set_global(name, Value::new_builtin(name, func));
+    }
+
+    /// Registers a plain Rust function as a global builtin.
+    /// Arguments and the result are converted with `FromValue` and `IntoValue`.
+    pub fn register_native<Args>(&self, name: &str, func: impl IntoBuiltin<Args>) {
+        self.set_global(name, Value::Builtin(Builtin::from_fn(name, func)).rc());
+    }
+
+    /// Calls a script function or a builtin with the arguments.
+    /// A function taken from another engine runs against the globals of that engine.
+    pub fn call(
+        &self,
+        function: &Rc<Value>,
+        args: Vec<Rc<Value>>,
+    ) -> Result<Rc<Value>, EngineError> {
+        Ok(self.vm().call(Rc::clone(function), args)?)
+    }
+
+    fn vm(&self) -> Vm {
+        let mut vm = Vm::new(Rc::clone(&self.globals));
+        vm.set_max_call_depth(self.max_call_depth);
+        vm
+    }
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for Engine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Engine")
+            .field("globals", &self.globals.borrow())
+            .field("max_call_depth", &self.max_call_depth)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::{Position, Span};
+
+    #[test]
+    fn test_engine_eval() {
+        let engine = Engine::new();
+
+        assert_eq!(engine.eval("1 + 2").unwrap(), Value::new_integer(3));
+        engine.eval("let x = 5;").unwrap();
+        assert_eq!(engine.eval("x * 2").unwrap(), Value::new_integer(10));
+
+        let script = engine.compile("x = x + 1; x").unwrap();
+        assert_eq!(engine.run(&script).unwrap(), Value::new_integer(6));
+        assert_eq!(engine.run(&script).unwrap(), Value::new_integer(7));
+    }
+
+    #[test]
+    fn test_engine_foreign_script() {
+        let engine = Engine::new();
+        let other = Engine::new();
+        other.eval("let a = 1; let b = 2;").unwrap();
+
+        let script = other.compile("b").unwrap();
+        assert!(matches!(
+            engine.run(&script),
+            Err(EngineError::ForeignScript)
+        ));
+        assert_eq!(other.run(&script.clone()).unwrap(), Value::new_integer(2));
+    }
+
+    #[test]
+    fn test_engine_globals() {
+        let engine = Engine::new();
+        assert_eq!(engine.get_global("answer"), None);
+
+        engine.set_global("answer", Value::new_integer(41));
+        assert_eq!(engine.eval("answer + 1").unwrap(), Value::new_integer(42));
+
+        engine.eval("answer = \"changed\";").unwrap();
+        assert_eq!(
+            engine.get_global("answer"),
+            Some(Value::new_string("changed".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_engine_call() {
+        let engine = Engine::new();
+        engine
+            .eval("let base = 10; fn add(a, b) { return base + a + b; }")
+            .unwrap();
+
+        let add = engine.get_global("add").unwrap();
+        let result = engine
+            .call(&add, vec![Value::new_integer(1), Value::new_integer(2)])
+            .unwrap();
+        assert_eq!(result, Value::new_integer(13));
+
+        let len = engine.get_global("len").unwrap();
+        let result = engine
+            .call(&len, vec![Value::new_string("abc".to_string())])
+            .unwrap();
+        assert_eq!(result, Value::new_integer(3));
+
+        assert!(matches!(
+            engine.call(&add, vec![]),
+            Err(EngineError::Eval(err))
+                if matches!(err.root(), EvalError::WrongNumberOfArguments(2, 0, _))
+        ));
+        assert!(matches!(
+            engine.call(&Value::new_integer(1), vec![]),
+            Err(EngineError::Eval(err)) if matches!(err.root(), EvalError::NotAFunction(_))
+        ));
+    }
+
+    #[test]
+    fn test_engine_foreign_function() {
+        let engine = Engine::new();
+        let other = Engine::new();
+        engine.eval("let unused = 0; let count = 100;").unwrap();
+        other
+            .eval("let count = 0; fn bump(n) { count = count + n; return count; }")
+            .unwrap();
+
+        let bump = other.get_global("bump").unwrap();
+        assert_eq!(
+            engine.call(&bump, vec![Value::new_integer(2)]).unwrap(),
+            Value::new_integer(2)
+        );
+
+        engine.set_global("bump", bump);
+        assert_eq!(engine.eval("bump(3)").unwrap(), Value::new_integer(5));
+        assert_eq!(engine.get_global("count"), Some(Value::new_integer(100)));
+        assert_eq!(other.get_global("count"), Some(Value::new_integer(5)));
+    }
+
+    #[test]
+    fn test_engine_call_stack() {
+        let engine = Engine::new();
+        engine
+            .eval("let one = 1;\nfn fail() { return one + true; }")
+            .unwrap();
+
+        // the host call is reported where the function is defined
+        let fail = engine.get_global("fail").unwrap();
+        match engine.call(&fail, vec![]) {
+            Err(EngineError::Eval(err)) => {
+                assert_eq!(err.stack().len(), 1);
+                assert_eq!(err.stack()[0].name, "fail");
+                assert_eq!(err.stack()[0].call_site, Position(2, 1));
+            }
+            other => panic!("Expected a runtime error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_engine_register_fn() {
+        let engine = Engine::new();
+        let log = Rc::new(RefCell::new(vec![]));
+
+        let captured = Rc::clone(&log);
+        engine.register_fn("record", move |args, call| {
+            if args.len() != 1 {
+                return Err(call.wrong_number_of_arguments(1, args.len()));
+            }
+            match &*args[0] {
+                Value::String(s) => captured.borrow_mut().push(s.clone()),
+                other => return Err(call.wrong_argument_type("String", other)),
+            }
+            Ok(Value::new_integer(captured.borrow().len() as i64))
+        });
+
+        let result = engine
+            .eval("record(\"a\"); for x in [\"b\", \"c\"] { record(x); } record(\"d\")")
+            .unwrap();
+        assert_eq!(result, Value::new_integer(4));
+        assert_eq!(*log.borrow(), vec!["a", "b", "c", "d"]);
+
+        match engine.eval("record(1)") {
+            Err(EngineError::Eval(err)) => assert_eq!(
+                err.to_string(),
+                "Type mismatch: record: expected: String, got 1 at line:1, col:7"
+            ),
+            other => panic!("Expected eval error, got {other:?}"),
+        }
+        match engine.eval("record()") {
+            Err(EngineError::Eval(err)) => match err.root() {
+                EvalError::BuiltinWrongNumberOfArguments(name, 1, 0, _) => {
+                    assert_eq!(name, "record")
+                }
+                other => panic!("Expected wrong number of arguments, got {other:?}"),
+            },
+            other => panic!("Expected eval error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_engine_register_native() {
+        fn repeat(s: String, n: i64) -> String {
+            s.repeat(n as usize)
+        }
+
+        let engine = Engine::new();
+        engine.register_native("repeat", repeat);
+        engine.register_native("sum", |nums: Vec<f64>| nums.iter().sum::<f64>());
+        engine.register_native("first", |arr: Vec<Rc<Value>>| arr.first().cloned());
+        engine.register_native("round", |x: f64, places: Option<i64>| {
+            let scale = 10f64.powi(places.unwrap_or(0) as i32);
+            (x * scale).round() / scale
+        });
+
+        assert_eq!(
+            engine.eval("repeat(\"ab\", 3)").unwrap(),
+            Value::new_string("ababab".to_string())
+        );
+        assert_eq!(
+            engine.eval("sum([1, 2.5, 3])").unwrap(),
+            Value::new_float(6.5)
+        );
+        assert_eq!(engine.eval("first([7])").unwrap(), Value::new_integer(7));
+        assert!(engine.eval("first([])").unwrap().is_void());
+        assert_eq!(engine.eval("round(2.345)").unwrap(), Value::new_float(2.0));
+        assert_eq!(
+            engine.eval("round(2.345, 1)").unwrap(),
+            Value::new_float(2.3)
+        );
+
+        let err = engine.eval("repeat(\"ab\", \"3\")").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Runtime error: Type mismatch: repeat: expected: Integer, got 3 at line:1, col:7"
+        );
+        let err = engine.eval("repeat(\"ab\")").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Runtime error: Wrong number of arguments: repeat: expected: 2, got 1 at line:1, col:7"
+        );
+        let err = engine.eval("sum([1, true])").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Runtime error: Type mismatch: sum: expected: Array of Float, got [1, true] at line:1, col:4"
+        );
+        let err = engine.eval("round(1.5, 1, 2)").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Runtime error: Wrong number of arguments: round: expected: 1 to 2, got 3 at line:1, col:6"
+        );
+    }
+
+    #[test]
+    fn test_engine_max_call_depth() {
+        let mut engine = Engine::new();
+        engine
+            .eval("fn depth(n) { if (n == 0) { return 0; } return 1 + depth(n - 1); }")
+            .unwrap();
+        assert_eq!(
+            engine.eval("depth(5000)").unwrap(),
+            Value::new_integer(5000)
+        );
+
+        engine.set_max_call_depth(100);
+        assert_eq!(engine.eval("depth(99)").unwrap(), Value::new_integer(99));
+        match engine.eval("depth(100)") {
+            Err(EngineError::Eval(err)) => {
+                assert!(matches!(err.root(), EvalError::StackOverflow(100, _)));
+                assert_eq!(err.stack().len(), 100);
+            }
+            other => panic!("Expected stack overflow, got {other:?}"),
+        }
+
+        let err = engine.eval("fn forever() { return 1 + forever(); } forever()");
+        assert!(err
+            .unwrap_err()
+            .to_string()
+            .starts_with("Runtime error: Stack overflow: call depth exceeded 100"));
+    }
+
+    #[test]
+    fn test_engine_errors() {
+        let engine = Engine::new();
+
+        match engine.eval("let = 1;") {
+            Err(err @ EngineError::Parse(_)) => {
+                assert!(err.to_string().starts_with("Parser errors:\n\t"))
+            }
+            other => panic!("Expected parse error, got {other:?}"),
+        }
+        assert!(matches!(
+            engine.eval("1 / 0"),
+            Err(EngineError::Eval(err)) if matches!(err.root(), EvalError::DivisionByZero(..))
+        ));
+    }
+
+    #[test]
+    fn test_engine_diagnostics_source() {
+        let engine = Engine::new();
+        engine.eval("fn f(x) { return x + true; }").unwrap();
+
+        let err = engine.eval("f(1)").unwrap_err();
+        let diagnostics = err.diagnostics();
+        assert_eq!(
+            diagnostics[0].render("f(1)"),
+            "error: Invalid infix operation: 1+true at line:1, col:20\n \
+             --> line:1, col:20\n  \
+             |\n\
+             1 | fn f(x) { return x + true; }\n  \
+             |                    ^\n  \
+             = note: in f called at line:1, col:2\n"
+        );
+
+        let err = engine.eval("let total = 1;\nprint(totl);").unwrap_err();
+        assert_eq!(
+            err.diagnostics()[0].span,
+            Some(Span::new(Position(2, 7), Position(2, 10)))
+        );
+    }
+}
@@ -1,32 +1,25 @@
 use std::rc::Rc;
 
-use crate::{
-    eval::{EvalError, Value},
-    lexer::Position,
-};
+use crate::eval::{FromValue, Value};
 
-pub fn length(args: Vec<Rc<Value>>, pos: Position) -> Result<Rc<Value>, EvalError> {
-    if args.len() != 1 {
-        return Err(EvalError::BuiltinWrongNumberOfArguments(
-            "len".to_string(),
-            1,
-            args.len(),
-            pos,
-        ));
-    }
+/// Length of the argument of `len`: characters of a string, elements of an array or entries of a map.
+pub struct Length(usize);
 
-    if !args[0].is_string() && !args[0].is_array() {
-        return Err(EvalError::BuiltinWrongArgumentType(
-            "len".to_string(),
-            "String or Array".to_string(),
-            args[0].to_string(),
-            pos,
-        ));
+impl FromValue for Length {
+    fn type_name() -> String {
+        "String, Array or Map".to_string()
     }
 
-    match &*args[0] {
-        Value::String(str) => Ok(Value::new_integer(str.len() as i64)),
-        Value::Array(arr) => Ok(Value::new_integer(arr.len() as i64)),
-        _ => unreachable!(),
+    fn from_value(value: &Rc<Value>) -> Option<Self> {
+        match &**value {
+            Value::String(str) => Some(Length(str.chars().count())),
+            Value::Array(arr) => Some(Length(arr.borrow().len())),
+            Value::Map(map) => Some(Length(map.borrow().len())),
+            _ => None,
+        }
     }
 }
+
+pub fn length(value: Length) -> i64 {
+    value.0 as i64
+}
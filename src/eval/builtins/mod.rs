@@ -1,16 +1,21 @@
-use super::{BuiltinFn, Environment, GlobalEnvironment, Value};
+use std::rc::Rc;
+
+use super::{Builtin, BuiltinCall, Environment, EvalError, GlobalEnvironment, Value};
 
 mod len;
 mod print;
 
 pub fn register_builtins(env: &mut GlobalEnvironment) {
-    let to_register: Vec<(&str, BuiltinFn)> = vec![
-        ("print", print::print),
-        ("println", print::println),
-        ("len", len::length),
-    ];
+    type NativeFn = fn(Vec<Rc<Value>>, &BuiltinCall) -> Result<Rc<Value>, EvalError>;
+
+    let to_register: Vec<(&str, NativeFn)> =
+        vec![("print", print::print), ("println", print::println)];
 
     for (name, func) in to_register {
-        env.set(name.to_string(), Value::new_builtin(func));
+        env.set(name.to_string(), Value::new_builtin(name, func));
     }
+    env.set(
+        "len".to_string(),
+        Value::Builtin(Builtin::from_fn("len", len::length)).rc(),
+    );
 }
@@ -1,11 +1,8 @@
 use std::rc::Rc;
 
-use crate::{
-    eval::{EvalError, Value, VOID},
-    lexer::Position,
-};
+use crate::eval::{BuiltinCall, EvalError, Value, VOID};
 
-pub fn print(args: Vec<Rc<Value>>, _pos: Position) -> Result<Rc<Value>, EvalError> {
+pub fn print(args: Vec<Rc<Value>>, _call: &BuiltinCall) -> Result<Rc<Value>, EvalError> {
     print!(
         "{}",
         &args
@@ -18,7 +15,7 @@ pub fn print(args: Vec<Rc<Value>>, _pos: Position) -> Result<Rc<Value>, EvalErro
     Ok(VOID.rc())
 }
 
-pub fn println(args: Vec<Rc<Value>>, _pos: Position) -> Result<Rc<Value>, EvalError> {
+pub fn println(args: Vec<Rc<Value>>, _call: &BuiltinCall) -> Result<Rc<Value>, EvalError> {
     println!(
         "{}",
         &args
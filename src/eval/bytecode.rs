@@ -0,0 +1,291 @@
+use std::{fmt::Debug, rc::Rc};
+
+use crate::lexer::{Span, TokenKind};
+
+use super::{MapKey, Value};
+
+/// Single virtual machine instruction.
+///
+/// Operands are indices into the owning [`Chunk`] tables or absolute jump targets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OpCode {
+    /// Pushes `chunk.constants[idx]`.
+    Constant(usize),
+    True,
+    False,
+    Void,
+    Pop,
+    /// Duplicates the two values on top of the stack.
+    Dup2,
+
+    /// Pushes the value of a variable described by `chunk.lookups[idx]`.
+    Get(usize),
+    /// Pops a value and assigns it to a variable described by `chunk.lookups[idx]`.
+    Set(usize),
+    /// Pops a value and stores it in the slot of the innermost local scope.
+    Define(usize),
+    /// Pops a value and stores it in the global slot.
+    DefineGlobal(usize),
+
+    /// Pushes a function built from `chunk.functions[idx]` that captures the current scope.
+    Closure(usize),
+    /// Pops `n` values and pushes them as an array.
+    Array(usize),
+    /// Pops `n` key and value pairs and pushes them as a map.
+    Map(usize),
+    /// Pops `n` values and pushes their strings joined together.
+    Template(usize),
+    /// Pops an index and a value, pushes the indexed element.
+    Index,
+    /// Pops a value, an index and a collection, stores the value at the index.
+    SetIndex,
+    /// Pops the end and the start of an integer range, pushes the range.
+    Range {
+        inclusive: bool,
+    },
+    Prefix(PrefixOp),
+    Infix(InfixOp),
+    /// Calls a function with `n` arguments on top of the stack.
+    Call(usize),
+    /// Same as `Call`, but a script function replaces the current frame instead of
+    /// growing the call stack. Always followed by a `Return`, which builtins reach.
+    TailCall(usize),
+    Return,
+
+    Jump(usize),
+    /// Records the stack height at the start of a loop.
+    EnterLoop,
+    /// Forgets the stack height of the innermost loop.
+    ExitLoop,
+    /// Pops the values above the stack height of the innermost loop.
+    /// `break` and `continue` may leave the operands of an expression behind.
+    Unwind,
+    /// Checks that the value on top of the stack is iterable and pushes an iteration cursor above it.
+    IterStart,
+    /// Advances the cursor on top of the stack and pushes the next element.
+    /// Jumps without touching the stack once the iterable is exhausted.
+    IterNext(usize),
+    /// Pops a condition and jumps if it is `false`. The condition must be a boolean.
+    JumpIfFalse(usize),
+    /// Tests the value on top of the stack against `chunk.patterns[idx]` and pushes whether it matched.
+    /// The bindings of a successful match are stored in the innermost local scope.
+    Match(usize),
+    /// Pops the value no arm of a `match` matched and raises an error.
+    NoMatch,
+
+    /// Opens a local scope with `n` slots.
+    PushScope(usize),
+    /// Closes the innermost local scope.
+    PopScope,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrefixOp {
+    /// !x
+    Not,
+    /// -x
+    Negate,
+}
+
+impl PrefixOp {
+    pub fn from_token_kind(kind: &TokenKind) -> Option<Self> {
+        match kind {
+            TokenKind::Bang => Some(Self::Not),
+            TokenKind::Minus => Some(Self::Negate),
+            _ => None,
+        }
+    }
+
+    pub fn literal(&self) -> String {
+        match self {
+            Self::Not => "!".to_string(),
+            Self::Negate => "-".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InfixOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Equal,
+    NotEqual,
+    LessThan,
+    GreaterThan,
+    LessThanEqual,
+    GreaterThanEqual,
+}
+
+impl InfixOp {
+    pub fn from_token_kind(kind: &TokenKind) -> Option<Self> {
+        match kind {
+            TokenKind::Plus => Some(Self::Add),
+            TokenKind::Minus => Some(Self::Subtract),
+            TokenKind::Asterisk => Some(Self::Multiply),
+            TokenKind::Slash => Some(Self::Divide),
+            TokenKind::EqualsEquals => Some(Self::Equal),
+            TokenKind::BangEquals => Some(Self::NotEqual),
+            TokenKind::LessThan => Some(Self::LessThan),
+            TokenKind::GreaterThan => Some(Self::GreaterThan),
+            TokenKind::LessThanEquals => Some(Self::LessThanEqual),
+            TokenKind::GreaterThanEquals => Some(Self::GreaterThanEqual),
+            _ => None,
+        }
+    }
+
+    /// Operator of a compound assignment token, `+=` is `Add`.
+    pub fn from_assign_token_kind(kind: &TokenKind) -> Option<Self> {
+        match kind {
+            TokenKind::PlusEquals => Some(Self::Add),
+            TokenKind::MinusEquals => Some(Self::Subtract),
+            TokenKind::AsteriskEquals => Some(Self::Multiply),
+            TokenKind::SlashEquals => Some(Self::Divide),
+            _ => None,
+        }
+    }
+
+    pub fn literal(&self) -> String {
+        match self {
+            Self::Add => "+".to_string(),
+            Self::Subtract => "-".to_string(),
+            Self::Multiply => "*".to_string(),
+            Self::Divide => "/".to_string(),
+            Self::Equal => "==".to_string(),
+            Self::NotEqual => "!=".to_string(),
+            Self::LessThan => "<".to_string(),
+            Self::GreaterThan => ">".to_string(),
+            Self::LessThanEqual => "<=".to_string(),
+            Self::GreaterThanEqual => ">=".to_string(),
+        }
+    }
+}
+
+/// Where a variable lives at runtime.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Binding {
+    /// Slot of a local scope, `depth` scopes up from the current one.
+    Local { depth: usize, slot: usize },
+    /// Slot of the global environment.
+    Global(usize),
+}
+
+/// Resolved variable reference.
+///
+/// A local may be read before its `let` ran, in which case the outer
+/// bindings are tried in order, just like a lookup by name would do.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lookup {
+    pub name: String,
+    pub bindings: Vec<Binding>,
+}
+
+/// Compiled pattern of a `match` arm.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatchPattern {
+    /// Matches anything.
+    Any,
+    /// Matches values of the same type equal to the constant, `1` does not match `1.0`.
+    Constant(Rc<Value>),
+    /// Matches anything and stores it in the local slot.
+    Bind(usize),
+    /// Matches arrays of the same length with every element matching.
+    Array(Vec<MatchPattern>),
+    /// Matches maps having every key with a matching value, other keys are ignored.
+    Map(Vec<(MapKey, MatchPattern)>),
+    /// Matches if any of the alternatives matches, the first one that does binds.
+    Or(Vec<MatchPattern>),
+}
+
+/// Compiled code of a single function or of a whole program.
+#[derive(Debug, Default, PartialEq)]
+pub struct Chunk {
+    pub code: Vec<OpCode>,
+    /// Source span of every instruction in `code`.
+    pub spans: Vec<Span>,
+    pub constants: Vec<Rc<Value>>,
+    pub lookups: Vec<Lookup>,
+    pub functions: Vec<Rc<FunctionProto>>,
+    pub patterns: Vec<MatchPattern>,
+}
+
+impl Chunk {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends an instruction and returns its index.
+    pub fn push(&mut self, op: OpCode, span: Span) -> usize {
+        self.code.push(op);
+        self.spans.push(span);
+        self.code.len() - 1
+    }
+
+    pub fn add_constant(&mut self, value: Rc<Value>) -> usize {
+        self.constants.push(value);
+        self.constants.len() - 1
+    }
+
+    pub fn add_lookup(&mut self, lookup: Lookup) -> usize {
+        self.lookups.push(lookup);
+        self.lookups.len() - 1
+    }
+
+    pub fn add_function(&mut self, function: FunctionProto) -> usize {
+        self.functions.push(Rc::new(function));
+        self.functions.len() - 1
+    }
+
+    pub fn add_pattern(&mut self, pattern: MatchPattern) -> usize {
+        self.patterns.push(pattern);
+        self.patterns.len() - 1
+    }
+}
+
+/// Compiled function, shared by every closure created from it.
+#[derive(PartialEq)]
+pub struct FunctionProto {
+    /// Name of a declared function, `None` for function expressions and scripts.
+    pub name: Option<String>,
+    pub params: Vec<String>,
+    /// Local slot of every parameter, in declaration order.
+    pub param_slots: Vec<usize>,
+    /// Number of slots in the function scope (parameters included).
+    pub slots: usize,
+    pub chunk: Chunk,
+    /// Source the function was compiled from, runtime errors are shown against it.
+    pub source: Option<Rc<str>>,
+    /// Where the function is defined, `None` for scripts.
+    pub span: Option<Span>,
+}
+
+impl FunctionProto {
+    pub fn new(params: Vec<String>, param_slots: Vec<usize>, slots: usize, chunk: Chunk) -> Self {
+        FunctionProto {
+            name: None,
+            params,
+            param_slots,
+            slots,
+            chunk,
+            source: None,
+            span: None,
+        }
+    }
+
+    /// Name shown in stack traces.
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or("<anonymous>")
+    }
+}
+
+impl Debug for FunctionProto {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FunctionProto")
+            .field("name", &self.name)
+            .field("params", &self.params)
+            .field("slots", &self.slots)
+            .field("code", &self.chunk.code.len())
+            .finish()
+    }
+}
@@ -0,0 +1,554 @@
+use std::{cell::RefCell, rc::Rc};
+
+#[cfg(test)]
+use crate::lexer::Position;
+use crate::{
+    lexer::Span,
+    parser::{Block, Expression, Identifier, Program, Statement},
+};
+
+use super::{
+    Binding, Chunk, ExecutionEnvironment, FunctionProto, Lookup, MatchPattern, OpCode, Value,
+};
+#[cfg(test)]
+use super::{EvalError, Vm};
+
+/// Ast nodes that can be compiled to bytecode.
+///
+/// Expressions leave exactly one value on the stack, statements leave the stack as it was.
+pub trait Compile {
+    fn compile(&self, compiler: &mut Compiler);
+}
+
+/// Variables of a single local scope.
+#[derive(Debug, Default)]
+struct Scope {
+    names: Vec<String>,
+    /// Whether the variable is surely defined at the point being compiled.
+    initialized: Vec<bool>,
+}
+
+impl Scope {
+    fn slot_of(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n == name)
+    }
+
+    /// Reserves a slot for the name, reusing the existing one on redeclaration.
+    fn declare(&mut self, name: String, initialized: bool) -> usize {
+        if let Some(slot) = self.slot_of(&name) {
+            self.initialized[slot] |= initialized;
+            return slot;
+        }
+        self.names.push(name);
+        self.initialized.push(initialized);
+        self.names.len() - 1
+    }
+}
+
+/// Loop being compiled.
+#[derive(Debug)]
+struct Loop {
+    /// Index of the first instruction of the loop condition.
+    start: usize,
+    /// Number of scopes that were open when the loop started.
+    scopes: usize,
+    /// Jumps out of the loop to patch once its end is known.
+    breaks: Vec<usize>,
+}
+
+/// Compiles ast into bytecode.
+///
+/// Variables declared in functions and blocks are resolved to local slots.
+/// Everything else lives in the global environment the compiler was created with,
+/// so code compiled at different times (REPL lines) shares the same globals.
+pub struct Compiler {
+    globals: Rc<RefCell<ExecutionEnvironment>>,
+    /// Chunks of the functions being compiled, innermost last.
+    chunks: Vec<Chunk>,
+    /// Lexical scopes, innermost last. Each one maps to a runtime local environment.
+    scopes: Vec<Scope>,
+    /// Loops of the function being compiled, innermost last.
+    loops: Vec<Loop>,
+    /// Source being compiled, shared by every function compiled from it.
+    source: Option<Rc<str>>,
+}
+
+impl Compiler {
+    pub fn new(globals: Rc<RefCell<ExecutionEnvironment>>) -> Self {
+        Compiler {
+            globals,
+            chunks: vec![Chunk::new()],
+            scopes: vec![],
+            loops: vec![],
+            source: None,
+        }
+    }
+
+    /// Records the source of the compiled functions, so runtime errors can be shown against it.
+    pub fn with_source(mut self, source: Rc<str>) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Compiles a whole program into a function that returns the value of the last statement.
+    pub fn compile_program(mut self, program: &Program) -> FunctionProto {
+        program.compile(&mut self);
+
+        let chunk = self.chunks.pop().expect("script chunk");
+        let mut script = FunctionProto::new(vec![], vec![], 0, chunk);
+        script.source = self.source;
+        script
+    }
+
+    /// Chunk of the function being compiled.
+    pub fn chunk(&mut self) -> &mut Chunk {
+        self.chunks.last_mut().expect("at least the script chunk")
+    }
+
+    /// Appends an instruction and returns its index.
+    pub fn emit(&mut self, op: OpCode, span: Span) -> usize {
+        self.chunk().push(op, span)
+    }
+
+    pub fn emit_constant(&mut self, value: Rc<Value>, span: Span) {
+        let idx = self.chunk().add_constant(value);
+        self.emit(OpCode::Constant(idx), span);
+    }
+
+    /// Points a previously emitted jump at the next instruction.
+    pub fn patch_jump(&mut self, jump: usize) {
+        let target = self.chunk().code.len();
+        match &mut self.chunk().code[jump] {
+            OpCode::Jump(to) | OpCode::JumpIfFalse(to) | OpCode::IterNext(to) => *to = target,
+            op => unreachable!("not a jump: {op:?}"),
+        }
+    }
+
+    /// Emits a read of the variable.
+    pub fn emit_get(&mut self, name: &str, span: Span) {
+        let lookup = self.resolve(name);
+        let idx = self.chunk().add_lookup(lookup);
+        self.emit(OpCode::Get(idx), span);
+    }
+
+    /// Emits an assignment to an already defined variable.
+    pub fn emit_set(&mut self, name: &str, span: Span) {
+        let lookup = self.resolve(name);
+        let idx = self.chunk().add_lookup(lookup);
+        self.emit(OpCode::Set(idx), span);
+    }
+
+    /// Emits a definition of the variable in the innermost scope.
+    pub fn emit_define(&mut self, name: &str, span: Span) {
+        match self.scopes.last_mut() {
+            Some(scope) => {
+                let slot = scope.declare(name.to_string(), true);
+                self.emit(OpCode::Define(slot), span);
+            }
+            None => {
+                let slot = self.globals.borrow_mut().intern(name);
+                self.emit(OpCode::DefineGlobal(slot), span);
+            }
+        }
+    }
+
+    /// Marks a variable of the innermost scope as defined before its definition is emitted.
+    /// Used by function declarations, so they can refer to themselves.
+    pub fn mark_defined(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.declare(name.to_string(), true);
+        }
+    }
+
+    /// Compiles a function body and returns its index in the current chunk.
+    /// The name is only known for function declarations.
+    pub fn compile_function(
+        &mut self,
+        name: Option<String>,
+        params: &[Identifier],
+        body: &Block,
+        span: Span,
+    ) -> usize {
+        let mut scope = Scope::default();
+        let param_slots = params
+            .iter()
+            .map(|param| scope.declare(param.to_string(), true))
+            .collect::<Vec<usize>>();
+        for name in declared_names(&body.statements) {
+            scope.declare(name, false);
+        }
+
+        self.scopes.push(scope);
+        self.chunks.push(Chunk::new());
+        let loops = std::mem::take(&mut self.loops);
+
+        body.compile(self);
+        self.emit(OpCode::Void, body.token.span());
+        self.emit(OpCode::Return, body.token.span());
+
+        self.loops = loops;
+        let chunk = self.chunks.pop().expect("pushed before");
+        let scope = self.scopes.pop().expect("pushed before");
+
+        let params = params.iter().map(|param| param.to_string()).collect();
+        let mut function = FunctionProto::new(params, param_slots, scope.names.len(), chunk);
+        function.name = name;
+        function.source = self.source.clone();
+        function.span = Some(span);
+        self.chunk().add_function(function)
+    }
+
+    /// Compiles a block in its own scope.
+    /// The scope is only created at runtime if the block declares variables.
+    pub fn compile_scoped_block(&mut self, block: &Block) {
+        self.in_block_scope(block, |compiler| block.compile(compiler));
+    }
+
+    /// Compiles a block in its own scope, leaving the value of its last expression on the stack.
+    pub fn compile_block_value(&mut self, block: &Block) {
+        self.in_block_scope(block, |compiler| {
+            compiler.compile_statements_value(&block.statements, block.token.span(), false)
+        });
+    }
+
+    /// Compiles the statements, leaving the value of the last one on the stack.
+    /// Only an expression statement has a value, anything else leaves void.
+    /// At the top level of a program a trailing `if` or `match` is a statement and leaves void too.
+    pub fn compile_statements_value(
+        &mut self,
+        statements: &[Statement],
+        span: Span,
+        top_level: bool,
+    ) {
+        match statements.split_last() {
+            Some((last, rest)) => {
+                for stmt in rest {
+                    stmt.compile(self);
+                }
+
+                match last {
+                    Statement::ExpressionStatement(expr_stmt)
+                        if !top_level || expr_stmt.has_value() =>
+                    {
+                        expr_stmt.expression.compile(self)
+                    }
+                    stmt => {
+                        stmt.compile(self);
+                        self.emit(OpCode::Void, span);
+                    }
+                }
+            }
+            None => {
+                self.emit(OpCode::Void, span);
+            }
+        }
+    }
+
+    /// Opens a scope for the variables declared in the block around the compiled code.
+    fn in_block_scope(&mut self, block: &Block, compile: impl FnOnce(&mut Compiler)) {
+        let names = declared_names(&block.statements);
+        if names.is_empty() {
+            compile(self);
+            return;
+        }
+
+        let mut scope = Scope::default();
+        for name in names {
+            scope.declare(name, false);
+        }
+        let slots = scope.names.len();
+
+        self.scopes.push(scope);
+        self.emit(OpCode::PushScope(slots), block.token.span());
+        compile(self);
+        self.emit(OpCode::PopScope, block.token.span());
+        self.scopes.pop();
+    }
+
+    /// Starts a loop after the next instruction and returns the index of its first instruction.
+    pub fn begin_loop(&mut self, span: Span) -> usize {
+        self.emit(OpCode::EnterLoop, span);
+        let start = self.chunk().code.len();
+        self.loops.push(Loop {
+            start,
+            scopes: self.scopes.len(),
+            breaks: vec![],
+        });
+        start
+    }
+
+    /// Ends the innermost loop, pointing its `break`s at the next instruction.
+    pub fn end_loop(&mut self, span: Span) {
+        let current = self.loops.pop().expect("loop was started");
+        for jump in current.breaks {
+            self.patch_jump(jump);
+        }
+        self.emit(OpCode::ExitLoop, span);
+    }
+
+    /// Emits a jump out of the innermost loop.
+    pub fn emit_break(&mut self, span: Span) {
+        self.emit_loop_exit(span);
+        let jump = self.emit(OpCode::Jump(0), span);
+        self.loops
+            .last_mut()
+            .expect("break outside of a loop is rejected by the parser")
+            .breaks
+            .push(jump);
+    }
+
+    /// Emits a jump back to the condition of the innermost loop.
+    pub fn emit_continue(&mut self, span: Span) {
+        self.emit_loop_exit(span);
+        let start = self
+            .loops
+            .last()
+            .expect("continue outside of a loop is rejected by the parser")
+            .start;
+        self.emit(OpCode::Jump(start), span);
+    }
+
+    /// Drops the operands pushed and closes the scopes opened inside the innermost loop.
+    fn emit_loop_exit(&mut self, span: Span) {
+        self.emit(OpCode::Unwind, span);
+        let outer = self
+            .loops
+            .last()
+            .map(|l| l.scopes)
+            .unwrap_or(self.scopes.len());
+        for _ in outer..self.scopes.len() {
+            self.emit(OpCode::PopScope, span);
+        }
+    }
+
+    /// Compiles a `for` loop body in its own scope, binding the current element to `item`.
+    pub fn compile_for_body(&mut self, item: &Identifier, body: &Block) {
+        let mut scope = Scope::default();
+        let item_slot = scope.declare(item.to_string(), true);
+        for name in declared_names(&body.statements) {
+            scope.declare(name, false);
+        }
+        let slots = scope.names.len();
+
+        self.scopes.push(scope);
+        self.emit(OpCode::PushScope(slots), item.token.span());
+        self.emit(OpCode::Define(item_slot), item.token.span());
+        body.compile(self);
+        self.emit(OpCode::PopScope, body.token.span());
+        self.scopes.pop();
+    }
+
+    /// Compiles a `match` arm testing the pattern against the value on top of the stack.
+    ///
+    /// The names bound by the pattern get the slots of a scope of their own, in order,
+    /// around the guard and the body. The value is popped before the body runs,
+    /// otherwise it's left for the next arm. Returns the jump to the end of the match.
+    pub fn compile_match_arm(
+        &mut self,
+        bindings: Vec<String>,
+        pattern: MatchPattern,
+        guard: Option<&Expression>,
+        body: impl FnOnce(&mut Compiler),
+        span: Span,
+    ) -> usize {
+        let scoped = !bindings.is_empty();
+        if scoped {
+            let mut scope = Scope::default();
+            for name in bindings {
+                scope.declare(name, true);
+            }
+            let slots = scope.names.len();
+            self.scopes.push(scope);
+            self.emit(OpCode::PushScope(slots), span);
+        }
+
+        let idx = self.chunk().add_pattern(pattern);
+        self.emit(OpCode::Match(idx), span);
+        let mut to_next = vec![self.emit(OpCode::JumpIfFalse(0), span)];
+        if let Some(guard) = guard {
+            guard.compile(self);
+            to_next.push(self.emit(OpCode::JumpIfFalse(0), span));
+        }
+
+        self.emit(OpCode::Pop, span);
+        body(self);
+        if scoped {
+            self.emit(OpCode::PopScope, span);
+        }
+        let to_end = self.emit(OpCode::Jump(0), span);
+
+        for jump in to_next {
+            self.patch_jump(jump);
+        }
+        if scoped {
+            self.emit(OpCode::PopScope, span);
+            self.scopes.pop();
+        }
+        to_end
+    }
+
+    /// Finds every binding the name may refer to, from the innermost scope outwards.
+    fn resolve(&mut self, name: &str) -> Lookup {
+        let mut bindings = vec![];
+
+        for (depth, scope) in self.scopes.iter().rev().enumerate() {
+            if let Some(slot) = scope.slot_of(name) {
+                bindings.push(Binding::Local { depth, slot });
+                if scope.initialized[slot] {
+                    return Lookup {
+                        name: name.to_string(),
+                        bindings,
+                    };
+                }
+            }
+        }
+
+        bindings.push(Binding::Global(self.globals.borrow_mut().intern(name)));
+        Lookup {
+            name: name.to_string(),
+            bindings,
+        }
+    }
+}
+
+/// Names of variables declared directly in the statements.
+fn declared_names(statements: &[Statement]) -> Vec<String> {
+    let mut names = vec![];
+    for stmt in statements {
+        match stmt {
+            Statement::LetStatement(let_stmt) => names.push(let_stmt.identifier.to_string()),
+            Statement::FunctionDeclaration(func_decl) => {
+                names.push(func_decl.identifier.to_string())
+            }
+            Statement::Block(block) => names.extend(declared_names(&block.statements)),
+            _ => {}
+        }
+    }
+    names
+}
+
+/// Compiles the code emitted by `compile` as a script and runs it against the globals.
+/// The code must leave the result on the stack.
+#[cfg(test)]
+pub fn eval_compiled(
+    globals: Rc<RefCell<ExecutionEnvironment>>,
+    compile: impl FnOnce(&mut Compiler),
+) -> Result<Rc<Value>, EvalError> {
+    let mut compiler = Compiler::new(Rc::clone(&globals));
+    compile(&mut compiler);
+    compiler.emit(OpCode::Return, Span::point(Position(0, 0)));
+
+    let chunk = compiler.chunks.pop().expect("script chunk");
+    Vm::new(globals).run(Rc::new(FunctionProto::new(vec![], vec![], 0, chunk)))
+}
+
+/// Runs a single expression node and returns its value.
+#[cfg(test)]
+pub fn eval_expression(
+    expression: &impl Compile,
+    globals: Rc<RefCell<ExecutionEnvironment>>,
+) -> Result<Rc<Value>, EvalError> {
+    eval_compiled(globals, |compiler| expression.compile(compiler))
+}
+
+/// Runs a single statement node, returns void unless it returns a value.
+#[cfg(test)]
+pub fn eval_statement(
+    statement: &impl Compile,
+    globals: Rc<RefCell<ExecutionEnvironment>>,
+) -> Result<Rc<Value>, EvalError> {
+    eval_compiled(globals, |compiler| {
+        statement.compile(compiler);
+        compiler.emit(OpCode::Void, Span::point(Position(0, 0)));
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{eval::InfixOp, lexer::Lexer, parser::Parser};
+
+    fn compile(input: &str) -> FunctionProto {
+        let mut parser = Parser::new(Lexer::new(input.to_string()));
+        let program = parser.parse().unwrap();
+        Compiler::new(ExecutionEnvironment::new_global()).compile_program(&program)
+    }
+
+    #[test]
+    fn test_compile_program() {
+        let script = compile("1 + 2; 3");
+
+        assert_eq!(
+            script.chunk.code,
+            vec![
+                OpCode::Constant(0),
+                OpCode::Constant(1),
+                OpCode::Infix(InfixOp::Add),
+                OpCode::Pop,
+                OpCode::Constant(2),
+                OpCode::Return,
+            ]
+        );
+        assert_eq!(script.chunk.constants[2], Value::new_integer(3));
+
+        let script = compile("let x = 1;");
+        assert!(matches!(
+            script.chunk.code[..],
+            [
+                OpCode::Constant(0),
+                OpCode::DefineGlobal(_),
+                OpCode::Void,
+                OpCode::Return
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_compile_function_locals() {
+        let script = compile("fn add(a, b) { let sum = a + b; return sum; }");
+        let add = &script.chunk.functions[0];
+
+        assert_eq!(add.params, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(add.param_slots, vec![0, 1]);
+        assert_eq!(add.slots, 3);
+        assert_eq!(
+            add.chunk.lookups[0].bindings,
+            vec![Binding::Local { depth: 0, slot: 0 }]
+        );
+        assert_eq!(
+            add.chunk.code,
+            vec![
+                OpCode::Get(0),
+                OpCode::Get(1),
+                OpCode::Infix(InfixOp::Add),
+                OpCode::Define(2),
+                OpCode::Get(2),
+                OpCode::Return,
+                OpCode::Void,
+                OpCode::Return,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_uninitialized_local() {
+        let script = compile("let x = 1; fn f() { let y = x; let x = 2; return y; }");
+        let f = &script.chunk.functions[0];
+
+        assert_eq!(f.chunk.lookups[0].name, "x");
+        assert!(matches!(
+            f.chunk.lookups[0].bindings[..],
+            [Binding::Local { depth: 0, slot: 1 }, Binding::Global(_)]
+        ));
+    }
+
+    #[test]
+    fn test_compile_scoped_block() {
+        let script = compile("if (true) { let x = 1; }");
+        assert!(script.chunk.code.contains(&OpCode::PushScope(1)));
+        assert!(script.chunk.code.contains(&OpCode::PopScope));
+
+        let script = compile("if (true) { 1; }");
+        assert!(!script.chunk.code.contains(&OpCode::PopScope));
+    }
+}
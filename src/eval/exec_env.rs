@@ -1,4 +1,4 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, fmt::Debug, rc::Rc};
 
 use super::{builtins, Value};
 
@@ -16,129 +16,227 @@ impl ExecutionEnvironment {
         )))
     }
 
-    pub fn new_local(parent: Rc<RefCell<ExecutionEnvironment>>) -> Rc<RefCell<Self>> {
+    /// Creates a local environment with `slots` empty variable slots.
+    pub fn new_local(parent: Rc<RefCell<ExecutionEnvironment>>, slots: usize) -> Rc<RefCell<Self>> {
         Rc::new(RefCell::new(ExecutionEnvironment::Local(
-            LocalEnvironment::new(parent),
+            LocalEnvironment::new(parent, slots),
         )))
     }
 
-    pub fn assign(&mut self, name: &str, value: Rc<Value>) -> bool {
+    /// Returns the slot index of a global variable, reserving a new slot for unknown names.
+    pub fn intern(&mut self, name: &str) -> usize {
         match self {
-            ExecutionEnvironment::Global(env) => {
-                if env.store.contains_key(name) {
-                    env.store.insert(name.to_string(), value);
+            ExecutionEnvironment::Global(env) => env.intern(name),
+            ExecutionEnvironment::Local(env) => env.parent.borrow_mut().intern(name),
+        }
+    }
+
+    /// Reads a local slot `depth` scopes up from this one.
+    /// Returns `None` if the variable was not defined yet.
+    pub fn get_local(&self, depth: usize, slot: usize) -> Option<Rc<Value>> {
+        match self {
+            ExecutionEnvironment::Global(_) => None,
+            ExecutionEnvironment::Local(env) if depth == 0 => env.slots[slot].clone(),
+            ExecutionEnvironment::Local(env) => env.parent.borrow().get_local(depth - 1, slot),
+        }
+    }
+
+    /// Overwrites an already defined local slot `depth` scopes up from this one.
+    /// Returns `false` if the variable was not defined yet.
+    pub fn assign_local(&mut self, depth: usize, slot: usize, value: Rc<Value>) -> bool {
+        match self {
+            ExecutionEnvironment::Global(_) => false,
+            ExecutionEnvironment::Local(env) if depth == 0 => match &mut env.slots[slot] {
+                Some(stored) => {
+                    *stored = value;
                     true
-                } else {
-                    false
                 }
-            }
+                None => false,
+            },
             ExecutionEnvironment::Local(env) => {
-                if env.store.contains_key(name) {
-                    env.store.insert(name.to_string(), value);
-                    return true;
-                }
+                env.parent.borrow_mut().assign_local(depth - 1, slot, value)
+            }
+        }
+    }
 
-                fn find_and_assign(
-                    current_env: &mut Rc<RefCell<ExecutionEnvironment>>,
-                    name: &str,
-                    value: Rc<Value>,
-                ) -> bool {
-                    match &mut *current_env.borrow_mut() {
-                        ExecutionEnvironment::Global(global_env) => {
-                            if global_env.store.contains_key(name) {
-                                global_env.store.insert(name.to_string(), value);
-                                true
-                            } else {
-                                false
-                            }
-                        }
-                        ExecutionEnvironment::Local(local_env) => {
-                            if local_env.store.contains_key(name) {
-                                local_env.store.insert(name.to_string(), value);
-                                return true;
-                            }
-
-                            find_and_assign(&mut local_env.parent, name, value)
-                        }
-                    }
+    /// Stores a value in a slot of this local environment.
+    pub fn define_local(&mut self, slot: usize, value: Rc<Value>) {
+        match self {
+            ExecutionEnvironment::Global(_) => unreachable!("global scope has no local slots"),
+            ExecutionEnvironment::Local(env) => env.slots[slot] = Some(value),
+        }
+    }
+
+    pub fn get_global(&self, slot: usize) -> Option<Rc<Value>> {
+        match self {
+            ExecutionEnvironment::Global(env) => env.slots[slot].clone(),
+            ExecutionEnvironment::Local(env) => env.parent.borrow().get_global(slot),
+        }
+    }
+
+    /// Overwrites an already defined global slot.
+    /// Returns `false` if the variable was not defined yet.
+    pub fn assign_global(&mut self, slot: usize, value: Rc<Value>) -> bool {
+        match self {
+            ExecutionEnvironment::Global(env) => match &mut env.slots[slot] {
+                Some(stored) => {
+                    *stored = value;
+                    true
                 }
+                None => false,
+            },
+            ExecutionEnvironment::Local(env) => env.parent.borrow_mut().assign_global(slot, value),
+        }
+    }
 
-                find_and_assign(&mut env.parent, name, value)
-            }
+    pub fn define_global(&mut self, slot: usize, value: Rc<Value>) {
+        match self {
+            ExecutionEnvironment::Global(env) => env.slots[slot] = Some(value),
+            ExecutionEnvironment::Local(env) => env.parent.borrow_mut().define_global(slot, value),
+        }
+    }
+
+    /// Returns the parent of a local environment.
+    pub fn parent(&self) -> Option<Rc<RefCell<ExecutionEnvironment>>> {
+        match self {
+            ExecutionEnvironment::Global(_) => None,
+            ExecutionEnvironment::Local(env) => Some(Rc::clone(&env.parent)),
         }
     }
 }
 
+/// Name based access always addresses the global scope.
+/// Local variables are resolved to slots at compile time.
 impl Environment for ExecutionEnvironment {
     fn get(&self, name: &str) -> Option<Rc<Value>> {
         match self {
             ExecutionEnvironment::Global(env) => env.get(name),
-            ExecutionEnvironment::Local(env) => env.get(name),
+            ExecutionEnvironment::Local(env) => env.parent.borrow().get(name),
         }
     }
 
     fn set(&mut self, name: String, value: Rc<Value>) {
         match self {
             ExecutionEnvironment::Global(env) => env.set(name, value),
-            ExecutionEnvironment::Local(env) => env.set(name, value),
+            ExecutionEnvironment::Local(env) => env.parent.borrow_mut().set(name, value),
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(PartialEq)]
 pub struct GlobalEnvironment {
-    store: HashMap<String, Rc<Value>>,
+    /// Slot index of every name the compiler has seen.
+    names: HashMap<String, usize>,
+    slots: Vec<Option<Rc<Value>>>,
 }
 
 impl GlobalEnvironment {
     pub fn new() -> Self {
         let mut env = GlobalEnvironment {
-            store: HashMap::new(),
+            names: HashMap::new(),
+            slots: vec![],
         };
         builtins::register_builtins(&mut env);
         env
     }
+
+    pub fn intern(&mut self, name: &str) -> usize {
+        if let Some(slot) = self.names.get(name) {
+            return *slot;
+        }
+        self.slots.push(None);
+        self.names.insert(name.to_string(), self.slots.len() - 1);
+        self.slots.len() - 1
+    }
 }
 
 impl Environment for GlobalEnvironment {
     fn get(&self, name: &str) -> Option<Rc<Value>> {
-        self.store.get(name).cloned()
+        self.names
+            .get(name)
+            .and_then(|slot| self.slots[*slot].clone())
     }
 
     fn set(&mut self, name: String, value: Rc<Value>) {
-        self.store.insert(name, value);
+        let slot = self.intern(&name);
+        self.slots[slot] = Some(value);
+    }
+}
+
+impl Debug for GlobalEnvironment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut names = self.names.iter().collect::<Vec<_>>();
+        names.sort_by_key(|(_, slot)| **slot);
+
+        let mut store = f.debug_map();
+        for (name, slot) in names {
+            if let Some(value) = &self.slots[*slot] {
+                store.entry(name, value);
+            }
+        }
+        store.finish()
     }
 }
 
 #[derive(Debug, PartialEq)]
 pub struct LocalEnvironment {
-    store: HashMap<String, Rc<Value>>,
+    slots: Vec<Option<Rc<Value>>>,
     pub parent: Rc<RefCell<ExecutionEnvironment>>,
 }
 
 impl LocalEnvironment {
-    pub fn new(parent: Rc<RefCell<ExecutionEnvironment>>) -> Self {
+    pub fn new(parent: Rc<RefCell<ExecutionEnvironment>>, slots: usize) -> Self {
         LocalEnvironment {
-            store: HashMap::new(),
+            slots: vec![None; slots],
             parent,
         }
     }
 }
 
-impl Environment for LocalEnvironment {
-    fn get(&self, name: &str) -> Option<Rc<Value>> {
-        self.store
-            .get(name)
-            .cloned() // clones inner rc value
-            .or_else(|| self.parent.borrow().get(name))
-    }
-
-    fn set(&mut self, name: String, value: Rc<Value>) {
-        self.store.insert(name, value);
-    }
-}
-
 pub trait Environment {
     fn get(&self, name: &str) -> Option<Rc<Value>>;
     fn set(&mut self, name: String, value: Rc<Value>);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_global_names() {
+        let env = ExecutionEnvironment::new_global();
+        let slot = env.borrow_mut().intern("x");
+
+        assert_eq!(env.borrow_mut().intern("x"), slot);
+        assert_eq!(env.borrow().get("x"), None);
+        assert!(!env.borrow_mut().assign_global(slot, Value::new_integer(1)));
+
+        env.borrow_mut().set("x".to_string(), Value::new_integer(2));
+        assert_eq!(env.borrow().get_global(slot), Some(Value::new_integer(2)));
+        assert!(env.borrow_mut().assign_global(slot, Value::new_integer(3)));
+        assert_eq!(env.borrow().get("x"), Some(Value::new_integer(3)));
+    }
+
+    #[test]
+    fn test_local_slots() {
+        let global = ExecutionEnvironment::new_global();
+        let outer = ExecutionEnvironment::new_local(Rc::clone(&global), 1);
+        let inner = ExecutionEnvironment::new_local(Rc::clone(&outer), 2);
+
+        outer.borrow_mut().define_local(0, Value::new_integer(1));
+        inner.borrow_mut().define_local(1, Value::new_integer(2));
+
+        assert_eq!(inner.borrow().get_local(1, 0), Some(Value::new_integer(1)));
+        assert_eq!(inner.borrow().get_local(0, 1), Some(Value::new_integer(2)));
+        assert_eq!(inner.borrow().get_local(0, 0), None);
+
+        assert!(inner.borrow_mut().assign_local(1, 0, Value::new_integer(3)));
+        assert!(!inner.borrow_mut().assign_local(0, 0, Value::new_integer(3)));
+        assert_eq!(outer.borrow().get_local(0, 0), Some(Value::new_integer(3)));
+
+        inner
+            .borrow_mut()
+            .set("y".to_string(), Value::new_integer(4));
+        assert_eq!(global.borrow().get("y"), Some(Value::new_integer(4)));
+    }
+}
@@ -1,14 +1,21 @@
 mod builtins;
+mod bytecode;
+mod compiler;
 mod exec_env;
 mod values;
+mod vm;
+
+use std::{fmt::Display, rc::Rc};
 
-use std::{cell::RefCell, rc::Rc};
 use thiserror::Error;
 
+pub use bytecode::*;
+pub use compiler::*;
 pub use exec_env::*;
 pub use values::*;
+pub use vm::*;
 
-use crate::lexer::Position;
+use crate::lexer::{Position, Span};
 
 #[derive(Debug, Error)]
 pub enum EvalError {
@@ -33,14 +40,134 @@ pub enum EvalError {
     #[error("Non-boolean condition: want true or false, got {0} at {1}")]
     NonBooleanCondition(String, Position),
     #[error("Index out of bounds: index {0}, while length {1} at {2}")]
-    IndexOutOfBounds(usize, usize, Position),
+    IndexOutOfBounds(i64, usize, Position),
+    #[error("Invalid range: {0}..{1} at {2}")]
+    InvalidRange(String, String, Position),
+    #[error("Not iterable: {0} at {1}")]
+    NotIterable(String, Position),
+    #[error("Invalid map key: {0} at {1}")]
+    InvalidMapKey(String, Position),
+    #[error("Key not found: {0} at {1}")]
+    KeyNotFound(String, Position),
+    #[error("Stack overflow: call depth exceeded {0} at {1}")]
+    StackOverflow(usize, Position),
+    #[error("Non-exhaustive match: no arm matches {0} at {1}")]
+    NonExhaustiveMatch(String, Position),
 
     #[error("Wrong number of arguments: {0}: expected: {1}, got {2} at {3}")]
     BuiltinWrongNumberOfArguments(String, usize, usize, Position),
+    #[error("Wrong number of arguments: {0}: expected: {1} to {2}, got {3} at {4}")]
+    BuiltinWrongNumberOfArgumentsRange(String, usize, usize, usize, Position),
     #[error("Type mismatch: {0}: expected: {1}, got {2} at {3}")]
     BuiltinWrongArgumentType(String, String, String, Position),
+
+    /// Error raised inside function calls, with the calls that led to it, innermost first.
+    #[error("{0}")]
+    WithStack(Box<EvalError>, Vec<StackFrame>),
+    /// Error with the location of the instruction that raised it.
+    #[error("{0}")]
+    Located(Box<EvalError>, Location),
+}
+
+/// Source range of the instruction that raised an error and the source it belongs to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub span: Span,
+    /// `None` for code compiled without its source.
+    pub source: Option<Rc<str>>,
 }
 
-pub trait Eval {
-    fn eval(&self, env: Rc<RefCell<ExecutionEnvironment>>) -> Result<Rc<Value>, EvalError>;
+/// Function call that was active when an error was raised.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackFrame {
+    /// Function name, `<anonymous>` for function expressions.
+    pub name: String,
+    pub call_site: Position,
+    pub builtin: bool,
+}
+
+impl Display for StackFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.builtin {
+            write!(f, "in builtin {} called at {}", self.name, self.call_site)
+        } else {
+            write!(f, "in {} called at {}", self.name, self.call_site)
+        }
+    }
+}
+
+impl EvalError {
+    pub fn position(&self) -> Position {
+        match self {
+            EvalError::TriedToStoreVoid(position)
+            | EvalError::NotDefined(_, position)
+            | EvalError::InvalidPrefixOperation(_, _, position)
+            | EvalError::InvalidInfixOperation(_, _, _, position)
+            | EvalError::InvalidIndexExpression(_, _, position)
+            | EvalError::DivisionByZero(_, _, position)
+            | EvalError::VoidValueAsArgument(position)
+            | EvalError::NotAFunction(position)
+            | EvalError::WrongNumberOfArguments(_, _, position)
+            | EvalError::NonBooleanCondition(_, position)
+            | EvalError::IndexOutOfBounds(_, _, position)
+            | EvalError::InvalidRange(_, _, position)
+            | EvalError::NotIterable(_, position)
+            | EvalError::InvalidMapKey(_, position)
+            | EvalError::KeyNotFound(_, position)
+            | EvalError::StackOverflow(_, position)
+            | EvalError::NonExhaustiveMatch(_, position)
+            | EvalError::BuiltinWrongNumberOfArguments(_, _, _, position)
+            | EvalError::BuiltinWrongNumberOfArgumentsRange(_, _, _, _, position)
+            | EvalError::BuiltinWrongArgumentType(_, _, _, position) => *position,
+            EvalError::WithStack(err, _) | EvalError::Located(err, _) => err.position(),
+        }
+    }
+
+    /// Where the error was raised, known once it has left the virtual machine.
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            EvalError::Located(_, location) => Some(location),
+            _ => None,
+        }
+    }
+
+    /// Error without the call stack.
+    pub fn root(&self) -> &EvalError {
+        match self {
+            EvalError::WithStack(err, _) => err,
+            EvalError::Located(err, _) => err.root(),
+            err => err,
+        }
+    }
+
+    /// Calls that led to the error, innermost first.
+    pub fn stack(&self) -> &[StackFrame] {
+        match self {
+            EvalError::WithStack(_, stack) => stack,
+            EvalError::Located(err, _) => err.stack(),
+            _ => &[],
+        }
+    }
+
+    /// Records where the error was raised, unless it already is.
+    pub fn at(self, location: Location) -> Self {
+        match self {
+            err @ EvalError::Located(..) => err,
+            err => EvalError::Located(Box::new(err), location),
+        }
+    }
+
+    /// Adds a call outside of the ones already recorded.
+    pub fn with_frame(self, frame: StackFrame) -> Self {
+        match self {
+            EvalError::WithStack(err, mut stack) => {
+                stack.push(frame);
+                EvalError::WithStack(err, stack)
+            }
+            EvalError::Located(err, location) => {
+                EvalError::Located(Box::new(err.with_frame(frame)), location)
+            }
+            err => EvalError::WithStack(Box::new(err), vec![frame]),
+        }
+    }
 }
@@ -0,0 +1,273 @@
+use std::{fmt::Debug, rc::Rc};
+
+use crate::{eval::EvalError, lexer::Position};
+
+use super::{FromValue, IntoValueResult, Value};
+
+/// Native function callable from scripts.
+///
+/// Closures may capture host state. Use `Cell` or `RefCell` for state they mutate.
+pub type BuiltinFn = dyn Fn(Vec<Rc<Value>>, &BuiltinCall) -> Result<Rc<Value>, EvalError>;
+
+pub struct Builtin {
+    pub name: String,
+    func: Box<BuiltinFn>,
+}
+
+impl Builtin {
+    pub fn new<F>(name: &str, func: F) -> Self
+    where
+        F: Fn(Vec<Rc<Value>>, &BuiltinCall) -> Result<Rc<Value>, EvalError> + 'static,
+    {
+        Builtin {
+            name: name.to_string(),
+            func: Box::new(func),
+        }
+    }
+
+    /// Wraps a plain Rust function, see `IntoBuiltin`.
+    pub fn from_fn<Args>(name: &str, func: impl IntoBuiltin<Args>) -> Self {
+        func.into_builtin(name)
+    }
+
+    pub fn call(&self, args: Vec<Rc<Value>>, position: Position) -> Result<Rc<Value>, EvalError> {
+        let call = BuiltinCall {
+            name: &self.name,
+            position,
+        };
+        (self.func)(args, &call)
+    }
+}
+
+/// Builtins are equal only if they are the same registered function.
+impl PartialEq for Builtin {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Debug for Builtin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Builtin").field("name", &self.name).finish()
+    }
+}
+
+/// Details of a single builtin call, used to build errors that name the builtin.
+#[derive(Debug)]
+pub struct BuiltinCall<'a> {
+    pub name: &'a str,
+    pub position: Position,
+}
+
+impl BuiltinCall<'_> {
+    pub fn wrong_number_of_arguments(&self, expected: usize, got: usize) -> EvalError {
+        EvalError::BuiltinWrongNumberOfArguments(
+            self.name.to_string(),
+            expected,
+            got,
+            self.position,
+        )
+    }
+
+    /// Arity error of a builtin with optional trailing arguments.
+    pub fn wrong_number_of_arguments_between(
+        &self,
+        min: usize,
+        max: usize,
+        got: usize,
+    ) -> EvalError {
+        if min == max {
+            return self.wrong_number_of_arguments(max, got);
+        }
+        EvalError::BuiltinWrongNumberOfArgumentsRange(
+            self.name.to_string(),
+            min,
+            max,
+            got,
+            self.position,
+        )
+    }
+
+    pub fn wrong_argument_type(&self, expected: &str, got: &Value) -> EvalError {
+        EvalError::BuiltinWrongArgumentType(
+            self.name.to_string(),
+            expected.to_string(),
+            got.to_string(),
+            self.position,
+        )
+    }
+
+    pub fn wrong_type_for<T: FromValue>(&self, got: &Value) -> EvalError {
+        self.wrong_argument_type(&T::type_name(), got)
+    }
+}
+
+/// Rust function that can be wrapped as a builtin.
+///
+/// Implemented for functions whose parameters are `FromValue` and whose return type is
+/// `IntoValue` or `Result` of it. Arity and type errors are generated from the signature.
+/// Trailing `Option` parameters may be left out and receive `None`.
+pub trait IntoBuiltin<Args> {
+    fn into_builtin(self, name: &str) -> Builtin;
+}
+
+macro_rules! impl_into_builtin {
+    ($count:expr; $($arg:ident),*) => {
+        impl<Func, Ret, $($arg),*> IntoBuiltin<($($arg,)*)> for Func
+        where
+            Func: Fn($($arg),*) -> Ret + 'static,
+            Ret: IntoValueResult,
+            $($arg: FromValue,)*
+        {
+            #[allow(non_snake_case, unused_mut, unused_variables)]
+            fn into_builtin(self, name: &str) -> Builtin {
+                // the arguments after the last required one may be left out
+                let required: [bool; $count] = [$($arg::missing().is_none()),*];
+                let min = required.iter().rposition(|r| *r).map_or(0, |idx| idx + 1);
+
+                Builtin::new(name, move |args, call| {
+                    if !(min..=$count).contains(&args.len()) {
+                        return Err(call.wrong_number_of_arguments_between(min, $count, args.len()));
+                    }
+                    let mut args = args.iter();
+                    $(
+                        let $arg = match args.next() {
+                            Some(value) => $arg::from_value(value)
+                                .ok_or_else(|| call.wrong_type_for::<$arg>(value))?,
+                            None => $arg::missing().expect("checked by the arity"),
+                        };
+                    )*
+                    (self)($($arg),*).into_value_result()
+                })
+            }
+        }
+    };
+}
+
+impl_into_builtin!(0;);
+impl_into_builtin!(1; A);
+impl_into_builtin!(2; A, B);
+impl_into_builtin!(3; A, B, C);
+impl_into_builtin!(4; A, B, C, D);
+impl_into_builtin!(5; A, B, C, D, E);
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[test]
+    fn test_builtin_call() {
+        let calls = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&calls);
+        let builtin = Builtin::new("count", move |args, call| {
+            if !args.is_empty() {
+                return Err(call.wrong_number_of_arguments(0, args.len()));
+            }
+            counter.set(counter.get() + 1);
+            Ok(Value::new_integer(counter.get()))
+        });
+
+        assert_eq!(
+            builtin.call(vec![], Position(1, 1)).unwrap(),
+            Value::new_integer(1)
+        );
+        assert_eq!(
+            builtin.call(vec![], Position(1, 1)).unwrap(),
+            Value::new_integer(2)
+        );
+        assert_eq!(calls.get(), 2);
+
+        let err = builtin
+            .call(vec![Value::new_integer(1)], Position(2, 3))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Wrong number of arguments: count: expected: 0, got 1 at line:2, col:3"
+        );
+        assert_eq!(builtin, builtin);
+    }
+
+    #[test]
+    fn test_builtin_from_fn() {
+        let builtin = Builtin::from_fn("add", |a: i64, b: i64| a + b);
+        assert_eq!(
+            builtin
+                .call(
+                    vec![Value::new_integer(1), Value::new_integer(2)],
+                    Position(1, 1)
+                )
+                .unwrap(),
+            Value::new_integer(3)
+        );
+
+        let err = builtin
+            .call(
+                vec![Value::new_integer(1), Value::from_native_bool(true)],
+                Position(1, 4),
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Type mismatch: add: expected: Integer, got true at line:1, col:4"
+        );
+
+        let checked = Builtin::from_fn("checked", |n: i64| {
+            if n < 0 {
+                Err(EvalError::NotAFunction(Position(0, 0)))
+            } else {
+                Ok(n)
+            }
+        });
+        assert!(checked
+            .call(vec![Value::new_integer(-1)], Position(1, 1))
+            .is_err());
+        assert_eq!(
+            checked
+                .call(vec![], Position(1, 1))
+                .unwrap_err()
+                .to_string(),
+            "Wrong number of arguments: checked: expected: 1, got 0 at line:1, col:1"
+        );
+
+        let pad = Builtin::from_fn(
+            "pad",
+            |s: String, width: Option<i64>, fill: Option<String>| {
+                let fill = fill.unwrap_or_else(|| " ".to_string());
+                let missing = (width.unwrap_or(0) as usize).saturating_sub(s.len());
+                format!("{}{s}", fill.repeat(missing))
+            },
+        );
+        let call = |args: Vec<Rc<Value>>| pad.call(args, Position(1, 1));
+        let text = |s: &str| Value::new_string(s.to_string());
+
+        assert_eq!(call(vec![text("ab")]).unwrap(), text("ab"));
+        assert_eq!(
+            call(vec![text("ab"), Value::new_integer(4)]).unwrap(),
+            text("  ab")
+        );
+        assert_eq!(
+            call(vec![text("ab"), Value::new_integer(3), text("*")]).unwrap(),
+            text("*ab")
+        );
+        assert_eq!(
+            call(vec![]).unwrap_err().to_string(),
+            "Wrong number of arguments: pad: expected: 1 to 3, got 0 at line:1, col:1"
+        );
+        assert_eq!(
+            call(vec![text("ab"), text("4")]).unwrap_err().to_string(),
+            "Type mismatch: pad: expected: Integer, got 4 at line:1, col:1"
+        );
+
+        // an optional parameter before a required one must be passed
+        let between = Builtin::from_fn("between", |_: Option<i64>, b: i64| b);
+        assert_eq!(
+            between
+                .call(vec![Value::new_integer(1)], Position(1, 1))
+                .unwrap_err()
+                .to_string(),
+            "Wrong number of arguments: between: expected: 2, got 1 at line:1, col:1"
+        );
+    }
+}
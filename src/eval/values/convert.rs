@@ -0,0 +1,412 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+    rc::Rc,
+};
+
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+
+use crate::eval::EvalError;
+
+use super::{MapKey, Value, VOID};
+
+/// Conversion from a script value to a Rust type.
+pub trait FromValue: Sized {
+    /// Name of the expected type, used in type mismatch errors.
+    fn type_name() -> String;
+
+    /// Returns `None` if the value doesn't have the expected type.
+    fn from_value(value: &Rc<Value>) -> Option<Self>;
+
+    /// Value of a trailing argument left out of a builtin call, `None` if it's required.
+    fn missing() -> Option<Self> {
+        None
+    }
+}
+
+/// Conversion from a Rust type to a script value.
+pub trait IntoValue {
+    fn into_value(self) -> Rc<Value>;
+}
+
+/// Return type of a native function, either a plain value or a `Result`.
+pub trait IntoValueResult {
+    fn into_value_result(self) -> Result<Rc<Value>, EvalError>;
+}
+
+impl<T: IntoValue> IntoValueResult for T {
+    fn into_value_result(self) -> Result<Rc<Value>, EvalError> {
+        Ok(self.into_value())
+    }
+}
+
+impl<T: IntoValue> IntoValueResult for Result<T, EvalError> {
+    fn into_value_result(self) -> Result<Rc<Value>, EvalError> {
+        self.map(IntoValue::into_value)
+    }
+}
+
+/// Rust type that can be used as a map key.
+pub trait MapKeyType: Sized {
+    fn from_key(key: &MapKey) -> Option<Self>;
+    fn into_key(self) -> MapKey;
+}
+
+impl FromValue for Rc<Value> {
+    fn type_name() -> String {
+        "Any".to_string()
+    }
+
+    fn from_value(value: &Rc<Value>) -> Option<Self> {
+        Some(Rc::clone(value))
+    }
+}
+
+impl IntoValue for Rc<Value> {
+    fn into_value(self) -> Rc<Value> {
+        self
+    }
+}
+
+impl IntoValue for () {
+    fn into_value(self) -> Rc<Value> {
+        VOID.rc()
+    }
+}
+
+impl FromValue for i64 {
+    fn type_name() -> String {
+        "Integer".to_string()
+    }
+
+    fn from_value(value: &Rc<Value>) -> Option<Self> {
+        match **value {
+            Value::Integer(i) => Some(i),
+            _ => None,
+        }
+    }
+}
+
+impl IntoValue for i64 {
+    fn into_value(self) -> Rc<Value> {
+        Value::new_integer(self)
+    }
+}
+
+/// Integers of any size are accepted.
+impl FromValue for BigInt {
+    fn type_name() -> String {
+        "Integer".to_string()
+    }
+
+    fn from_value(value: &Rc<Value>) -> Option<Self> {
+        match &**value {
+            Value::Integer(i) => Some(BigInt::from(*i)),
+            Value::BigInt(i) => Some(i.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl IntoValue for BigInt {
+    fn into_value(self) -> Rc<Value> {
+        Value::new_bigint(self)
+    }
+}
+
+/// Integers of any size are accepted as floats, rounded to the nearest one.
+impl FromValue for f64 {
+    fn type_name() -> String {
+        "Float".to_string()
+    }
+
+    fn from_value(value: &Rc<Value>) -> Option<Self> {
+        match **value {
+            Value::Float(f) => Some(f),
+            Value::Integer(i) => Some(i as f64),
+            Value::BigInt(ref i) => i.to_f64(),
+            _ => None,
+        }
+    }
+}
+
+impl IntoValue for f64 {
+    fn into_value(self) -> Rc<Value> {
+        Value::new_float(self)
+    }
+}
+
+impl FromValue for bool {
+    fn type_name() -> String {
+        "Boolean".to_string()
+    }
+
+    fn from_value(value: &Rc<Value>) -> Option<Self> {
+        match **value {
+            Value::Boolean(b) => Some(b),
+            _ => None,
+        }
+    }
+}
+
+impl IntoValue for bool {
+    fn into_value(self) -> Rc<Value> {
+        Value::from_native_bool(self)
+    }
+}
+
+impl FromValue for String {
+    fn type_name() -> String {
+        "String".to_string()
+    }
+
+    fn from_value(value: &Rc<Value>) -> Option<Self> {
+        match &**value {
+            Value::String(s) => Some(s.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl IntoValue for String {
+    fn into_value(self) -> Rc<Value> {
+        Value::new_string(self)
+    }
+}
+
+impl IntoValue for &str {
+    fn into_value(self) -> Rc<Value> {
+        Value::new_string(self.to_string())
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn type_name() -> String {
+        format!("Array of {}", T::type_name())
+    }
+
+    fn from_value(value: &Rc<Value>) -> Option<Self> {
+        match &**value {
+            Value::Array(arr) => arr.borrow().iter().map(T::from_value).collect(),
+            _ => None,
+        }
+    }
+}
+
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn into_value(self) -> Rc<Value> {
+        Value::new_array(self.into_iter().map(IntoValue::into_value).collect())
+    }
+}
+
+/// Optional trailing argument of a builtin, `None` when it's left out.
+impl<T: FromValue> FromValue for Option<T> {
+    fn type_name() -> String {
+        T::type_name()
+    }
+
+    fn from_value(value: &Rc<Value>) -> Option<Self> {
+        T::from_value(value).map(Some)
+    }
+
+    fn missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn into_value(self) -> Rc<Value> {
+        match self {
+            Some(value) => value.into_value(),
+            None => VOID.rc(),
+        }
+    }
+}
+
+impl MapKeyType for MapKey {
+    fn from_key(key: &MapKey) -> Option<Self> {
+        Some(key.clone())
+    }
+
+    fn into_key(self) -> MapKey {
+        self
+    }
+}
+
+impl MapKeyType for i64 {
+    fn from_key(key: &MapKey) -> Option<Self> {
+        match key {
+            MapKey::Integer(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    fn into_key(self) -> MapKey {
+        MapKey::Integer(self)
+    }
+}
+
+impl MapKeyType for BigInt {
+    fn from_key(key: &MapKey) -> Option<Self> {
+        match key {
+            MapKey::Integer(i) => Some(BigInt::from(*i)),
+            MapKey::BigInt(i) => Some(i.clone()),
+            _ => None,
+        }
+    }
+
+    fn into_key(self) -> MapKey {
+        match self.to_i64() {
+            Some(i) => MapKey::Integer(i),
+            None => MapKey::BigInt(self),
+        }
+    }
+}
+
+impl MapKeyType for bool {
+    fn from_key(key: &MapKey) -> Option<Self> {
+        match key {
+            MapKey::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    fn into_key(self) -> MapKey {
+        MapKey::Boolean(self)
+    }
+}
+
+impl MapKeyType for String {
+    fn from_key(key: &MapKey) -> Option<Self> {
+        match key {
+            MapKey::String(s) => Some(s.clone()),
+            _ => None,
+        }
+    }
+
+    fn into_key(self) -> MapKey {
+        MapKey::String(self)
+    }
+}
+
+impl<K: MapKeyType + Ord, V: FromValue> FromValue for BTreeMap<K, V> {
+    fn type_name() -> String {
+        format!("Map of {}", V::type_name())
+    }
+
+    fn from_value(value: &Rc<Value>) -> Option<Self> {
+        match &**value {
+            Value::Map(map) => map
+                .borrow()
+                .iter()
+                .map(|(key, value)| Some((K::from_key(key)?, V::from_value(value)?)))
+                .collect(),
+            _ => None,
+        }
+    }
+}
+
+impl<K: MapKeyType, V: IntoValue> IntoValue for BTreeMap<K, V> {
+    fn into_value(self) -> Rc<Value> {
+        Value::new_map(
+            self.into_iter()
+                .map(|(key, value)| (key.into_key(), value.into_value()))
+                .collect(),
+        )
+    }
+}
+
+impl<K: MapKeyType + Eq + Hash, V: FromValue> FromValue for HashMap<K, V> {
+    fn type_name() -> String {
+        format!("Map of {}", V::type_name())
+    }
+
+    fn from_value(value: &Rc<Value>) -> Option<Self> {
+        match &**value {
+            Value::Map(map) => map
+                .borrow()
+                .iter()
+                .map(|(key, value)| Some((K::from_key(key)?, V::from_value(value)?)))
+                .collect(),
+            _ => None,
+        }
+    }
+}
+
+impl<K: MapKeyType, V: IntoValue> IntoValue for HashMap<K, V> {
+    fn into_value(self) -> Rc<Value> {
+        Value::new_map(
+            self.into_iter()
+                .map(|(key, value)| (key.into_key(), value.into_value()))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_value() {
+        assert_eq!(i64::from_value(&Value::new_integer(3)), Some(3));
+        assert_eq!(i64::from_value(&Value::new_float(3.0)), None);
+        assert_eq!(f64::from_value(&Value::new_integer(3)), Some(3.0));
+        assert_eq!(
+            f64::from_value(&(BigInt::from(i64::MAX) * 4i64).into_value()),
+            Some(i64::MAX as f64 * 4.0)
+        );
+        assert_eq!(
+            BigInt::from_value(&Value::new_integer(3)),
+            Some(BigInt::from(3))
+        );
+        let big = (BigInt::from(i64::MAX) + 1i64).into_value();
+        assert_eq!(i64::from_value(&big), None);
+        assert_eq!(
+            BigInt::from_value(&big),
+            Some(BigInt::from(i64::MAX) + 1i64)
+        );
+        assert_eq!(bool::from_value(&Value::from_native_bool(true)), Some(true));
+        assert_eq!(
+            String::from_value(&Value::new_string("a".to_string())),
+            Some("a".to_string())
+        );
+
+        let arr = vec![1i64, 2].into_value();
+        assert_eq!(Vec::<i64>::from_value(&arr), Some(vec![1, 2]));
+        assert_eq!(Vec::<String>::from_value(&arr), None);
+
+        assert_eq!(i64::missing(), None);
+        assert_eq!(Option::<i64>::missing(), Some(None));
+        assert_eq!(
+            Option::<i64>::from_value(&Value::new_integer(1)),
+            Some(Some(1))
+        );
+        assert_eq!(
+            Option::<i64>::from_value(&Value::new_string(String::new())),
+            None
+        );
+        assert_eq!(Option::<i64>::missing(), Some(None));
+        assert_eq!(i64::missing(), None);
+
+        assert!(Some(1i64).into_value() == Value::new_integer(1));
+        assert!(None::<i64>.into_value().is_void());
+
+        let map = BTreeMap::from([("a".to_string(), 1i64), ("b".to_string(), 2)]).into_value();
+        assert_eq!(map.to_string(), "{a: 1, b: 2}");
+        assert_eq!(
+            HashMap::<String, i64>::from_value(&map),
+            Some(HashMap::from([("a".to_string(), 1), ("b".to_string(), 2)]))
+        );
+        assert_eq!(BTreeMap::<i64, i64>::from_value(&map), None);
+    }
+
+    #[test]
+    fn test_type_names() {
+        assert_eq!(i64::type_name(), "Integer");
+        assert_eq!(Vec::<String>::type_name(), "Array of String");
+        assert_eq!(Option::<String>::type_name(), "String");
+        assert_eq!(BTreeMap::<String, f64>::type_name(), "Map of Float");
+    }
+}
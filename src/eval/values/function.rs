@@ -1,34 +1,34 @@
 use std::{cell::RefCell, fmt::Debug, rc::Rc};
 
-use crate::{eval::ExecutionEnvironment, parser::Block};
+use crate::eval::{ExecutionEnvironment, FunctionProto};
 
-#[derive(PartialEq)]
 pub struct Function {
     pub captured_env: Rc<RefCell<ExecutionEnvironment>>,
-    pub params: Vec<String>,
-    pub body: Rc<Block>,
+    pub proto: Rc<FunctionProto>,
 }
 
 impl Function {
-    pub fn new(
-        params: Vec<String>,
-        body: Rc<Block>,
-        env: Rc<RefCell<ExecutionEnvironment>>,
-    ) -> Self {
+    pub fn new(proto: Rc<FunctionProto>, env: Rc<RefCell<ExecutionEnvironment>>) -> Self {
         Function {
             captured_env: env,
-            params,
-            body,
+            proto,
         }
     }
 }
 
+/// Functions are equal only if they are the same closure of the same code.
+impl PartialEq for Function {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.proto, &other.proto) && Rc::ptr_eq(&self.captured_env, &other.captured_env)
+    }
+}
+
 impl Debug for Function {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Function")
             .field("captured_env", &self.captured_env.as_ptr())
-            .field("params", &self.params)
-            .field("body", &Rc::as_ptr(&self.body))
+            .field("params", &self.proto.params)
+            .field("proto", &Rc::as_ptr(&self.proto))
             .finish()
     }
 }
@@ -0,0 +1,133 @@
+use std::{cmp::Ordering, fmt::Display};
+
+use num_bigint::{BigInt, Sign};
+use num_traits::ToPrimitive;
+
+use super::Value;
+
+/// Value that can be used as a map key.
+///
+/// The ordering keeps map iteration and printing deterministic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapKey {
+    Boolean(bool),
+    Integer(i64),
+    /// Integer outside of the `i64` range, like `Value::BigInt`.
+    BigInt(BigInt),
+    String(String),
+}
+
+impl MapKey {
+    /// Converts a value to a key. Returns `None` for values that can't be keys.
+    pub fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Boolean(b) => Some(MapKey::Boolean(*b)),
+            Value::Integer(i) => Some(MapKey::Integer(*i)),
+            Value::BigInt(i) => Some(match i.to_i64() {
+                Some(i) => MapKey::Integer(i),
+                None => MapKey::BigInt(i.clone()),
+            }),
+            Value::String(s) => Some(MapKey::String(s.clone())),
+            _ => None,
+        }
+    }
+
+    /// Booleans come first, then integers, then strings.
+    fn rank(&self) -> u8 {
+        match self {
+            MapKey::Boolean(_) => 0,
+            MapKey::Integer(_) | MapKey::BigInt(_) => 1,
+            MapKey::String(_) => 2,
+        }
+    }
+}
+
+/// Integer keys are ordered by value, whether they are `Integer` or `BigInt`.
+impl Ord for MapKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (MapKey::Boolean(left), MapKey::Boolean(right)) => left.cmp(right),
+            (MapKey::Integer(left), MapKey::Integer(right)) => left.cmp(right),
+            (MapKey::BigInt(left), MapKey::BigInt(right)) => left.cmp(right),
+            // a `BigInt` is below or above every `i64`, depending on its sign
+            (MapKey::Integer(_), MapKey::BigInt(right)) => match right.sign() {
+                Sign::Minus => Ordering::Greater,
+                _ => Ordering::Less,
+            },
+            (MapKey::BigInt(_), MapKey::Integer(_)) => other.cmp(self).reverse(),
+            (MapKey::String(left), MapKey::String(right)) => left.cmp(right),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl PartialOrd for MapKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for MapKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapKey::Boolean(b) => write!(f, "{b}"),
+            MapKey::Integer(i) => write!(f, "{i}"),
+            MapKey::BigInt(i) => write!(f, "{i}"),
+            MapKey::String(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_key_from_value() {
+        assert_eq!(
+            MapKey::from_value(&Value::Integer(1)),
+            Some(MapKey::Integer(1))
+        );
+        assert_eq!(
+            MapKey::from_value(&Value::String("a".to_string())),
+            Some(MapKey::String("a".to_string()))
+        );
+        assert_eq!(
+            MapKey::from_value(&Value::BigInt(BigInt::from(i64::MAX) + 1)),
+            Some(MapKey::BigInt(BigInt::from(i64::MAX) + 1))
+        );
+        assert_eq!(
+            MapKey::from_value(&Value::BigInt(BigInt::from(7))),
+            Some(MapKey::Integer(7))
+        );
+        assert_eq!(MapKey::from_value(&Value::Float(1.0)), None);
+        assert_eq!(MapKey::from_value(&Value::Array(vec![].into())), None);
+    }
+
+    #[test]
+    fn test_map_key_ordering() {
+        let mut keys = [
+            MapKey::String("b".to_string()),
+            MapKey::Integer(2),
+            MapKey::String("a".to_string()),
+            MapKey::Boolean(false),
+            MapKey::Integer(-1),
+            MapKey::BigInt(BigInt::from(i64::MAX) + 1),
+            MapKey::BigInt(BigInt::from(i64::MIN) - 1),
+        ];
+        keys.sort();
+
+        assert_eq!(
+            keys.iter().map(|k| k.to_string()).collect::<Vec<String>>(),
+            vec![
+                "false",
+                "-9223372036854775809",
+                "-1",
+                "2",
+                "9223372036854775808",
+                "a",
+                "b"
+            ]
+        );
+    }
+}
@@ -1,38 +1,60 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fmt::{self, Debug, Display},
+    rc::Rc,
+};
 
-use crate::{lexer::Position, parser::Block};
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
 
+mod builtin;
+mod convert;
 mod function;
+mod map;
 
+pub use builtin::*;
+pub use convert::*;
 pub use function::*;
+pub use map::*;
 
-use super::{EvalError, ExecutionEnvironment};
+use super::{EvalError, ExecutionEnvironment, FunctionProto};
 
 pub const VOID: Value = Value::VOID;
 pub const TRUE: Value = Value::Boolean(true);
 pub const FALSE: Value = Value::Boolean(false);
 
-pub type BuiltinFn = fn(args: Vec<Rc<Value>>, pos: Position) -> Result<Rc<Value>, EvalError>;
-
-#[derive(Debug, PartialEq)]
 pub enum Value {
     Integer(i64),
+    /// Integer outside of the `i64` range, smaller ones are always `Integer`.
+    BigInt(BigInt),
     Float(f64),
     Boolean(bool),
     Function(Function),
     String(String),
-    Array(Vec<Rc<Value>>),
-
-    Builtin(BuiltinFn),
+    /// Arrays and maps are shared, a mutation is visible through every binding.
+    Array(RefCell<Vec<Rc<Value>>>),
+    Map(RefCell<BTreeMap<MapKey, Rc<Value>>>),
+    /// Integer range, the end is included only if the flag is set.
+    Range(i64, i64, bool),
 
-    Returned(Rc<Value>),
+    Builtin(Builtin),
 
     VOID,
 }
 
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        self.eq_guarded(other, &mut vec![])
+    }
+}
+
 impl Value {
-    pub fn new_builtin(value: BuiltinFn) -> Rc<Self> {
-        Rc::new(Value::Builtin(value))
+    pub fn new_builtin<F>(name: &str, func: F) -> Rc<Self>
+    where
+        F: Fn(Vec<Rc<Value>>, &BuiltinCall) -> Result<Rc<Value>, EvalError> + 'static,
+    {
+        Rc::new(Value::Builtin(Builtin::new(name, func)))
     }
     pub fn new_string(value: String) -> Rc<Self> {
         Rc::new(Value::String(value))
@@ -40,21 +62,30 @@ impl Value {
     pub fn new_integer(value: i64) -> Rc<Self> {
         Rc::new(Value::Integer(value))
     }
+    /// Creates an `Integer` if the value fits in `i64`.
+    pub fn new_bigint(value: BigInt) -> Rc<Self> {
+        match value.to_i64() {
+            Some(value) => Rc::new(Value::Integer(value)),
+            None => Rc::new(Value::BigInt(value)),
+        }
+    }
     pub fn new_float(value: f64) -> Rc<Self> {
         Rc::new(Value::Float(value))
     }
-    pub fn new_returned(value: Rc<Value>) -> Rc<Self> {
-        Rc::new(Value::Returned(value))
-    }
     pub fn new_function(
-        params: Vec<String>,
-        body: Rc<Block>,
+        proto: Rc<FunctionProto>,
         env: Rc<RefCell<ExecutionEnvironment>>,
     ) -> Rc<Self> {
-        Rc::new(Value::Function(Function::new(params, body, env)))
+        Rc::new(Value::Function(Function::new(proto, env)))
     }
     pub fn new_array(els: Vec<Rc<Value>>) -> Rc<Self> {
-        Rc::new(Value::Array(els))
+        Rc::new(Value::Array(RefCell::new(els)))
+    }
+    pub fn new_map(pairs: BTreeMap<MapKey, Rc<Value>>) -> Rc<Self> {
+        Rc::new(Value::Map(RefCell::new(pairs)))
+    }
+    pub fn new_range(start: i64, end: i64, inclusive: bool) -> Rc<Self> {
+        Rc::new(Value::Range(start, end, inclusive))
     }
 
     pub fn from_native_bool(value: bool) -> Rc<Self> {
@@ -65,25 +96,8 @@ impl Value {
         }
     }
 
-    // Returns the inner value of a Returned variant or VOID if not a Returned variant
-    pub fn unwrap_returned(&self) -> Rc<Value> {
-        match self {
-            Value::Returned(value) => Rc::clone(&value),
-            _ => VOID.rc(),
-        }
-    }
-
     pub fn is_void(&self) -> bool {
-        match self {
-            Value::VOID => true,
-            _ => false,
-        }
-    }
-    pub fn is_returned(&self) -> bool {
-        match self {
-            Value::Returned(_) => true,
-            _ => false,
-        }
+        matches!(self, Value::VOID)
     }
     pub fn is_string(&self) -> bool {
         matches!(self, Value::String(_))
@@ -91,35 +105,249 @@ impl Value {
     pub fn is_array(&self) -> bool {
         matches!(self, Value::Array(_))
     }
+    pub fn is_map(&self) -> bool {
+        matches!(self, Value::Map(_))
+    }
 
     pub fn rc(self) -> Rc<Self> {
         Rc::new(self)
     }
-}
 
-impl ToString for Value {
-    fn to_string(&self) -> String {
-        match self {
-            Value::VOID => "void".to_string(),
-            Value::Integer(value) => value.to_string(),
-            Value::Float(value) => value.to_string(),
-            Value::Boolean(value) => value.to_string(),
-            Value::Returned(value) => value.to_string(),
-            Value::String(value) => value.to_string(),
-            Value::Function(func) => {
-                format!("fn({})", func.params.join(", "))
+    /// Compares the values, `comparing` holds the pairs of containers being compared
+    /// further up, a pair met again is part of a cycle and taken as equal.
+    fn eq_guarded(&self, other: &Self, comparing: &mut Vec<(*const Value, *const Value)>) -> bool {
+        let pair = (self as *const Value, other as *const Value);
+        match (self, other) {
+            (Value::Integer(left), Value::Integer(right)) => left == right,
+            (Value::BigInt(left), Value::BigInt(right)) => left == right,
+            (Value::Float(left), Value::Float(right)) => left == right,
+            (Value::Boolean(left), Value::Boolean(right)) => left == right,
+            (Value::Function(left), Value::Function(right)) => left == right,
+            (Value::String(left), Value::String(right)) => left == right,
+            (Value::Array(_), Value::Array(_)) | (Value::Map(_), Value::Map(_))
+                if comparing.contains(&pair) =>
+            {
+                true
             }
-            Value::Builtin(_) => "builtin".to_string(),
+            (Value::Array(left), Value::Array(right)) => {
+                let (left, right) = (left.borrow(), right.borrow());
+                comparing.push(pair);
+                let equal = left.len() == right.len()
+                    && left
+                        .iter()
+                        .zip(right.iter())
+                        .all(|(l, r)| l.eq_guarded(r, comparing));
+                comparing.pop();
+                equal
+            }
+            (Value::Map(left), Value::Map(right)) => {
+                let (left, right) = (left.borrow(), right.borrow());
+                comparing.push(pair);
+                let equal = left.len() == right.len()
+                    && left
+                        .iter()
+                        .zip(right.iter())
+                        .all(|((lk, lv), (rk, rv))| lk == rk && lv.eq_guarded(rv, comparing));
+                comparing.pop();
+                equal
+            }
+            (
+                Value::Range(l_start, l_end, l_inclusive),
+                Value::Range(r_start, r_end, r_inclusive),
+            ) => l_start == r_start && l_end == r_end && l_inclusive == r_inclusive,
+            (Value::Builtin(left), Value::Builtin(right)) => left == right,
+            (Value::VOID, Value::VOID) => true,
+            _ => false,
+        }
+    }
+
+    /// Formats the value, `visiting` holds the containers being formatted further up,
+    /// a container met again is part of a cycle and printed as `[...]` or `{...}`.
+    fn to_string_guarded(&self, visiting: &mut Vec<*const Value>) -> String {
+        let this = self as *const Value;
+        match self {
+            Value::Array(_) if visiting.contains(&this) => "[...]".to_string(),
+            Value::Map(_) if visiting.contains(&this) => "{...}".to_string(),
             Value::Array(array) => {
-                format!(
+                visiting.push(this);
+                let out = format!(
                     "[{}]",
                     array
+                        .borrow()
                         .iter()
-                        .map(|el| el.to_string())
+                        .map(|el| el.to_string_guarded(visiting))
                         .collect::<Vec<String>>()
                         .join(", ")
-                )
+                );
+                visiting.pop();
+                out
             }
+            Value::Map(map) => {
+                visiting.push(this);
+                let out = format!(
+                    "{{{}}}",
+                    map.borrow()
+                        .iter()
+                        .map(|(key, value)| {
+                            format!("{key}: {}", value.to_string_guarded(visiting))
+                        })
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                );
+                visiting.pop();
+                out
+            }
+            _ => self.to_string(),
+        }
+    }
+
+    /// Debug formatting like `to_string_guarded`, a container met again is printed
+    /// as `[...]` or `{...}`.
+    fn debug_guarded(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        visiting: &RefCell<Vec<*const Value>>,
+    ) -> fmt::Result {
+        let this = self as *const Value;
+        let guarded = |value| GuardedDebug { value, visiting };
+        match self {
+            Value::Array(_) if visiting.borrow().contains(&this) => f.write_str("[...]"),
+            Value::Map(_) if visiting.borrow().contains(&this) => f.write_str("{...}"),
+            Value::Array(array) => {
+                visiting.borrow_mut().push(this);
+                let array = array.borrow();
+                let result = f
+                    .debug_list()
+                    .entries(array.iter().map(|el| guarded(el)))
+                    .finish();
+                visiting.borrow_mut().pop();
+                result
+            }
+            Value::Map(map) => {
+                visiting.borrow_mut().push(this);
+                let map = map.borrow();
+                let result = f
+                    .debug_map()
+                    .entries(map.iter().map(|(key, value)| (key, guarded(value))))
+                    .finish();
+                visiting.borrow_mut().pop();
+                result
+            }
+            Value::Integer(value) => f.debug_tuple("Integer").field(value).finish(),
+            Value::BigInt(value) => f.debug_tuple("BigInt").field(value).finish(),
+            Value::Float(value) => f.debug_tuple("Float").field(value).finish(),
+            Value::Boolean(value) => f.debug_tuple("Boolean").field(value).finish(),
+            Value::Function(func) => f.debug_tuple("Function").field(func).finish(),
+            Value::String(value) => f.debug_tuple("String").field(value).finish(),
+            Value::Range(start, end, inclusive) => f
+                .debug_tuple("Range")
+                .field(start)
+                .field(end)
+                .field(inclusive)
+                .finish(),
+            Value::Builtin(builtin) => f.debug_tuple("Builtin").field(builtin).finish(),
+            Value::VOID => f.write_str("VOID"),
+        }
+    }
+}
+
+impl Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.debug_guarded(f, &RefCell::new(vec![]))
+    }
+}
+
+/// Debug formatting of a value that shares the cycle guard of the container around it.
+struct GuardedDebug<'a> {
+    value: &'a Value,
+    visiting: &'a RefCell<Vec<*const Value>>,
+}
+
+impl Debug for GuardedDebug<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.debug_guarded(f, self.visiting)
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::VOID => write!(f, "void"),
+            Value::Integer(value) => write!(f, "{value}"),
+            Value::BigInt(value) => write!(f, "{value}"),
+            Value::Float(value) => write!(f, "{value}"),
+            Value::Boolean(value) => write!(f, "{value}"),
+            Value::String(value) => write!(f, "{value}"),
+            Value::Function(func) => write!(f, "fn({})", func.proto.params.join(", ")),
+            Value::Builtin(_) => write!(f, "builtin"),
+            Value::Range(start, end, true) => write!(f, "{start}..={end}"),
+            Value::Range(start, end, false) => write!(f, "{start}..{end}"),
+            Value::Array(_) | Value::Map(_) => write!(f, "{}", self.to_string_guarded(&mut vec![])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `[1, <itself>]`
+    fn cyclic_array() -> Rc<Value> {
+        let array = Value::new_array(vec![Value::new_integer(1)]);
+        if let Value::Array(elements) = &*array {
+            elements.borrow_mut().push(Rc::clone(&array));
+        }
+        array
+    }
+
+    /// `{"self": <itself>}`
+    fn cyclic_map() -> Rc<Value> {
+        let map = Value::new_map(BTreeMap::new());
+        if let Value::Map(entries) = &*map {
+            entries
+                .borrow_mut()
+                .insert(MapKey::String("self".to_string()), Rc::clone(&map));
+        }
+        map
+    }
+
+    #[test]
+    fn test_cyclic_to_string() {
+        assert_eq!(cyclic_array().to_string(), "[1, [...]]");
+        assert_eq!(cyclic_map().to_string(), "{self: {...}}");
+
+        // a container seen twice without a cycle is printed in full
+        let shared = Value::new_array(vec![Value::new_integer(2)]);
+        let outer = Value::new_array(vec![Rc::clone(&shared), shared]);
+        assert_eq!(outer.to_string(), "[[2], [2]]");
+    }
+
+    #[test]
+    fn test_cyclic_debug() {
+        assert_eq!(format!("{:?}", cyclic_array()), "[Integer(1), [...]]");
+        assert_eq!(format!("{:?}", cyclic_map()), r#"{String("self"): {...}}"#);
+        assert!(format!("{:#?}", cyclic_array()).contains("[...]"));
+
+        let shared = Value::new_array(vec![Value::new_integer(2)]);
+        let outer = Value::new_array(vec![Rc::clone(&shared), shared]);
+        assert_eq!(format!("{outer:?}"), "[[Integer(2)], [Integer(2)]]");
+    }
+
+    #[test]
+    fn test_cyclic_eq() {
+        let (a, b) = (cyclic_array(), cyclic_array());
+        assert_eq!(a, a);
+        assert_eq!(a, b);
+        assert_eq!(cyclic_map(), cyclic_map());
+
+        let other = Value::new_array(vec![Value::new_integer(2)]);
+        if let Value::Array(elements) = &*other {
+            elements.borrow_mut().push(Rc::clone(&other));
         }
+        assert_ne!(a, other);
+        assert_ne!(
+            a,
+            Value::new_array(vec![Value::new_integer(1), cyclic_map()])
+        );
     }
 }
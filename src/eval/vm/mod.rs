@@ -0,0 +1,926 @@
+mod ops;
+
+use std::{cell::RefCell, rc::Rc};
+
+use crate::lexer::{Position, Span};
+
+use super::{
+    Binding, Chunk, EvalError, ExecutionEnvironment, FunctionProto, Location, OpCode, StackFrame,
+    Value, FALSE, TRUE, VOID,
+};
+
+/// Activation record of a running function.
+struct Frame {
+    function: Rc<FunctionProto>,
+    /// Index of the next instruction.
+    ip: usize,
+    /// Innermost scope of the function.
+    env: Rc<RefCell<ExecutionEnvironment>>,
+    /// Stack height when the function was entered.
+    base: usize,
+    /// Stack heights of the loops being executed, innermost last.
+    loops: Vec<usize>,
+}
+
+impl Frame {
+    /// Source position of the instruction being executed.
+    fn position(&self) -> Position {
+        self.span().start
+    }
+
+    /// Source span of the instruction being executed.
+    fn span(&self) -> Span {
+        self.function.chunk.spans[self.ip - 1]
+    }
+}
+
+/// Default limit of nested script function calls.
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 10_000;
+
+/// Stack based virtual machine.
+///
+/// Script calls don't recurse on the native stack, every call pushes a `Frame` instead.
+pub struct Vm {
+    globals: Rc<RefCell<ExecutionEnvironment>>,
+    stack: Vec<Rc<Value>>,
+    frames: Vec<Frame>,
+    /// Number of nested script function calls that raises `EvalError::StackOverflow`.
+    max_call_depth: usize,
+}
+
+impl Vm {
+    pub fn new(globals: Rc<RefCell<ExecutionEnvironment>>) -> Self {
+        Vm {
+            globals,
+            stack: vec![],
+            frames: vec![],
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+        }
+    }
+
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.max_call_depth = depth;
+    }
+
+    /// Runs a compiled program and returns its result.
+    pub fn run(&mut self, script: Rc<FunctionProto>) -> Result<Rc<Value>, EvalError> {
+        let frame = Frame {
+            function: script,
+            ip: 0,
+            env: Rc::clone(&self.globals),
+            // the stack may already hold the operands of `call`
+            base: 0,
+            loops: vec![],
+        };
+
+        let result = self.execute(frame);
+        self.stack.clear();
+        self.frames.clear();
+        result
+    }
+
+    /// Calls a function with the arguments and returns its result.
+    /// There is no call site in the source, so the call is reported where the function is defined.
+    /// Builtins have no definition either and are called at the zero position.
+    pub fn call(
+        &mut self,
+        callee: Rc<Value>,
+        args: Vec<Rc<Value>>,
+    ) -> Result<Rc<Value>, EvalError> {
+        let (span, source) = match callee.as_ref() {
+            Value::Function(func) => (func.proto.span, func.proto.source.clone()),
+            _ => (None, None),
+        };
+        let span = span.unwrap_or(Span::point(Position(0, 0)));
+
+        let mut chunk = Chunk::new();
+        chunk.push(OpCode::Call(args.len()), span);
+        chunk.push(OpCode::Return, span);
+        let mut trampoline = FunctionProto::new(vec![], vec![], 0, chunk);
+        trampoline.source = source;
+
+        self.stack.push(callee);
+        self.stack.extend(args);
+        self.run(Rc::new(trampoline))
+    }
+
+    fn execute(&mut self, mut frame: Frame) -> Result<Rc<Value>, EvalError> {
+        self.dispatch(&mut frame)
+            .map_err(|err| self.unwind(err, frame))
+    }
+
+    /// Records where the error was raised and the calls that were active.
+    fn unwind(&mut self, err: EvalError, frame: Frame) -> EvalError {
+        let mut err = err.at(Location {
+            span: frame.span(),
+            source: frame.function.source.clone(),
+        });
+        let mut callee = frame;
+        while let Some(caller) = self.frames.pop() {
+            err = err.with_frame(StackFrame {
+                name: callee.function.display_name().to_string(),
+                call_site: caller.position(),
+                builtin: false,
+            });
+            callee = caller;
+        }
+        err
+    }
+
+    fn dispatch(&mut self, frame: &mut Frame) -> Result<Rc<Value>, EvalError> {
+        loop {
+            let op = frame.function.chunk.code[frame.ip];
+            frame.ip += 1;
+
+            match op {
+                OpCode::Constant(idx) => {
+                    let value = Rc::clone(&frame.function.chunk.constants[idx]);
+                    self.stack.push(value);
+                }
+                OpCode::True => self.stack.push(TRUE.rc()),
+                OpCode::False => self.stack.push(FALSE.rc()),
+                OpCode::Void => self.stack.push(VOID.rc()),
+                OpCode::Pop => {
+                    self.pop();
+                }
+                OpCode::Dup2 => {
+                    let len = self.stack.len();
+                    self.stack.extend_from_within(len - 2..);
+                }
+
+                OpCode::Get(idx) => {
+                    let lookup = &frame.function.chunk.lookups[idx];
+                    let value = lookup
+                        .bindings
+                        .iter()
+                        .find_map(|binding| self.read(&frame.env, *binding));
+                    match value {
+                        Some(value) => self.stack.push(value),
+                        None => {
+                            return Err(EvalError::NotDefined(
+                                lookup.name.clone(),
+                                frame.position(),
+                            ))
+                        }
+                    }
+                }
+                OpCode::Set(idx) => {
+                    let value = self.pop();
+                    if value.is_void() {
+                        return Err(EvalError::TriedToStoreVoid(frame.position()));
+                    }
+
+                    let lookup = &frame.function.chunk.lookups[idx];
+                    let assigned = lookup
+                        .bindings
+                        .iter()
+                        .any(|binding| self.write(&frame.env, *binding, Rc::clone(&value)));
+                    if !assigned {
+                        return Err(EvalError::NotDefined(lookup.name.clone(), frame.position()));
+                    }
+                }
+                OpCode::Define(slot) => {
+                    let value = self.pop();
+                    if value.is_void() {
+                        return Err(EvalError::TriedToStoreVoid(frame.position()));
+                    }
+                    frame.env.borrow_mut().define_local(slot, value);
+                }
+                OpCode::DefineGlobal(slot) => {
+                    let value = self.pop();
+                    if value.is_void() {
+                        return Err(EvalError::TriedToStoreVoid(frame.position()));
+                    }
+                    frame.env.borrow_mut().define_global(slot, value);
+                }
+
+                OpCode::Closure(idx) => {
+                    let proto = Rc::clone(&frame.function.chunk.functions[idx]);
+                    self.stack
+                        .push(Value::new_function(proto, Rc::clone(&frame.env)));
+                }
+                OpCode::Array(len) => {
+                    let els = self.stack.split_off(self.stack.len() - len);
+                    self.stack.push(Value::new_array(els));
+                }
+                OpCode::Template(len) => {
+                    let parts = self.stack.split_off(self.stack.len() - len);
+                    let string = parts.iter().map(|part| part.to_string()).collect();
+                    self.stack.push(Value::new_string(string));
+                }
+                OpCode::Map(len) => {
+                    let pairs = self.stack.split_off(self.stack.len() - len * 2);
+                    self.stack.push(ops::eval_map(pairs, frame.position())?);
+                }
+                OpCode::Index => {
+                    let index = self.pop();
+                    let of = self.pop();
+                    self.stack
+                        .push(ops::eval_index(&of, &index, frame.position())?);
+                }
+                OpCode::SetIndex => {
+                    let value = self.pop();
+                    let index = self.pop();
+                    let of = self.pop();
+                    if value.is_void() {
+                        return Err(EvalError::TriedToStoreVoid(frame.position()));
+                    }
+                    ops::eval_set_index(&of, &index, value, frame.position())?;
+                }
+                OpCode::Range { inclusive } => {
+                    let end = self.pop();
+                    let start = self.pop();
+                    self.stack
+                        .push(ops::eval_range(&start, &end, inclusive, frame.position())?);
+                }
+                OpCode::Prefix(op) => {
+                    let value = self.pop();
+                    self.stack
+                        .push(ops::eval_prefix(op, &value, frame.position())?);
+                }
+                OpCode::Infix(op) => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    self.stack
+                        .push(ops::eval_infix(&left, op, &right, frame.position())?);
+                }
+
+                OpCode::Call(argc) | OpCode::TailCall(argc) => {
+                    let tail = matches!(op, OpCode::TailCall(_));
+                    let callee_idx = self.stack.len() - argc - 1;
+                    if self.stack[callee_idx + 1..].iter().any(|arg| arg.is_void()) {
+                        return Err(EvalError::VoidValueAsArgument(frame.position()));
+                    }
+
+                    let callee = Rc::clone(&self.stack[callee_idx]);
+                    match &*callee {
+                        Value::Builtin(builtin) => {
+                            let args = self.stack.split_off(callee_idx + 1);
+                            self.stack.pop();
+                            let result = builtin.call(args, frame.position()).map_err(|err| {
+                                err.with_frame(StackFrame {
+                                    name: builtin.name.clone(),
+                                    call_site: frame.position(),
+                                    builtin: true,
+                                })
+                            })?;
+                            self.stack.push(result);
+                        }
+                        Value::Function(func) => {
+                            if argc != func.proto.params.len() {
+                                return Err(EvalError::WrongNumberOfArguments(
+                                    func.proto.params.len(),
+                                    argc,
+                                    frame.position(),
+                                ));
+                            }
+                            // `frames` holds the callers, so its length is the current call depth
+                            if !tail && self.frames.len() >= self.max_call_depth {
+                                return Err(EvalError::StackOverflow(
+                                    self.max_call_depth,
+                                    frame.position(),
+                                ));
+                            }
+
+                            let env = ExecutionEnvironment::new_local(
+                                Rc::clone(&func.captured_env),
+                                func.proto.slots,
+                            );
+                            {
+                                let mut env = env.borrow_mut();
+                                let args = self.stack.drain(callee_idx + 1..);
+                                for (slot, arg) in func.proto.param_slots.iter().zip(args) {
+                                    env.define_local(*slot, arg);
+                                }
+                            }
+                            self.stack.pop();
+
+                            let mut callee_frame = Frame {
+                                function: Rc::clone(&func.proto),
+                                ip: 0,
+                                env,
+                                base: self.stack.len(),
+                                loops: vec![],
+                            };
+                            if tail {
+                                // nothing of the current frame is needed after the call
+                                self.stack.truncate(frame.base);
+                                callee_frame.base = frame.base;
+                                *frame = callee_frame;
+                            } else {
+                                self.frames.push(std::mem::replace(frame, callee_frame));
+                            }
+                        }
+                        _ => return Err(EvalError::NotAFunction(frame.position())),
+                    }
+                }
+                OpCode::Return => {
+                    let result = self.pop();
+                    self.stack.truncate(frame.base);
+
+                    match self.frames.pop() {
+                        Some(caller) => {
+                            *frame = caller;
+                            self.stack.push(result);
+                        }
+                        None => return Ok(result),
+                    }
+                }
+
+                OpCode::Jump(to) => frame.ip = to,
+                OpCode::EnterLoop => frame.loops.push(self.stack.len()),
+                OpCode::ExitLoop => {
+                    frame.loops.pop();
+                }
+                OpCode::Unwind => {
+                    let height = *frame.loops.last().expect("unwind happens inside a loop");
+                    self.stack.truncate(height);
+                }
+                OpCode::IterStart => {
+                    let iterable = self.stack.last().expect("iterable was pushed before");
+                    if !ops::is_iterable(iterable) {
+                        return Err(EvalError::NotIterable(
+                            iterable.to_string(),
+                            frame.position(),
+                        ));
+                    }
+                    self.stack.push(Value::new_integer(0));
+                }
+                OpCode::IterNext(to) => {
+                    let len = self.stack.len();
+                    let cursor = match *self.stack[len - 1] {
+                        Value::Integer(cursor) => cursor as usize,
+                        _ => unreachable!("cursor is pushed by IterStart"),
+                    };
+                    match ops::iter_next(&self.stack[len - 2], cursor) {
+                        Some((item, next)) => {
+                            self.stack[len - 1] = Value::new_integer(next as i64);
+                            self.stack.push(item);
+                        }
+                        None => frame.ip = to,
+                    }
+                }
+                OpCode::JumpIfFalse(to) => {
+                    let condition = self.pop();
+                    match &*condition {
+                        Value::Boolean(true) => {}
+                        Value::Boolean(false) => frame.ip = to,
+                        _ => {
+                            return Err(EvalError::NonBooleanCondition(
+                                condition.to_string(),
+                                frame.position(),
+                            ))
+                        }
+                    }
+                }
+                OpCode::Match(idx) => {
+                    let value = self.stack.last().expect("matched value was pushed before");
+                    let mut bindings = vec![];
+                    let matched = ops::match_pattern(
+                        &frame.function.chunk.patterns[idx],
+                        value,
+                        &mut bindings,
+                    );
+                    for (slot, value) in bindings {
+                        if value.is_void() {
+                            return Err(EvalError::TriedToStoreVoid(frame.position()));
+                        }
+                        frame.env.borrow_mut().define_local(slot, value);
+                    }
+                    self.stack.push(Value::from_native_bool(matched));
+                }
+                OpCode::NoMatch => {
+                    let value = self.pop();
+                    return Err(EvalError::NonExhaustiveMatch(
+                        value.to_string(),
+                        frame.position(),
+                    ));
+                }
+
+                OpCode::PushScope(slots) => {
+                    frame.env = ExecutionEnvironment::new_local(Rc::clone(&frame.env), slots);
+                }
+                OpCode::PopScope => {
+                    let parent = frame.env.borrow().parent();
+                    frame.env = parent.expect("scopes are balanced by the compiler");
+                }
+            }
+        }
+    }
+
+    fn pop(&mut self) -> Rc<Value> {
+        self.stack.pop().expect("stack is balanced by the compiler")
+    }
+
+    /// Globals are resolved through the scope chain, a function made by another engine
+    /// keeps using the globals it was compiled against.
+    fn read(&self, env: &Rc<RefCell<ExecutionEnvironment>>, binding: Binding) -> Option<Rc<Value>> {
+        match binding {
+            Binding::Local { depth, slot } => env.borrow().get_local(depth, slot),
+            Binding::Global(slot) => env.borrow().get_global(slot),
+        }
+    }
+
+    fn write(
+        &self,
+        env: &Rc<RefCell<ExecutionEnvironment>>,
+        binding: Binding,
+        value: Rc<Value>,
+    ) -> bool {
+        match binding {
+            Binding::Local { depth, slot } => env.borrow_mut().assign_local(depth, slot, value),
+            Binding::Global(slot) => env.borrow_mut().assign_global(slot, value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{eval::Compiler, lexer::Lexer, parser::Parser};
+
+    fn run(input: &str) -> Result<Rc<Value>, EvalError> {
+        let mut parser = Parser::new(Lexer::new(input.to_string()));
+        let program = parser.parse().unwrap();
+
+        let globals = ExecutionEnvironment::new_global();
+        let script = Compiler::new(Rc::clone(&globals)).compile_program(&program);
+        Vm::new(globals).run(Rc::new(script))
+    }
+
+    #[test]
+    fn test_run_values() {
+        let tests = vec![
+            ("1 + 2 * 3", Value::new_integer(7)),
+            ("let x = 2; x = x * 5; x", Value::new_integer(10)),
+            ("if (1 < 2) { 10; } else { 20; } 3", Value::new_integer(3)),
+            ("fn add(a, b) { return a + b; } add(2, 3)", Value::new_integer(5)),
+            (
+                "len(\"abc\") + len([1, 2]) + len({1: 1})",
+                Value::new_integer(6),
+            ),
+            ("len(\"héllo\")", Value::new_integer(5)),
+            (
+                "let adder = fn(x) { return fn(y) { return x + y; }; }; adder(1)(2)",
+                Value::new_integer(3),
+            ),
+            (
+                "fn counter() { let n = 0; return fn() { n = n + 1; return n; }; } let c = counter(); c(); c()",
+                Value::new_integer(2),
+            ),
+            (
+                "fn f() { fn even(n) { if (n == 0) { return true; } return odd(n - 1); } fn odd(n) { if (n == 0) { return false; } return even(n - 1); } return even(10); } f()",
+                Value::from_native_bool(true),
+            ),
+            (
+                "let x = 1; fn f() { let y = x; let x = 2; return y + x; } f()",
+                Value::new_integer(3),
+            ),
+            (
+                "let x = 1; if (true) { let x = 2; x = 3; } x",
+                Value::new_integer(1),
+            ),
+            ("let x = 1; if (true) { x = 5; } x", Value::new_integer(5)),
+            ("fn f() {} f()", VOID.rc()),
+            (
+                "let i = 0; let sum = 0; while i < 10 { i = i + 1; if (i == 3) { continue; } let sq = i * i; if (sq > 50) { break; } sum = sum + sq; } sum",
+                Value::new_integer(131),
+            ),
+            (
+                "fn find(n) { let x = 0; while true { let y = x * 2; if (y >= n) { return x; } x = x + 1; } } find(9)",
+                Value::new_integer(5),
+            ),
+            (
+                "let n = 0; while n < 3 { let a = 1; while true { let b = 2; break; } n = n + a; } n",
+                Value::new_integer(3),
+            ),
+            (
+                "let f = 0; let g = 0; let i = 0; while i < 3 { let j = i; if (i == 0) { f = fn() { return j; }; } g = fn() { return j; }; i = i + 1; } f() + g()",
+                Value::new_integer(2),
+            ),
+            (
+                "let sum = 0; for i in 0..5 { sum = sum + i; } for i in 1..=3 { sum = sum + i; } sum",
+                Value::new_integer(16),
+            ),
+            (
+                "let out = \"\"; for ch in \"héllo\" { if (ch == \"l\") { continue; } out = out + ch; } out",
+                Value::new_string("héo".to_string()),
+            ),
+            (
+                "let last = 0; for x in [10, 20, 30] { if (x == 30) { break; } let y = x + 1; last = y; } last",
+                Value::new_integer(21),
+            ),
+            (
+                "fn first_big(xs) { for x in xs { if (x > 5) { return x; } } return 0; } first_big([1, 7, 9])",
+                Value::new_integer(7),
+            ),
+            (
+                "let f = 0; for i in 0..3 { if (i == 1) { f = fn() { return i; }; } } f()",
+                Value::new_integer(1),
+            ),
+            ("let n = 0; for i in 3..1 { n = n + 1; } n", Value::new_integer(0)),
+            ("1..=3", Value::new_range(1, 3, true)),
+            (
+                "let n = 0; for i in 3..=3 { n = n + i; } n",
+                Value::new_integer(3),
+            ),
+            (
+                "let n = 0; let last = 0; for i in 9223372036854775806..=9223372036854775807 { n += 1; last = i; } [n, last]",
+                Value::new_array(vec![Value::new_integer(2), Value::new_integer(i64::MAX)]),
+            ),
+            (
+                "let m = {\"b\": 1, 2: \"two\", true: [1, 2], \"a\": {\"x\": 9}}; m[\"a\"][\"x\"] + m[true][1] + len(m)",
+                Value::new_integer(15),
+            ),
+            ("let m = {1: 1, 1: 2}; m[1]", Value::new_integer(2)),
+            (
+                "\"héllo\"[1] + \"héllo\"[4]",
+                Value::new_string("éo".to_string()),
+            ),
+            (
+                "let a = [1, 2, 3]; let b = a; b[0] = 10; a[1] += 5; a[2] *= 2; b",
+                Value::new_array(vec![
+                    Value::new_integer(10),
+                    Value::new_integer(7),
+                    Value::new_integer(6),
+                ]),
+            ),
+            (
+                "let a = [1]; fn dec(xs) { xs[0] -= 1; } dec(a); dec(a); a[0]",
+                Value::new_integer(-1),
+            ),
+            (
+                "let m = {\"n\": 1}; m[\"n\"] += 1; m[\"new\"] = [4]; m[\"new\"][0] /= 2; m[\"n\"] + m[\"new\"][0]",
+                Value::new_integer(4),
+            ),
+            (
+                "let total = 0; for i in 0..4 { total += i; } total",
+                Value::new_integer(6),
+            ),
+            ("let x = 10.0; x /= 4; x", Value::new_float(2.5)),
+            (
+                "let n = 0; fn hit() { n += 1; return true; } let a = false && hit(); let b = true || hit(); n",
+                Value::new_integer(0),
+            ),
+            (
+                "let n = 0; fn hit() { n += 1; return true; } let a = true && hit(); let b = false || hit(); n",
+                Value::new_integer(2),
+            ),
+            ("1 < 2 && 2 < 3 || false", Value::from_native_bool(true)),
+            ("false || true && false", Value::from_native_bool(false)),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(run(input).unwrap(), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_run_map_ordering() {
+        let map = run("{\"b\": 1, 2: 2, \"a\": 3, false: 4}").unwrap();
+        assert_eq!(map.to_string(), "{false: 4, 2: 2, a: 3, b: 1}");
+    }
+
+    #[test]
+    fn test_run_cyclic_values() {
+        let array = run("let a = [1]; a[0] = a; a").unwrap();
+        assert_eq!(array.to_string(), "[[...]]");
+        let map = run(r#"let m = {}; m["self"] = m; m"#).unwrap();
+        assert_eq!(map.to_string(), "{self: {...}}");
+
+        let err = run(r#"let m = {}; m["self"] = m; m == m"#).unwrap_err();
+        assert!(matches!(
+            err.root(),
+            EvalError::InvalidInfixOperation(left, _, _, _) if left == "{self: {...}}"
+        ));
+    }
+
+    #[test]
+    fn test_run_errors() {
+        let tests = vec![
+            ("let x = println(1);", "Tried to store void value"),
+            ("y = 5;", "Not defined: y"),
+            ("if (1) { 2; }", "Non-boolean condition"),
+            ("1 + true", "Invalid infix operation"),
+            ("5 / 0", "Division by zero"),
+            ("[1][3]", "Index out of bounds"),
+            ("3(1)", "Not a function"),
+            ("fn f(a) {} f()", "Wrong number of arguments"),
+            ("println(println())", "Void as an function argument"),
+            ("while 1 { }", "Non-boolean condition"),
+            ("for x in 5 { }", "Not iterable: 5"),
+            ("1..\"a\"", "Invalid range: 1..a"),
+            ("{[1]: 1}", "Invalid map key: [1]"),
+            ("{1: 1}[fn() { return 1; }]", "Invalid map key: fn()"),
+            ("{1: 1}[2]", "Key not found: 2"),
+            ("let a = [1]; a[5] = 1;", "Index out of bounds"),
+            (
+                "\"héllo\"[5]",
+                "Index out of bounds: index 5, while length 5",
+            ),
+            (
+                "[1, 2][-1]",
+                "Index out of bounds: index -1, while length 2",
+            ),
+            (
+                "let a = [1]; a[-3] = 1;",
+                "Index out of bounds: index -3, while length 1",
+            ),
+            ("\"abc\"[0] = \"x\";", "Invalid index: abc[0]"),
+            ("let a = [1]; a[0] += \"s\";", "Invalid infix operation"),
+            ("y += 1;", "Not defined: y"),
+            (
+                "true && 1",
+                "Non-boolean condition: want true or false, got 1",
+            ),
+            (
+                "\"a\" || true",
+                "Non-boolean condition: want true or false, got a",
+            ),
+            (
+                "len(1)",
+                "Type mismatch: len: expected: String, Array or Map, got 1",
+            ),
+            (
+                "len(\"a\", \"b\")",
+                "Wrong number of arguments: len: expected: 1, got 2",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let err = run(input).unwrap_err().to_string();
+            assert!(err.starts_with(expected), "{input}: {err}");
+        }
+    }
+
+    #[test]
+    fn test_run_stack_trace() {
+        let input = "fn inner(x) { return x / 0; }
+let outer = fn(x) { return inner(x) + 1; };
+fn main() { let result = outer(1); return result; }
+main()";
+        let err = run(input).unwrap_err();
+
+        assert!(matches!(err.root(), EvalError::DivisionByZero(..)));
+        assert_eq!(err.position(), Position(1, 24));
+        assert_eq!(
+            err.stack()
+                .iter()
+                .map(|frame| frame.to_string())
+                .collect::<Vec<String>>(),
+            vec![
+                "in inner called at line:2, col:33",
+                "in <anonymous> called at line:3, col:31",
+                "in main called at line:4, col:5",
+            ]
+        );
+
+        let err = run("fn count(xs) { return len(xs); }\ncount(1)").unwrap_err();
+        assert_eq!(
+            err.stack(),
+            [
+                StackFrame {
+                    name: "len".to_string(),
+                    call_site: Position(1, 26),
+                    builtin: true,
+                },
+                StackFrame {
+                    name: "count".to_string(),
+                    call_site: Position(2, 6),
+                    builtin: false,
+                },
+            ]
+        );
+
+        assert!(run("1 / 0").unwrap_err().stack().is_empty());
+    }
+
+    #[test]
+    fn test_run_tail_calls() {
+        let input = "fn count(i, n) { if (i < n) { return count(i + 1, n); } return i; }
+count(0, 100000)";
+        assert_eq!(run(input).unwrap(), Value::new_integer(100000));
+
+        let input = "fn is_even(n) { if (n == 0) { return true; } return is_odd(n - 1); }
+fn is_odd(n) { if (n == 0) { return false; } return is_even(n - 1); }
+is_even(50001)";
+        assert_eq!(run(input).unwrap(), FALSE.rc());
+
+        // tail calls of builtins and closures, from inside loops and scopes
+        let input = "let add = fn(a) { return fn(b) { return a + b; }; };
+fn f(xs) { for x in xs { let y = x * 2; if (y > 4) { return add(y)(1); } } return len(xs); }
+[f([1, 2, 3]), f([1])]";
+        assert_eq!(run(input).unwrap().to_string(), "[7, 1]");
+
+        // the frame of a tail caller is replaced, so it's missing from the stack
+        let err = run("fn fail() { return 1 / 0; }\nfn f() { return fail(); }\nf()").unwrap_err();
+        assert_eq!(
+            err.stack()
+                .iter()
+                .map(|frame| frame.to_string())
+                .collect::<Vec<String>>(),
+            vec!["in fail called at line:3, col:2"]
+        );
+    }
+
+    #[test]
+    fn test_run_templates() {
+        let tests = [
+            (r#"let count = 3; "total: ${count * 2.5}""#, "total: 7.5"),
+            (
+                r#""${1}${true}${[1, "a"]}${{"k": 2}}""#,
+                "1true[1, a]{k: 2}",
+            ),
+            (r#"let name = "x"; "${"<${name}>"}!""#, "<x>!"),
+            (r#"fn f() {} "void: ${f()}""#, "void: void"),
+            (r#""${9223372036854775807 + 1}""#, "9223372036854775808"),
+        ];
+
+        for (input, expected) in tests {
+            let value = run(input).unwrap_or_else(|err| panic!("{input}: {err}"));
+            assert_eq!(*value, Value::String(expected.to_string()), "{input}");
+        }
+
+        let err = run(r#""a${1 / 0}""#).unwrap_err();
+        assert!(matches!(err.root(), EvalError::DivisionByZero(..)));
+    }
+
+    #[test]
+    fn test_run_conditionals() {
+        let tests = [
+            ("let x = if true { 1 } else { 2 }; x", "1"),
+            ("let x = if (1 > 2) { 1 } else { 2 }; x", "2"),
+            (
+                r#"let n = 0; let sign = if n < 0 { "-" } else if n == 0 { "0" } else { "+" }; sign"#,
+                "0",
+            ),
+            ("1 + if false { 1 } else { 2 }", "3"),
+            ("let y = if true { let a = 2; a * 3 } else { 0 }; y", "6"),
+            ("fn f() { return if false { 1 }; } f()", "void"),
+            ("fn f() { return if true { let a = 1; }; } f()", "void"),
+            ("fn abs(x) { return if x < 0 { -x } else { x }; } abs(-3)", "3"),
+            // statement position keeps discarding the values of the branches
+            ("if true { 1 } else { 2 }", "void"),
+            ("let x = if true { if false { 1 } else { 2 } } else { 3 }; x", "2"),
+            (
+                "let r = 0; let n = 2; if n == 1 { r = 10; } else if n == 2 { r = 20; } else { r = 30; } r",
+                "20",
+            ),
+            (
+                "fn f(x) { if x == 1 { return 10; } else if x == 2 { return 20; } return 30; } f(2) + f(3)",
+                "50",
+            ),
+            // break and continue drop the operands of the unfinished expression
+            (
+                "let s = 0; for a in [10, 20] { for i in 0..3 { let x = 1 + if i == 1 { break; 2 } else { 3 }; s += x; } s += a; } s",
+                "38",
+            ),
+            (
+                "let s = 0; for i in 0..4 { let x = 1 + if i == 1 || i == 3 { continue; 2 } else { 3 }; s += x; } s",
+                "8",
+            ),
+            (
+                "let s = 0; let i = 0; while i < 3 { i += 1; s += 1 + if i == 2 { continue; 0 } else { 10 }; } s",
+                "22",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let value = run(input).unwrap_or_else(|err| panic!("{input}: {err}"));
+            assert_eq!(value.to_string(), expected, "{input}");
+        }
+
+        let err = run("let v = if false { 1 };").unwrap_err();
+        assert!(matches!(err.root(), EvalError::TriedToStoreVoid(..)));
+    }
+
+    #[test]
+    fn test_run_match() {
+        let tests = [
+            (r#"let r = match 2 { 1 => "one", 2 => "two", _ => "many" }; r"#, "two"),
+            (
+                r#"let z = match 1 { 1 => { match 2 { 2 => "in", _ => "x" } }, _ => "o" }; z"#,
+                "in",
+            ),
+            (r#"let z = if true { match 2 { 2 => "in", _ => "x" } } else { "o" }; z"#, "in"),
+            (r#"let r = match "b" { "a" | "b" => 1, _ => 2 }; r"#, "1"),
+            ("let r = match -1 { -1 => true, _ => false }; r", "true"),
+            ("let r = match 1.0 { 1 => true, _ => false }; r", "false"),
+            ("let r = match 1.0 { 1.0 => true, _ => false }; r", "true"),
+            (r#"let r = match [1] { "[1]" => 1, 1 => 2, [1] => 3 }; r"#, "3"),
+            ("let r = match 9223372036854775807 + 1 { 9223372036854775808 => 1 }; r", "1"),
+            ("let r = match [1, [2, 3]] { [a, [b, c]] => a + b + c }; r", "6"),
+            ("let r = match [1, 2] { [x] => x, [x, y, z] => z, _ => 0 }; r", "0"),
+            ("let r = match [2, 1] { [1, x] | [x, 1] => x }; r", "2"),
+            (
+                r#"let r = match {"op": "add", "args": [1, 2], "id": 7} { {"op": "add", "args": [a, b]} => a + b }; r"#,
+                "3",
+            ),
+            (r#"let r = match {"a": 1} { {"b": b} => b, {} => 0 }; r"#, "0"),
+            ("let r = match 5 { n if n < 0 => -1, n if n > 0 => 1, _ => 0 }; r", "1"),
+            ("let x = 1; let y = match 2 { x => x } + x; y", "3"),
+            ("let r = match 3 { n => { let m = n * 2; m + 1 } }; r", "7"),
+            ("fn f() { return match 3 { _ => { let m = 1; } }; } f()", "void"),
+            // statement position discards the value of the arm
+            ("let r = 0; match [1, 2] { [a, b] => { r = a + b; } } r", "3"),
+            ("match 1 { _ => 2 }", "void"),
+            (
+                "let seen = 0; for i in 0..10 { match i { 3 => { break; } n => { seen += n; } } } seen",
+                "3",
+            ),
+            (
+                "fn f(v) { match v { [x] => { return x; } _ => {} } return 0; } f([5]) + f(5)",
+                "5",
+            ),
+            (
+                "let fs = [0, 0, 0]; for i in 0..3 { fs[i] = match i { n => fn() { return n; } }; } fs[1]() * 10 + fs[2]()",
+                "12",
+            ),
+            // break and continue drop the scrutinee and the operands of the unfinished expression
+            (
+                "let s = 0; for v in [1, 2, 1, 2] { let y = 1 + match v { 1 => { continue; 0 } _ => 2 }; s += y; } s",
+                "6",
+            ),
+            (
+                "let s = 0; for v in [2, 2, 1, 2] { s += 1 + match v { 1 => { break; 0 } _ => 2 }; } s",
+                "6",
+            ),
+            (
+                "let s = 0; for v in [1, 2, 3] { s += 1 + match v { x if if x == 2 { continue; true } else { true } => x }; } s",
+                "6",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let value = run(input).unwrap_or_else(|err| panic!("{input}: {err}"));
+            assert_eq!(value.to_string(), expected, "{input}");
+        }
+
+        let err = run("let v = [1];\nmatch v { [] => 0, [x, y] => 1 }").unwrap_err();
+        assert!(matches!(
+            err.root(),
+            EvalError::NonExhaustiveMatch(value, Position(2, 1)) if value == "[1]"
+        ));
+        let err = run("match 1 { x if x => 1 }").unwrap_err();
+        assert!(matches!(err.root(), EvalError::NonBooleanCondition(..)));
+    }
+
+    #[test]
+    fn test_run_bigint() {
+        let tests = [
+            ("9223372036854775807 + 1", "9223372036854775808"),
+            ("-9223372036854775807 - 2", "-9223372036854775809"),
+            ("4611686018427387904 * 2", "9223372036854775808"),
+            ("4611686018427387904 * 4", "18446744073709551616"),
+            ("(-9223372036854775807 - 1) / -1", "9223372036854775808"),
+            ("-(-9223372036854775807 - 1)", "9223372036854775808"),
+            (
+                "let x = 9223372036854775807; x += 1; x",
+                "9223372036854775808",
+            ),
+            (
+                "123456789012345678901234567890 * 10",
+                "1234567890123456789012345678900",
+            ),
+            // results that fit again are plain integers
+            ("(9223372036854775807 + 10) - 20", "9223372036854775797"),
+            ("18446744073709551616 / 4611686018427387904", "4"),
+            ("18446744073709551616 > 1", "true"),
+            ("-18446744073709551616 < -9223372036854775807", "true"),
+            ("18446744073709551616 == 18446744073709551615 + 1", "true"),
+            ("18446744073709551616 != 1", "true"),
+            ("18446744073709551616 * 0.5", "9223372036854776000"),
+            // big integers are map keys like the small ones
+            (
+                r#"let m = {1: "one"}; m[9223372036854775807 + 1] = "big"; m[9223372036854775808]"#,
+                "big",
+            ),
+            (
+                "let m = {}; m[9223372036854775808 - 1] = 1; m[9223372036854775807]",
+                "1",
+            ),
+            (
+                "{9223372036854775808: 1, -9223372036854775809: 2, 0: 3}",
+                "{-9223372036854775809: 2, 0: 3, 9223372036854775808: 1}",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let value = run(input).unwrap_or_else(|err| panic!("{input}: {err}"));
+            assert_eq!(value.to_string(), expected, "{input}");
+        }
+
+        assert_eq!(
+            run("9223372036854775807 + 1 - 1").unwrap(),
+            Value::new_integer(i64::MAX)
+        );
+        assert!(matches!(
+            *run("9223372036854775807 + 1").unwrap(),
+            Value::BigInt(_)
+        ));
+        assert!(run("18446744073709551616 / 0")
+            .unwrap_err()
+            .to_string()
+            .starts_with("Division by zero: 18446744073709551616/0"));
+    }
+}
@@ -0,0 +1,392 @@
+use std::{collections::BTreeMap, rc::Rc};
+
+use num_bigint::BigInt;
+use num_traits::{ToPrimitive, Zero};
+
+use crate::{
+    eval::{EvalError, InfixOp, MapKey, MatchPattern, PrefixOp, Value},
+    lexer::Position,
+};
+
+pub fn eval_prefix(op: PrefixOp, value: &Value, pos: Position) -> Result<Rc<Value>, EvalError> {
+    match op {
+        PrefixOp::Not => match value {
+            Value::Boolean(b) => Ok(Value::from_native_bool(!b)),
+            _ => Err(EvalError::InvalidPrefixOperation(
+                op.literal(),
+                value.to_string(),
+                pos,
+            )),
+        },
+        PrefixOp::Negate => match value {
+            Value::Integer(i) => match i.checked_neg() {
+                Some(negated) => Ok(Value::new_integer(negated)),
+                None => Ok(Value::new_bigint(-BigInt::from(*i))),
+            },
+            Value::BigInt(i) => Ok(Value::new_bigint(-i)),
+            Value::Float(f) => Ok(Value::new_float(-f)),
+            _ => Err(EvalError::InvalidPrefixOperation(
+                op.literal(),
+                value.to_string(),
+                pos,
+            )),
+        },
+    }
+}
+
+pub fn eval_infix(
+    left: &Value,
+    op: InfixOp,
+    right: &Value,
+    pos: Position,
+) -> Result<Rc<Value>, EvalError> {
+    match (left, right) {
+        (Value::String(left), Value::String(right)) => eval_string_infix(left, op, right, pos),
+        (Value::Integer(left), Value::Integer(right)) => eval_int_infix(*left, op, *right, pos),
+        (Value::BigInt(left), Value::BigInt(right)) => eval_bigint_infix(left, op, right, pos),
+        (Value::Integer(left), Value::BigInt(right)) => {
+            eval_bigint_infix(&BigInt::from(*left), op, right, pos)
+        }
+        (Value::BigInt(left), Value::Integer(right)) => {
+            eval_bigint_infix(left, op, &BigInt::from(*right), pos)
+        }
+        (Value::Boolean(left), Value::Boolean(right)) => eval_bool_infix(*left, op, *right, pos),
+        (Value::Float(left), Value::Float(right)) => eval_float_infix(*left, op, *right, pos),
+        (Value::Integer(left), Value::Float(right)) => {
+            eval_float_infix(*left as f64, op, *right, pos)
+        }
+        (Value::Float(left), Value::Integer(right)) => {
+            eval_float_infix(*left, op, *right as f64, pos)
+        }
+        (Value::BigInt(left), Value::Float(right)) => {
+            eval_float_infix(bigint_to_f64(left), op, *right, pos)
+        }
+        (Value::Float(left), Value::BigInt(right)) => {
+            eval_float_infix(*left, op, bigint_to_f64(right), pos)
+        }
+        _ => Err(EvalError::InvalidInfixOperation(
+            left.to_string(),
+            op.literal(),
+            right.to_string(),
+            pos,
+        )),
+    }
+}
+
+fn eval_string_infix(
+    left: &str,
+    op: InfixOp,
+    right: &str,
+    pos: Position,
+) -> Result<Rc<Value>, EvalError> {
+    match op {
+        InfixOp::Add => Ok(Value::new_string(format!("{left}{right}"))),
+        InfixOp::Equal => Ok(Value::from_native_bool(left == right)),
+        InfixOp::NotEqual => Ok(Value::from_native_bool(left != right)),
+        _ => Err(EvalError::InvalidInfixOperation(
+            left.to_string(),
+            op.literal(),
+            right.to_string(),
+            pos,
+        )),
+    }
+}
+
+fn eval_float_infix(
+    left: f64,
+    op: InfixOp,
+    right: f64,
+    pos: Position,
+) -> Result<Rc<Value>, EvalError> {
+    if op == InfixOp::Divide && right == 0.0 {
+        return Err(EvalError::DivisionByZero(
+            left.to_string(),
+            right.to_string(),
+            pos,
+        ));
+    };
+
+    match op {
+        InfixOp::Add => Ok(Value::new_float(left + right)),
+        InfixOp::Subtract => Ok(Value::new_float(left - right)),
+        InfixOp::Multiply => Ok(Value::new_float(left * right)),
+        InfixOp::Divide => Ok(Value::new_float(left / right)),
+
+        InfixOp::Equal => Ok(Value::from_native_bool(left == right)),
+        InfixOp::NotEqual => Ok(Value::from_native_bool(left != right)),
+        InfixOp::LessThan => Ok(Value::from_native_bool(left < right)),
+        InfixOp::GreaterThan => Ok(Value::from_native_bool(left > right)),
+        InfixOp::LessThanEqual => Ok(Value::from_native_bool(left <= right)),
+        InfixOp::GreaterThanEqual => Ok(Value::from_native_bool(left >= right)),
+    }
+}
+
+fn eval_int_infix(
+    left: i64,
+    op: InfixOp,
+    right: i64,
+    pos: Position,
+) -> Result<Rc<Value>, EvalError> {
+    if op == InfixOp::Divide && right == 0 {
+        return Err(EvalError::DivisionByZero(
+            left.to_string(),
+            right.to_string(),
+            pos,
+        ));
+    };
+
+    // overflowing results, `i64::MIN / -1` included, are promoted to `BigInt`
+    let checked = |result: Option<i64>| match result {
+        Some(result) => Ok(Value::new_integer(result)),
+        None => eval_bigint_infix(&BigInt::from(left), op, &BigInt::from(right), pos),
+    };
+
+    match op {
+        InfixOp::Add => checked(left.checked_add(right)),
+        InfixOp::Subtract => checked(left.checked_sub(right)),
+        InfixOp::Multiply => checked(left.checked_mul(right)),
+        InfixOp::Divide => checked(left.checked_div(right)),
+
+        InfixOp::Equal => Ok(Value::from_native_bool(left == right)),
+        InfixOp::NotEqual => Ok(Value::from_native_bool(left != right)),
+        InfixOp::LessThan => Ok(Value::from_native_bool(left < right)),
+        InfixOp::GreaterThan => Ok(Value::from_native_bool(left > right)),
+        InfixOp::LessThanEqual => Ok(Value::from_native_bool(left <= right)),
+        InfixOp::GreaterThanEqual => Ok(Value::from_native_bool(left >= right)),
+    }
+}
+
+fn eval_bigint_infix(
+    left: &BigInt,
+    op: InfixOp,
+    right: &BigInt,
+    pos: Position,
+) -> Result<Rc<Value>, EvalError> {
+    if op == InfixOp::Divide && right.is_zero() {
+        return Err(EvalError::DivisionByZero(
+            left.to_string(),
+            right.to_string(),
+            pos,
+        ));
+    };
+
+    match op {
+        InfixOp::Add => Ok(Value::new_bigint(left + right)),
+        InfixOp::Subtract => Ok(Value::new_bigint(left - right)),
+        InfixOp::Multiply => Ok(Value::new_bigint(left * right)),
+        InfixOp::Divide => Ok(Value::new_bigint(left / right)),
+
+        InfixOp::Equal => Ok(Value::from_native_bool(left == right)),
+        InfixOp::NotEqual => Ok(Value::from_native_bool(left != right)),
+        InfixOp::LessThan => Ok(Value::from_native_bool(left < right)),
+        InfixOp::GreaterThan => Ok(Value::from_native_bool(left > right)),
+        InfixOp::LessThanEqual => Ok(Value::from_native_bool(left <= right)),
+        InfixOp::GreaterThanEqual => Ok(Value::from_native_bool(left >= right)),
+    }
+}
+
+/// Too big values become infinite.
+fn bigint_to_f64(value: &BigInt) -> f64 {
+    value
+        .to_f64()
+        .expect("big integers always convert to a float")
+}
+
+fn eval_bool_infix(
+    left: bool,
+    op: InfixOp,
+    right: bool,
+    pos: Position,
+) -> Result<Rc<Value>, EvalError> {
+    match op {
+        InfixOp::Equal => Ok(Value::from_native_bool(left == right)),
+        InfixOp::NotEqual => Ok(Value::from_native_bool(left != right)),
+        _ => Err(EvalError::InvalidInfixOperation(
+            left.to_string(),
+            op.literal(),
+            right.to_string(),
+            pos,
+        )),
+    }
+}
+
+pub fn eval_index(of: &Value, index: &Value, pos: Position) -> Result<Rc<Value>, EvalError> {
+    match (of, index) {
+        (Value::Array(arr), Value::Integer(int)) => {
+            let arr = arr.borrow();
+            let idx = checked_index(*int, arr.len(), pos)?;
+            Ok(Rc::clone(&arr[idx]))
+        }
+        // strings are indexed by character
+        (Value::String(s), Value::Integer(int)) => {
+            let len = s.chars().count();
+            let idx = checked_index(*int, len, pos)?;
+            match s.chars().nth(idx) {
+                Some(ch) => Ok(Value::new_string(ch.to_string())),
+                None => Err(EvalError::IndexOutOfBounds(*int, len, pos)),
+            }
+        }
+        (Value::Map(map), _) => match map.borrow().get(&map_key(index, pos)?) {
+            Some(value) => Ok(Rc::clone(value)),
+            None => Err(EvalError::KeyNotFound(index.to_string(), pos)),
+        },
+        _ => Err(EvalError::InvalidIndexExpression(
+            of.to_string(),
+            index.to_string(),
+            pos,
+        )),
+    }
+}
+
+/// Stores the value at the index of an array or a map. Missing map keys are inserted.
+pub fn eval_set_index(
+    of: &Value,
+    index: &Value,
+    value: Rc<Value>,
+    pos: Position,
+) -> Result<(), EvalError> {
+    match (of, index) {
+        (Value::Array(arr), Value::Integer(int)) => {
+            let mut arr = arr.borrow_mut();
+            let idx = checked_index(*int, arr.len(), pos)?;
+            arr[idx] = value;
+            Ok(())
+        }
+        (Value::Map(map), _) => {
+            map.borrow_mut().insert(map_key(index, pos)?, value);
+            Ok(())
+        }
+        _ => Err(EvalError::InvalidIndexExpression(
+            of.to_string(),
+            index.to_string(),
+            pos,
+        )),
+    }
+}
+
+/// Converts an index into a sequence of `len` elements, negative ones are out of bounds.
+fn checked_index(index: i64, len: usize, pos: Position) -> Result<usize, EvalError> {
+    match usize::try_from(index) {
+        Ok(idx) if idx < len => Ok(idx),
+        _ => Err(EvalError::IndexOutOfBounds(index, len, pos)),
+    }
+}
+
+/// Builds a map from alternating keys and values. Later keys overwrite earlier ones.
+pub fn eval_map(pairs: Vec<Rc<Value>>, pos: Position) -> Result<Rc<Value>, EvalError> {
+    let mut map = BTreeMap::new();
+    let mut pairs = pairs.into_iter();
+    while let (Some(key), Some(value)) = (pairs.next(), pairs.next()) {
+        map.insert(map_key(&key, pos)?, value);
+    }
+    Ok(Value::new_map(map))
+}
+
+fn map_key(value: &Value, pos: Position) -> Result<MapKey, EvalError> {
+    MapKey::from_value(value).ok_or_else(|| EvalError::InvalidMapKey(value.to_string(), pos))
+}
+
+pub fn eval_range(
+    start: &Value,
+    end: &Value,
+    inclusive: bool,
+    pos: Position,
+) -> Result<Rc<Value>, EvalError> {
+    match (start, end) {
+        (Value::Integer(start), Value::Integer(end)) => {
+            Ok(Value::new_range(*start, *end, inclusive))
+        }
+        _ => Err(EvalError::InvalidRange(
+            start.to_string(),
+            end.to_string(),
+            pos,
+        )),
+    }
+}
+
+pub fn is_iterable(value: &Value) -> bool {
+    matches!(value, Value::Array(_) | Value::String(_) | Value::Range(..))
+}
+
+/// Returns the element at `cursor` and the cursor of the element after it.
+/// Strings are walked by byte offset, so every step is O(1).
+pub fn iter_next(iterable: &Value, cursor: usize) -> Option<(Rc<Value>, usize)> {
+    match iterable {
+        Value::Array(arr) => arr
+            .borrow()
+            .get(cursor)
+            .map(|el| (Rc::clone(el), cursor + 1)),
+        Value::String(s) => s[cursor..]
+            .chars()
+            .next()
+            .map(|ch| (Value::new_string(ch.to_string()), cursor + ch.len_utf8())),
+        Value::Range(start, end, inclusive) => start
+            .checked_add(cursor as i64)
+            .filter(|n| if *inclusive { n <= end } else { n < end })
+            .map(|n| (Value::new_integer(n), cursor + 1)),
+        _ => unreachable!("checked by is_iterable"),
+    }
+}
+
+/// Tests the value against the pattern, collecting the slots and values it binds.
+/// Bindings of the alternatives and elements that didn't match are left out.
+pub fn match_pattern(
+    pattern: &MatchPattern,
+    value: &Rc<Value>,
+    bindings: &mut Vec<(usize, Rc<Value>)>,
+) -> bool {
+    match pattern {
+        MatchPattern::Any => true,
+        MatchPattern::Constant(constant) => match (&**constant, &**value) {
+            (Value::Integer(constant), Value::Integer(value)) => constant == value,
+            (Value::BigInt(constant), Value::BigInt(value)) => constant == value,
+            (Value::Float(constant), Value::Float(value)) => constant == value,
+            (Value::Boolean(constant), Value::Boolean(value)) => constant == value,
+            (Value::String(constant), Value::String(value)) => constant == value,
+            _ => false,
+        },
+        MatchPattern::Bind(slot) => {
+            bindings.push((*slot, Rc::clone(value)));
+            true
+        }
+        MatchPattern::Array(patterns) => match &**value {
+            Value::Array(arr) => {
+                let arr = arr.borrow();
+                let len = bindings.len();
+                let matched = arr.len() == patterns.len()
+                    && patterns
+                        .iter()
+                        .zip(arr.iter())
+                        .all(|(pattern, el)| match_pattern(pattern, el, bindings));
+                if !matched {
+                    bindings.truncate(len);
+                }
+                matched
+            }
+            _ => false,
+        },
+        MatchPattern::Map(entries) => match &**value {
+            Value::Map(map) => {
+                let map = map.borrow();
+                let len = bindings.len();
+                let matched = entries.iter().all(|(key, pattern)| {
+                    map.get(key)
+                        .is_some_and(|value| match_pattern(pattern, value, bindings))
+                });
+                if !matched {
+                    bindings.truncate(len);
+                }
+                matched
+            }
+            _ => false,
+        },
+        MatchPattern::Or(alternatives) => alternatives.iter().any(|alternative| {
+            let len = bindings.len();
+            let matched = match_pattern(alternative, value, bindings);
+            if !matched {
+                bindings.truncate(len);
+            }
+            matched
+        }),
+    }
+}
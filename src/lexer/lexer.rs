@@ -1,28 +1,42 @@
-use super::{Position, Token, TokenKind};
+use num_bigint::BigInt;
+
+use super::{Position, TemplatePart, Token, TokenKind};
 
 #[derive(Debug)]
 pub struct Lexer {
     /// Input string to be lexed
     input: String,
-    /// Pointer to the current character in the input string
-    current_pos: usize,
-    /// Current character being processed (`current_pos` points to)
+    /// Byte offset of the current character in the input string
+    offset: usize,
+    /// Byte offset of the input in the source it was taken from
+    base_offset: usize,
+    /// Current character being processed (`offset` points to)
     current_ch: Option<char>,
     /// Next character being processed
     peek_ch: Option<char>,
 
     /// Current position in the input string
     position: Position,
+    /// Position of the last consumed character, the end of the token being read
+    last_position: Position,
 }
 
 impl Lexer {
     pub fn new(input: String) -> Self {
+        Self::starting_after(input, Position(1, 0), 0)
+    }
+
+    /// Creates a lexer for a piece of a bigger source.
+    /// The input starts right after `position`, at byte `offset` of the source.
+    pub fn starting_after(input: String, position: Position, offset: usize) -> Self {
         let mut lexer = Lexer {
             input,
-            current_pos: 0,
+            offset: 0,
+            base_offset: offset,
             current_ch: None,
             peek_ch: None,
-            position: Position(1, 0),
+            position,
+            last_position: position,
         };
         lexer.read_char();
         lexer
@@ -31,26 +45,23 @@ impl Lexer {
     /// Reads the next character from the input string and updates the lexer's state.
     /// Moves the lexer's position to the next character and updates the current and peek characters.
     fn read_char(&mut self) {
-        if self.current_pos >= self.input.len() {
-            self.current_ch = None;
-            self.peek_ch = None;
-        } else {
-            self.current_ch = match self.peek_ch {
-                Some(ch) => Some(ch),
-                None => self.input.chars().nth(self.current_pos),
-            };
-            self.peek_ch = self.input.chars().nth(self.current_pos + 1);
+        if self.current_ch.is_some() {
+            self.last_position = self.position;
         }
 
-        if self.current_ch.is_some() {
+        // step over the current character, it's `None` only before the first one and at the end
+        self.offset += self.current_ch.map_or(0, char::len_utf8);
+        let mut rest = self.input[self.offset..].chars();
+        self.current_ch = rest.next();
+        self.peek_ch = rest.next();
+
+        if let Some(ch) = self.current_ch {
             self.position.1 += 1;
-            if self.current_ch.unwrap() == '\n' {
+            if ch == '\n' {
                 self.position.0 += 1;
                 self.position.1 = 0;
             }
         }
-
-        self.current_pos += 1;
     }
 
     /// Skips over any whitespace characters in the input string. I'm not a snake.
@@ -60,6 +71,20 @@ impl Lexer {
         }
     }
 
+    /// Start of a token, taken before its first character is read.
+    fn mark(&self) -> Mark {
+        Mark {
+            position: self.position,
+            offset: self.base_offset + self.offset,
+        }
+    }
+
+    /// Token from the mark to the last character read.
+    fn token_since(&self, kind: TokenKind, start: Mark) -> Token {
+        Token::spanning(kind, start.position, self.last_position)
+            .with_bytes(start.offset..self.base_offset + self.offset)
+    }
+
     /// Checks if the next character is the given character.
     fn peek_char_is(&self, ch: char) -> bool {
         self.peek_ch == Some(ch)
@@ -69,7 +94,7 @@ impl Lexer {
     /// If keyword, returns the corresponding keyword token.
     /// Otherwise, returns an identifier token.
     fn read_alphabetic_token(&mut self) -> Token {
-        let token_position = self.position;
+        let start = self.mark();
 
         let start_token = self.current_ch.unwrap();
         let mut buf = String::new();
@@ -82,49 +107,399 @@ impl Lexer {
             self.read_char();
         }
 
-        Token::new(TokenKind::new_alphabetic(buf), token_position)
+        self.token_since(TokenKind::new_alphabetic(buf), start)
     }
 
+    /// Reads a decimal, hexadecimal (`0x`), octal (`0o`) or binary (`0b`) number.
+    /// Digits may be separated by underscores, decimal ones may have a fraction and an exponent.
+    /// Text that doesn't make a valid number becomes a `MalformedNumber` token.
     fn read_numeric_token(&mut self) -> Token {
-        let token_position = self.position;
+        let start = self.mark();
+
+        let radix = match (self.current_ch, self.peek_ch) {
+            (Some('0'), Some('x' | 'X')) => Some(16),
+            (Some('0'), Some('o' | 'O')) => Some(8),
+            (Some('0'), Some('b' | 'B')) => Some(2),
+            _ => None,
+        };
 
-        let start_token = self.current_ch.unwrap();
         let mut buf = String::new();
-        buf.push(start_token);
+        let kind = match radix {
+            Some(radix) => {
+                // consume the prefix
+                for _ in 0..2 {
+                    buf.push(self.current_ch.unwrap());
+                    self.read_char();
+                }
 
-        self.read_char(); // consume start token
+                // digits of any radix are read, so `0b102` is malformed rather than `0b10` and `2`
+                while matches!(self.current_ch, Some(c) if c.is_alphanumeric() || c == '_') {
+                    buf.push(self.current_ch.unwrap());
+                    self.read_char();
+                }
 
-        while self.current_is_numeric() || self.current_ch == Some('.') {
-            buf.push(self.current_ch.unwrap());
+                parse_integer(&buf[2..], radix).unwrap_or(TokenKind::MalformedNumber(buf))
+            }
+            None => {
+                let mut is_float = false;
+
+                // `..` after an integer is a range, not a fraction
+                while self.current_is_numeric()
+                    || self.current_ch == Some('_')
+                    || (self.current_ch == Some('.') && !self.peek_char_is('.'))
+                {
+                    is_float |= self.current_ch == Some('.');
+                    buf.push(self.current_ch.unwrap());
+                    self.read_char();
+                }
+
+                if matches!(self.current_ch, Some('e' | 'E'))
+                    && matches!(self.peek_ch, Some(c) if c.is_numeric() || c == '+' || c == '-')
+                {
+                    is_float = true;
+                    buf.push(self.current_ch.unwrap());
+                    self.read_char();
+                    buf.push(self.current_ch.unwrap());
+                    self.read_char();
+
+                    // a dot is read to report `1e3.5` as a whole
+                    while self.current_is_numeric()
+                        || self.current_ch == Some('_')
+                        || (self.current_ch == Some('.') && !self.peek_char_is('.'))
+                    {
+                        buf.push(self.current_ch.unwrap());
+                        self.read_char();
+                    }
+                }
+
+                if is_float {
+                    parse_float(&buf).unwrap_or(TokenKind::MalformedNumber(buf))
+                } else {
+                    parse_integer(&buf, 10).unwrap_or(TokenKind::MalformedNumber(buf))
+                }
+            }
+        };
+
+        self.token_since(kind, start)
+    }
+
+    /// Reads a string literal, replacing escape sequences with the characters they stand for.
+    /// Strings end at the closing quote, a string still open at the end of the line is unterminated.
+    fn read_string(&mut self) -> Token {
+        let start = self.mark();
+
+        let mut parts = StringParts::default();
+        self.read_char();
+
+        let closed = loop {
+            match self.current_ch {
+                None | Some('\n') => break false,
+                Some('"') => {
+                    self.read_char();
+                    break true;
+                }
+                Some('\\') => self.read_string_escape(&mut parts),
+                Some('$') if self.peek_char_is('{') => {
+                    if !self.read_interpolation(&mut parts) {
+                        break false;
+                    }
+                }
+                Some(ch) => {
+                    parts.push(ch);
+                    self.read_char();
+                }
+            }
+        };
+
+        match closed {
+            true => parts.into_kind(),
+            false => Ok(parts.into_unterminated()),
+        }
+        .map_or_else(
+            |invalid_escape| invalid_escape,
+            |kind| self.token_since(kind, start),
+        )
+    }
+
+    /// Reads a `"""` string, which may span lines. Escapes and interpolations work as in `"` strings.
+    ///
+    /// A line break right after the opening delimiter is skipped. When the closing delimiter
+    /// is on its own line, the line break before it is dropped and its indentation is stripped
+    /// from the start of every line.
+    fn read_multiline_string(&mut self) -> Token {
+        let start = self.mark();
+        for _ in 0..3 {
             self.read_char();
         }
+        if self.current_ch == Some('\n') {
+            self.read_char();
+        }
+
+        // every line with the number of whitespace characters it starts with in the source
+        let mut lines = vec![];
+        let mut line = StringParts::default();
+        let mut indent = 0;
+        let mut in_indent = true;
+
+        let closed = loop {
+            match self.current_ch {
+                None => break false,
+                Some('"') if self.is_multiline_quote() => {
+                    for _ in 0..3 {
+                        self.read_char();
+                    }
+                    break true;
+                }
+                Some('\n') => {
+                    lines.push((std::mem::take(&mut line), indent));
+                    indent = 0;
+                    in_indent = true;
+                    self.read_char();
+                }
+                Some('\\') => {
+                    in_indent = false;
+                    self.read_string_escape(&mut line);
+                }
+                Some('$') if self.peek_char_is('{') => {
+                    in_indent = false;
+                    if !self.read_interpolation(&mut line) {
+                        break false;
+                    }
+                }
+                Some(ch) => {
+                    if in_indent && ch.is_whitespace() {
+                        indent += 1;
+                    } else {
+                        in_indent = false;
+                    }
+                    line.push(ch);
+                    self.read_char();
+                }
+            }
+        };
 
-        if buf.contains('.') {
-            Token::new(TokenKind::Float(buf.parse().unwrap()), token_position)
+        if closed && in_indent && !lines.is_empty() {
+            // the closing line holds nothing but the indentation
+            for (line, line_indent) in &mut lines {
+                line.strip_start((*line_indent).min(indent));
+            }
         } else {
-            Token::new(TokenKind::Integer(buf.parse().unwrap()), token_position)
+            lines.push((line, indent));
         }
+
+        let parts = StringParts::join_lines(lines.into_iter().map(|(line, _)| line));
+        match closed {
+            true => parts.into_kind(),
+            false => Ok(parts.into_unterminated()),
+        }
+        .map_or_else(
+            |invalid_escape| invalid_escape,
+            |kind| self.token_since(kind, start),
+        )
     }
 
-    fn read_string(&mut self) -> Token {
-        let token_position = self.position;
+    /// Reads an escape sequence into the string. Only the first invalid one is kept.
+    fn read_string_escape(&mut self, parts: &mut StringParts) {
+        let start = self.mark();
+        match self.read_escape() {
+            Ok(ch) => parts.push(ch),
+            Err(text) => {
+                parts
+                    .invalid_escape
+                    .get_or_insert(self.token_since(TokenKind::InvalidEscape(text), start));
+            }
+        }
+    }
+
+    /// Reads a `${...}` interpolation up to the matching `}`, the source between them is kept.
+    /// Returns `false` if the input ends first.
+    fn read_interpolation(&mut self, parts: &mut StringParts) -> bool {
+        self.read_char(); // consume `$`
+        let position = self.position;
+        let start = self.offset + 1;
+        self.read_char(); // consume `{`
+
+        let mut depth = 0;
+        loop {
+            match self.next().map(|token| token.kind) {
+                None => {
+                    // the rest of the input is text of the unterminated string
+                    let rest = &self.input[start..];
+                    format!("${{{rest}").chars().for_each(|ch| parts.push(ch));
+                    return false;
+                }
+                Some(TokenKind::LeftCurly) => depth += 1,
+                Some(TokenKind::RightCurly) if depth == 0 => break,
+                Some(TokenKind::RightCurly) => depth -= 1,
+                _ => {}
+            }
+        }
+
+        // the `}` is right behind the current character
+        let source = self.input[start..self.offset - 1].to_string();
+        parts.interpolate(source, position, self.base_offset + start);
+        true
+    }
+
+    /// Checks if the current character starts a `"""` delimiter.
+    fn is_multiline_quote(&self) -> bool {
+        self.current_ch == Some('"')
+            && self.peek_char_is('"')
+            && self.input[self.offset..].starts_with(r#"""""#)
+    }
+
+    /// Reads an escape sequence starting at the current backslash.
+    /// Returns the text of the sequence if it's invalid.
+    fn read_escape(&mut self) -> Result<char, String> {
+        let mut text = String::from('\\');
+        self.read_char();
+
+        let ch = match self.current_ch {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('0') => '\0',
+            Some('\\') => '\\',
+            Some('"') => '"',
+            Some('$') => '$',
+            Some('u') => return self.read_unicode_escape(),
+            // the end of the line is left to end the string
+            None | Some('\n') => return Err(text),
+            Some(ch) => {
+                text.push(ch);
+                self.read_char();
+                return Err(text);
+            }
+        };
+
+        self.read_char();
+        Ok(ch)
+    }
+
+    /// Reads the `u{...}` part of a unicode escape, with up to 6 hex digits.
+    fn read_unicode_escape(&mut self) -> Result<char, String> {
+        let mut text = String::from("\\u");
+        self.read_char();
+
+        if self.current_ch != Some('{') {
+            return Err(text);
+        }
+        text.push('{');
+        self.read_char();
+
+        let mut digits = String::new();
+        while let Some(ch) = self.current_ch.filter(|ch| ch.is_ascii_hexdigit()) {
+            digits.push(ch);
+            text.push(ch);
+            self.read_char();
+        }
+
+        if self.current_ch != Some('}') {
+            return Err(text);
+        }
+        text.push('}');
+        self.read_char();
+
+        if digits.is_empty() || digits.len() > 6 {
+            return Err(text);
+        }
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or(text)
+    }
+
+    /// Reads a raw string, `r"..."` or `r#"..."#` with any number of `#`.
+    /// Nothing is escaped, the string ends at a quote followed by as many `#` as it started with.
+    fn read_raw_string(&mut self) -> Token {
+        let start = self.mark();
+        self.read_char(); // consume `r`
+
+        let mut hashes = 0;
+        while self.current_ch == Some('#') {
+            hashes += 1;
+            self.read_char();
+        }
+        if self.current_ch != Some('"') {
+            return self.token_since(TokenKind::ILLEGAL('#'), start);
+        }
+        self.read_char();
 
         let mut buf = String::new();
+        loop {
+            match self.current_ch {
+                None => return self.token_since(TokenKind::UnterminatedString(buf), start),
+                Some('"') => {
+                    self.read_char();
+                    let mut closing = 0;
+                    while closing < hashes && self.current_ch == Some('#') {
+                        closing += 1;
+                        self.read_char();
+                    }
+                    if closing == hashes {
+                        break;
+                    }
+                    // not the end, the quote and the hashes are content
+                    buf.push('"');
+                    buf.push_str(&"#".repeat(closing));
+                }
+                Some(ch) => {
+                    buf.push(ch);
+                    self.read_char();
+                }
+            }
+        }
+
+        self.token_since(TokenKind::String(buf), start)
+    }
+
+    /// Reads a `//` comment up to the end of the line.
+    /// Returns a token for `///` doc comments only, other comments are skipped.
+    fn read_line_comment(&mut self) -> Option<Token> {
+        let start = self.mark();
+        self.read_char();
         self.read_char();
 
-        while self.current_ch.is_some()
-            && self.current_ch != Some('"')
-            && self.current_ch != Some('\n')
-        {
-            buf.push(self.current_ch.unwrap());
+        // `////` is a plain comment, like in Rust
+        let is_doc = self.current_ch == Some('/') && !self.peek_char_is('/');
+        if is_doc {
             self.read_char();
         }
 
-        if self.current_ch == Some('"') {
+        let mut text = String::new();
+        while let Some(ch) = self.current_ch.filter(|&ch| ch != '\n') {
+            text.push(ch);
+            self.read_char();
+        }
+
+        is_doc.then(|| self.token_since(TokenKind::DocComment(text), start))
+    }
+
+    /// Skips a `/* */` comment, which may contain other block comments.
+    /// Returns a token only if the input ends before the comment is closed.
+    fn read_block_comment(&mut self) -> Option<Token> {
+        let start = self.mark();
+        self.read_char();
+        self.read_char();
+
+        let mut depth = 1;
+        while depth > 0 {
+            match (self.current_ch, self.peek_ch) {
+                (None, _) => return Some(self.token_since(TokenKind::UnterminatedComment, start)),
+                (Some('/'), Some('*')) => {
+                    depth += 1;
+                    self.read_char();
+                }
+                (Some('*'), Some('/')) => {
+                    depth -= 1;
+                    self.read_char();
+                }
+                _ => {}
+            }
             self.read_char();
         }
 
-        Token::new(TokenKind::String(buf), token_position)
+        None
     }
 
     /// Checks if the current character is an alphabetic character or an underscore.
@@ -144,93 +519,245 @@ impl Lexer {
     }
 }
 
+/// Where a token starts in the source.
+#[derive(Debug, Clone, Copy)]
+struct Mark {
+    position: Position,
+    offset: usize,
+}
+
+/// Text and interpolations of a string literal being read.
+#[derive(Debug, Default)]
+struct StringParts {
+    parts: Vec<TemplatePart>,
+    /// First invalid escape sequence, it's reported instead of the string.
+    invalid_escape: Option<Token>,
+}
+
+impl StringParts {
+    fn push(&mut self, ch: char) {
+        match self.parts.last_mut() {
+            Some(TemplatePart::Text(text)) => text.push(ch),
+            _ => self.parts.push(TemplatePart::Text(ch.to_string())),
+        }
+    }
+
+    fn interpolate(&mut self, source: String, position: Position, offset: usize) {
+        self.parts.push(TemplatePart::Interpolation {
+            source,
+            position,
+            offset,
+        });
+    }
+
+    /// Removes up to `n` characters from the start of the text.
+    fn strip_start(&mut self, n: usize) {
+        if let Some(TemplatePart::Text(text)) = self.parts.first_mut() {
+            *text = text.chars().skip(n).collect();
+        }
+    }
+
+    fn join_lines(lines: impl Iterator<Item = StringParts>) -> Self {
+        let mut joined = StringParts::default();
+        for (i, line) in lines.enumerate() {
+            if i > 0 {
+                joined.push('\n');
+            }
+            for part in line.parts {
+                match part {
+                    TemplatePart::Text(text) => text.chars().for_each(|ch| joined.push(ch)),
+                    part => joined.parts.push(part),
+                }
+            }
+            joined.invalid_escape = joined.invalid_escape.or(line.invalid_escape);
+        }
+        joined
+    }
+
+    /// A plain string without interpolations or a template with them.
+    /// Fails with the invalid escape if there is one.
+    fn into_kind(self) -> Result<TokenKind, Token> {
+        if let Some(token) = self.invalid_escape {
+            return Err(token);
+        }
+
+        let mut parts = self.parts;
+        parts.retain(|part| part != &TemplatePart::Text(String::new()));
+        Ok(match parts.as_slice() {
+            [] => TokenKind::String(String::new()),
+            [TemplatePart::Text(text)] => TokenKind::String(text.clone()),
+            _ => TokenKind::Template(parts),
+        })
+    }
+
+    fn into_unterminated(self) -> TokenKind {
+        TokenKind::UnterminatedString(self.parts.iter().map(TemplatePart::literal).collect())
+    }
+}
+
+/// Parses integer digits, without a radix prefix. Integers that don't fit in `i64` are big.
+fn parse_integer(digits: &str, radix: u32) -> Option<TokenKind> {
+    let digits = digits.replace('_', "");
+    if let Ok(integer) = i64::from_str_radix(&digits, radix) {
+        return Some(TokenKind::Integer(integer));
+    }
+    // `from_str_radix` accepts a sign, a literal can't have one
+    if digits.starts_with(['+', '-']) {
+        return None;
+    }
+    BigInt::parse_bytes(digits.as_bytes(), radix).map(TokenKind::BigInt)
+}
+
+/// Floats too big to be finite are malformed.
+fn parse_float(text: &str) -> Option<TokenKind> {
+    text.replace('_', "")
+        .parse::<f64>()
+        .ok()
+        .filter(|float| float.is_finite())
+        .map(TokenKind::Float)
+}
+
 impl Iterator for Lexer {
     type Item = Token;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.fuck_whitespaces();
-
-        let ch = self.current_ch;
-        if ch.is_none() {
-            return None;
+        loop {
+            self.fuck_whitespaces();
+            let comment = match (self.current_ch, self.peek_ch) {
+                (Some('/'), Some('/')) => self.read_line_comment(),
+                (Some('/'), Some('*')) => self.read_block_comment(),
+                _ => break,
+            };
+            if comment.is_some() {
+                return comment;
+            }
         }
 
-        let token_position = self.position;
+        let ch = self.current_ch?;
+        let start = self.mark();
 
-        let token = match ch.expect("cant be None") {
-            '+' => Token::new(TokenKind::Plus, token_position),
-            '-' => Token::new(TokenKind::Minus, token_position),
-            '*' => Token::new(TokenKind::Asterisk, token_position),
+        let kind = match ch {
+            '+' => {
+                if self.peek_char_is('=') {
+                    self.read_char();
+                    TokenKind::PlusEquals
+                } else {
+                    TokenKind::Plus
+                }
+            }
+            '-' => {
+                if self.peek_char_is('=') {
+                    self.read_char();
+                    TokenKind::MinusEquals
+                } else {
+                    TokenKind::Minus
+                }
+            }
+            '*' => {
+                if self.peek_char_is('=') {
+                    self.read_char();
+                    TokenKind::AsteriskEquals
+                } else {
+                    TokenKind::Asterisk
+                }
+            }
             '/' => {
-                if self.peek_char_is('/') {
-                    while self.current_ch != Some('\n') || self.current_ch.is_none() {
+                if self.peek_char_is('=') {
+                    self.read_char();
+                    TokenKind::SlashEquals
+                } else {
+                    TokenKind::Slash
+                }
+            }
+
+            ';' => TokenKind::SemiColon,
+            ':' => TokenKind::Colon,
+            '(' => TokenKind::LeftParen,
+            ')' => TokenKind::RightParen,
+            '{' => TokenKind::LeftCurly,
+            '}' => TokenKind::RightCurly,
+            '[' => TokenKind::LeftBracket,
+            ']' => TokenKind::RightBracket,
+            ',' => TokenKind::Comma,
+            '.' => {
+                if self.peek_char_is('.') {
+                    self.read_char();
+                    if self.peek_char_is('=') {
                         self.read_char();
+                        TokenKind::DotDotEquals
+                    } else {
+                        TokenKind::DotDot
                     }
-                    return self.next();
                 } else {
-                    Token::new(TokenKind::Slash, token_position)
+                    TokenKind::Dot
                 }
             }
 
-            ';' => Token::new(TokenKind::SemiColon, token_position),
-            ':' => Token::new(TokenKind::Colon, token_position),
-            '(' => Token::new(TokenKind::LeftParen, token_position),
-            ')' => Token::new(TokenKind::RightParen, token_position),
-            '{' => Token::new(TokenKind::LeftCurly, token_position),
-            '}' => Token::new(TokenKind::RightCurly, token_position),
-            '[' => Token::new(TokenKind::LeftBracket, token_position),
-            ']' => Token::new(TokenKind::RightBracket, token_position),
-            ',' => Token::new(TokenKind::Comma, token_position),
-            '.' => Token::new(TokenKind::Dot, token_position),
-
             '=' => {
                 if self.peek_char_is('=') {
                     self.read_char();
-                    Token::new(TokenKind::EqualsEquals, token_position)
+                    TokenKind::EqualsEquals
+                } else if self.peek_char_is('>') {
+                    self.read_char();
+                    TokenKind::FatArrow
                 } else {
-                    Token::new(TokenKind::Equals, token_position)
+                    TokenKind::Equals
                 }
             }
             '!' => {
                 if self.peek_char_is('=') {
                     self.read_char();
-                    Token::new(TokenKind::BangEquals, token_position)
+                    TokenKind::BangEquals
                 } else {
-                    Token::new(TokenKind::Bang, token_position)
+                    TokenKind::Bang
                 }
             }
             '>' => {
                 if self.peek_char_is('=') {
                     self.read_char();
-                    Token::new(TokenKind::GreaterThanEquals, token_position)
+                    TokenKind::GreaterThanEquals
                 } else {
-                    Token::new(TokenKind::GreaterThan, token_position)
+                    TokenKind::GreaterThan
                 }
             }
             '<' => {
                 if self.peek_char_is('=') {
                     self.read_char();
-                    Token::new(TokenKind::LessThanEquals, token_position)
+                    TokenKind::LessThanEquals
                 } else {
-                    Token::new(TokenKind::LessThan, token_position)
+                    TokenKind::LessThan
                 }
             }
+            '&' if self.peek_char_is('&') => {
+                self.read_char();
+                TokenKind::AndAnd
+            }
+            '|' if self.peek_char_is('|') => {
+                self.read_char();
+                TokenKind::OrOr
+            }
+            '|' => TokenKind::Pipe,
 
+            '"' if self.is_multiline_quote() => return Some(self.read_multiline_string()),
             '"' => return Some(self.read_string()),
+            'r' if self.peek_char_is('"') || self.peek_char_is('#') => {
+                return Some(self.read_raw_string())
+            }
             _ if self.current_is_alphabetic() => return Some(self.read_alphabetic_token()),
             _ if self.current_is_numeric() => return Some(self.read_numeric_token()),
 
-            c => Token::new(TokenKind::ILLEGAL(c), token_position),
+            c => TokenKind::ILLEGAL(c),
         };
 
         self.read_char();
-        Some(token)
+        Some(self.token_since(kind, start))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::lexer::Span;
 
     #[test]
     fn test_smth_that_makes_sense() {
@@ -361,6 +888,75 @@ mod tests {
         assert_eq!(lexer.next(), None);
     }
 
+    #[test]
+    fn test_numeric_literal_syntax() {
+        let input = String::from(
+            "0xFF 0o755 0b1010 1_000_000 1.5e-3 2E10 0x_dead_BEEF 1e+2 0xFFFFFFFFFFFFFFFF",
+        );
+        let tokens = Lexer::new(input).collect::<Vec<Token>>();
+
+        let expected = [
+            (TokenKind::Integer(255), Position(1, 1), Position(1, 4)),
+            (TokenKind::Integer(0o755), Position(1, 6), Position(1, 10)),
+            (TokenKind::Integer(10), Position(1, 12), Position(1, 17)),
+            (
+                TokenKind::Integer(1_000_000),
+                Position(1, 19),
+                Position(1, 27),
+            ),
+            (TokenKind::Float(0.0015), Position(1, 29), Position(1, 34)),
+            (TokenKind::Float(2e10), Position(1, 36), Position(1, 39)),
+            (
+                TokenKind::Integer(0xdeadbeef),
+                Position(1, 41),
+                Position(1, 52),
+            ),
+            (TokenKind::Float(100.0), Position(1, 54), Position(1, 57)),
+            (
+                TokenKind::BigInt(u64::MAX.into()),
+                Position(1, 59),
+                Position(1, 76),
+            ),
+        ];
+        assert_eq!(
+            tokens,
+            expected
+                .into_iter()
+                .map(|(kind, start, end)| Token::spanning(kind, start, end))
+                .collect::<Vec<Token>>()
+        );
+    }
+
+    #[test]
+    fn test_malformed_numeric_literals() {
+        for input in ["0x", "0b102", "0o8", "0xG1", "1e+", "1e999", "1.2e3.4"] {
+            let mut lexer = Lexer::new(input.to_string());
+            assert_eq!(
+                lexer.next().map(|token| token.kind),
+                Some(TokenKind::MalformedNumber(input.to_string())),
+                "{input}"
+            );
+            assert_eq!(lexer.next(), None, "{input}");
+        }
+
+        // an `e` that doesn't start an exponent is a separate identifier
+        let kinds = Lexer::new("2else".to_string())
+            .map(|token| token.kind)
+            .collect::<Vec<TokenKind>>();
+        assert_eq!(kinds, vec![TokenKind::Integer(2), TokenKind::Else]);
+    }
+
+    #[test]
+    fn test_numeric_literal_round_trip() {
+        let input = "0xFF 1_000 1.0 2E10 1.5e-3 1e300 0.1 123456789.125 9223372036854775808";
+        for token in Lexer::new(input.to_string()) {
+            let literal = token.literal();
+            let relexed = Lexer::new(literal.clone()).collect::<Vec<Token>>();
+            assert_eq!(relexed.len(), 1, "{literal}");
+            assert_eq!(relexed[0].kind, token.kind, "{literal}");
+        }
+    }
+
     #[test]
     fn test_punctuation_tokens() {
         let input = String::from(";:[]{}(),.");
@@ -408,9 +1004,32 @@ mod tests {
         assert_eq!(lexer.next(), None);
     }
 
+    #[test]
+    fn test_range_tokens() {
+        let input = String::from("0..10 1..=2.5 . x..y");
+        let mut lexer = Lexer::new(input);
+        let expected = vec![
+            Token::new(TokenKind::Integer(0), Position(1, 1)),
+            Token::new(TokenKind::DotDot, Position(1, 2)),
+            Token::new(TokenKind::Integer(10), Position(1, 4)),
+            Token::new(TokenKind::Integer(1), Position(1, 7)),
+            Token::new(TokenKind::DotDotEquals, Position(1, 8)),
+            Token::new(TokenKind::Float(2.5), Position(1, 11)),
+            Token::new(TokenKind::Dot, Position(1, 15)),
+            Token::new(TokenKind::Identifier("x".to_string()), Position(1, 17)),
+            Token::new(TokenKind::DotDot, Position(1, 18)),
+            Token::new(TokenKind::Identifier("y".to_string()), Position(1, 20)),
+        ];
+
+        for token in expected {
+            assert_eq!(lexer.next(), Some(token));
+        }
+        assert_eq!(lexer.next(), None);
+    }
+
     #[test]
     fn test_alphabetic_token() {
-        let input = String::from("let what_x return if else");
+        let input = String::from("let what_x return if else while break continue");
         let mut lexer = Lexer::new(input);
         assert_eq!(
             lexer.next(),
@@ -435,6 +1054,18 @@ mod tests {
             lexer.next(),
             Some(Token::new(TokenKind::Else, Position(1, 22)))
         );
+        assert_eq!(
+            lexer.next(),
+            Some(Token::new(TokenKind::While, Position(1, 27)))
+        );
+        assert_eq!(
+            lexer.next(),
+            Some(Token::new(TokenKind::Break, Position(1, 33)))
+        );
+        assert_eq!(
+            lexer.next(),
+            Some(Token::new(TokenKind::Continue, Position(1, 39)))
+        );
         assert_eq!(lexer.next(), None);
     }
 
@@ -456,11 +1087,7 @@ mod tests {
         );
         assert_eq!(
             lexer.next(),
-            Some(Token::new(TokenKind::Slash, Position(1, 4)))
-        );
-        assert_eq!(
-            lexer.next(),
-            Some(Token::new(TokenKind::Equals, Position(1, 5)))
+            Some(Token::new(TokenKind::SlashEquals, Position(1, 4)))
         );
         assert_eq!(
             lexer.next(),
@@ -494,13 +1121,73 @@ mod tests {
         assert_eq!(lexer.next(), None);
     }
 
+    #[test]
+    fn test_compound_assign_tokens() {
+        let input = String::from("+= -= *= /= / = + =");
+        let mut lexer = Lexer::new(input);
+        let expected = vec![
+            Token::new(TokenKind::PlusEquals, Position(1, 1)),
+            Token::new(TokenKind::MinusEquals, Position(1, 4)),
+            Token::new(TokenKind::AsteriskEquals, Position(1, 7)),
+            Token::new(TokenKind::SlashEquals, Position(1, 10)),
+            Token::new(TokenKind::Slash, Position(1, 13)),
+            Token::new(TokenKind::Equals, Position(1, 15)),
+            Token::new(TokenKind::Plus, Position(1, 17)),
+            Token::new(TokenKind::Equals, Position(1, 19)),
+        ];
+
+        for token in expected {
+            assert_eq!(lexer.next(), Some(token));
+        }
+        assert_eq!(lexer.next(), None);
+    }
+
+    #[test]
+    fn test_logical_tokens() {
+        let input = String::from("&& || & |");
+        let mut lexer = Lexer::new(input);
+        let expected = vec![
+            Token::new(TokenKind::AndAnd, Position(1, 1)),
+            Token::new(TokenKind::OrOr, Position(1, 4)),
+            Token::new(TokenKind::ILLEGAL('&'), Position(1, 7)),
+            Token::new(TokenKind::Pipe, Position(1, 9)),
+        ];
+
+        for token in expected {
+            assert_eq!(lexer.next(), Some(token));
+        }
+        assert_eq!(lexer.next(), None);
+    }
+
+    #[test]
+    fn test_match_tokens() {
+        let input = String::from("match x { 1 | 2 => _ }");
+        let mut lexer = Lexer::new(input);
+        let expected = vec![
+            Token::new(TokenKind::Match, Position(1, 1)),
+            Token::new(TokenKind::Identifier("x".to_string()), Position(1, 7)),
+            Token::new(TokenKind::LeftCurly, Position(1, 9)),
+            Token::new(TokenKind::Integer(1), Position(1, 11)),
+            Token::new(TokenKind::Pipe, Position(1, 13)),
+            Token::new(TokenKind::Integer(2), Position(1, 15)),
+            Token::new(TokenKind::FatArrow, Position(1, 17)),
+            Token::new(TokenKind::Identifier("_".to_string()), Position(1, 20)),
+            Token::new(TokenKind::RightCurly, Position(1, 22)),
+        ];
+
+        for token in expected {
+            assert_eq!(lexer.next(), Some(token));
+        }
+        assert_eq!(lexer.next(), None);
+    }
+
     #[test]
     fn test_new() {
         let input = String::from("80085");
         let lexer = Lexer::new(input.clone());
         assert_eq!(lexer.input, input);
-        assert_eq!(lexer.current_pos, 1);
-        assert_eq!(lexer.current_ch, input.chars().nth(0));
+        assert_eq!(lexer.offset, 0);
+        assert_eq!(lexer.current_ch, input.chars().next());
         assert_eq!(lexer.peek_ch, input.chars().nth(1));
     }
 
@@ -509,7 +1196,7 @@ mod tests {
         let input = String::from("  80085");
         let mut lexer = Lexer::new(input.clone());
         lexer.fuck_whitespaces();
-        assert_eq!(lexer.current_pos, 3);
+        assert_eq!(lexer.offset, 2);
         assert_eq!(lexer.current_ch, input.chars().nth(2));
         assert_eq!(lexer.peek_ch, input.chars().nth(3));
     }
@@ -519,19 +1206,19 @@ mod tests {
         let input = String::from("ick");
         let mut lexer = Lexer::new(input.clone());
         lexer.read_char();
-        assert_eq!(lexer.current_pos, 2);
+        assert_eq!(lexer.offset, 1);
         assert_eq!(lexer.current_ch, input.chars().nth(1));
         assert_eq!(lexer.peek_ch, input.chars().nth(2));
         lexer.read_char();
-        assert_eq!(lexer.current_pos, 3);
+        assert_eq!(lexer.offset, 2);
         assert_eq!(lexer.current_ch, input.chars().nth(2));
         assert_eq!(lexer.peek_ch, input.chars().nth(3));
         lexer.read_char();
-        assert_eq!(lexer.current_pos, 4);
+        assert_eq!(lexer.offset, 3);
         assert_eq!(lexer.current_ch, input.chars().nth(3));
         assert_eq!(lexer.peek_ch, None);
         lexer.read_char();
-        assert_eq!(lexer.current_pos, 5);
+        assert_eq!(lexer.offset, 3);
         assert_eq!(lexer.current_ch, None);
         assert_eq!(lexer.peek_ch, None);
     }
@@ -630,4 +1317,435 @@ mod tests {
             Some(Token::new(TokenKind::RightCurly, Position(4, 1)))
         );
     }
+
+    #[test]
+    fn test_token_spans() {
+        let input = String::from("let name = \"tsykr\";\n1.50 ..= 10");
+        let spans = Lexer::new(input)
+            .map(|token| token.span())
+            .collect::<Vec<Span>>();
+
+        assert_eq!(
+            spans,
+            vec![
+                Span::new(Position(1, 1), Position(1, 3)),
+                Span::new(Position(1, 5), Position(1, 8)),
+                Span::new(Position(1, 10), Position(1, 10)),
+                Span::new(Position(1, 12), Position(1, 18)),
+                Span::new(Position(1, 19), Position(1, 19)),
+                Span::new(Position(2, 1), Position(2, 4)),
+                Span::new(Position(2, 6), Position(2, 8)),
+                Span::new(Position(2, 10), Position(2, 11)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_big_integer_token() {
+        let input = String::from("9223372036854775807 9223372036854775808");
+        let mut lexer = Lexer::new(input);
+
+        assert_eq!(
+            lexer.next(),
+            Some(Token::new(TokenKind::Integer(i64::MAX), Position(1, 1)))
+        );
+        assert_eq!(
+            lexer.next(),
+            Some(Token::new(
+                TokenKind::BigInt("9223372036854775808".parse().unwrap()),
+                Position(1, 21)
+            ))
+        );
+        assert_eq!(lexer.next(), None);
+    }
+
+    #[test]
+    fn test_malformed_number_token() {
+        let input = String::from("1.2.3 4");
+        let mut lexer = Lexer::new(input);
+
+        assert_eq!(
+            lexer.next(),
+            Some(Token::new(
+                TokenKind::MalformedNumber("1.2.3".to_string()),
+                Position(1, 1)
+            ))
+        );
+        assert_eq!(
+            lexer.next(),
+            Some(Token::new(TokenKind::Integer(4), Position(1, 7)))
+        );
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        let input = String::from(r#""a\nb\t\"c\" \\ \0\r" "\u{1F600}\u{e9}" 1"#);
+        let mut lexer = Lexer::new(input);
+
+        assert_eq!(
+            lexer.next(),
+            Some(Token::spanning(
+                TokenKind::String("a\nb\t\"c\" \\ \0\r".to_string()),
+                Position(1, 1),
+                Position(1, 21)
+            ))
+        );
+        assert_eq!(
+            lexer.next(),
+            Some(Token::spanning(
+                TokenKind::String("\u{1F600}\u{e9}".to_string()),
+                Position(1, 23),
+                Position(1, 39)
+            ))
+        );
+        assert_eq!(
+            lexer.next(),
+            Some(Token::new(TokenKind::Integer(1), Position(1, 41)))
+        );
+    }
+
+    #[test]
+    fn test_invalid_escapes() {
+        let cases = [
+            (r#""a\qb""#, "\\q", Position(1, 3), Position(1, 4)),
+            (
+                r#""\u{110000}""#,
+                "\\u{110000}",
+                Position(1, 2),
+                Position(1, 11),
+            ),
+            (r#""\u{}""#, "\\u{}", Position(1, 2), Position(1, 5)),
+            (
+                r#""\u{1234567}""#,
+                "\\u{1234567}",
+                Position(1, 2),
+                Position(1, 12),
+            ),
+            (r#""\u41""#, "\\u", Position(1, 2), Position(1, 3)),
+            (r#""\x \y""#, "\\x", Position(1, 2), Position(1, 3)),
+        ];
+
+        for (input, text, start, end) in cases {
+            let mut lexer = Lexer::new(input.to_string());
+            assert_eq!(
+                lexer.next(),
+                Some(Token::spanning(
+                    TokenKind::InvalidEscape(text.to_string()),
+                    start,
+                    end
+                )),
+                "{input}"
+            );
+            assert_eq!(lexer.next(), None, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_raw_strings() {
+        let input = String::from("r\"\\d+\\.\" r#\"say \"hi\"\"# r##\"a\"#b\n\"## r x");
+        let mut lexer = Lexer::new(input);
+
+        assert_eq!(
+            lexer.next(),
+            Some(Token::spanning(
+                TokenKind::String("\\d+\\.".to_string()),
+                Position(1, 1),
+                Position(1, 8)
+            ))
+        );
+        assert_eq!(
+            lexer.next(),
+            Some(Token::spanning(
+                TokenKind::String("say \"hi\"".to_string()),
+                Position(1, 10),
+                Position(1, 22)
+            ))
+        );
+        assert_eq!(
+            lexer.next(),
+            Some(Token::spanning(
+                TokenKind::String("a\"#b\n".to_string()),
+                Position(1, 24),
+                Position(2, 3)
+            ))
+        );
+        assert_eq!(
+            lexer.next(),
+            Some(Token::new(
+                TokenKind::Identifier("r".to_string()),
+                Position(2, 5)
+            ))
+        );
+        assert_eq!(
+            lexer.next(),
+            Some(Token::new(
+                TokenKind::Identifier("x".to_string()),
+                Position(2, 7)
+            ))
+        );
+        assert_eq!(lexer.next(), None);
+    }
+
+    #[test]
+    fn test_unterminated_strings() {
+        let input = String::from("\"abc\nlet a = \"x\\\"");
+        let mut lexer = Lexer::new(input);
+
+        assert_eq!(
+            lexer.next(),
+            Some(Token::spanning(
+                TokenKind::UnterminatedString("abc".to_string()),
+                Position(1, 1),
+                Position(1, 4)
+            ))
+        );
+        assert_eq!(
+            lexer.next(),
+            Some(Token::new(TokenKind::Let, Position(2, 1)))
+        );
+        lexer.next();
+        lexer.next();
+        assert_eq!(
+            lexer.next(),
+            Some(Token::spanning(
+                TokenKind::UnterminatedString("x\"".to_string()),
+                Position(2, 9),
+                Position(2, 12)
+            ))
+        );
+        assert_eq!(lexer.next(), None);
+
+        let mut lexer = Lexer::new(String::from("r#\"open\" "));
+        assert_eq!(
+            lexer.next(),
+            Some(Token::spanning(
+                TokenKind::UnterminatedString("open\" ".to_string()),
+                Position(1, 1),
+                Position(1, 9)
+            ))
+        );
+        assert_eq!(
+            Lexer::new(String::from("r#x")).next(),
+            Some(Token::spanning(
+                TokenKind::ILLEGAL('#'),
+                Position(1, 1),
+                Position(1, 2)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_multiline_strings() {
+        let input = String::from(
+            "let q = \"\"\"\n    SELECT *\n      FROM t\n\n    WHERE \\\"a\\\" = 1\n    \"\"\";\nlet j = \"\"\"{\n  \"k\": \"\\t\"\n}\"\"\";\n\"\"\"\"\"",
+        );
+        let tokens = Lexer::new(input).collect::<Vec<Token>>();
+
+        assert_eq!(
+            tokens[3],
+            Token::spanning(
+                TokenKind::String("SELECT *\n  FROM t\n\nWHERE \"a\" = 1".to_string()),
+                Position(1, 9),
+                Position(6, 7)
+            )
+        );
+        assert_eq!(tokens[4], Token::new(TokenKind::SemiColon, Position(6, 8)));
+        assert_eq!(tokens[5], Token::new(TokenKind::Let, Position(7, 1)));
+        assert_eq!(
+            tokens[8],
+            Token::spanning(
+                TokenKind::String("{\n  \"k\": \"\t\"\n}".to_string()),
+                Position(7, 9),
+                Position(9, 4)
+            )
+        );
+        // the first three quotes open a string, the other two are its content
+        assert_eq!(
+            tokens[10],
+            Token::spanning(
+                TokenKind::UnterminatedString("\"\"".to_string()),
+                Position(10, 1),
+                Position(10, 5)
+            )
+        );
+        assert_eq!(tokens.len(), 11);
+    }
+
+    #[test]
+    fn test_unterminated_multiline_string() {
+        let input = String::from("\"\"\"\n  a\n  b\n");
+        let mut lexer = Lexer::new(input);
+
+        assert_eq!(
+            lexer.next(),
+            Some(Token::spanning(
+                TokenKind::UnterminatedString("  a\n  b\n".to_string()),
+                Position(1, 1),
+                Position(4, 0)
+            ))
+        );
+        assert_eq!(lexer.next(), None);
+    }
+
+    #[test]
+    fn test_template_strings() {
+        let input = String::from(r#""a ${x + 1}b${ f("}", {}) }" "\${x}" "${x"#);
+        let mut lexer = Lexer::new(input);
+
+        assert_eq!(
+            lexer.next(),
+            Some(Token::spanning(
+                TokenKind::Template(vec![
+                    TemplatePart::Text("a ".to_string()),
+                    TemplatePart::Interpolation {
+                        source: "x + 1".to_string(),
+                        position: Position(1, 5),
+                        offset: 5,
+                    },
+                    TemplatePart::Text("b".to_string()),
+                    TemplatePart::Interpolation {
+                        source: " f(\"}\", {}) ".to_string(),
+                        position: Position(1, 14),
+                        offset: 14,
+                    },
+                ]),
+                Position(1, 1),
+                Position(1, 28)
+            ))
+        );
+        assert_eq!(
+            lexer.next(),
+            Some(Token::spanning(
+                TokenKind::String("${x}".to_string()),
+                Position(1, 30),
+                Position(1, 36)
+            ))
+        );
+        assert_eq!(
+            lexer.next(),
+            Some(Token::spanning(
+                TokenKind::UnterminatedString("${x".to_string()),
+                Position(1, 38),
+                Position(1, 41)
+            ))
+        );
+        assert_eq!(lexer.next(), None);
+
+        let mut lexer = Lexer::starting_after("x + 1".to_string(), Position(1, 5), 5);
+        let token = lexer.next().unwrap();
+        assert_eq!(
+            token,
+            Token::new(TokenKind::Identifier("x".to_string()), Position(1, 6))
+        );
+        assert_eq!(token.bytes, 5..6);
+    }
+
+    #[test]
+    fn test_comments() {
+        let tests = [
+            ("1 // one", vec![TokenKind::Integer(1)]),
+            ("1 //", vec![TokenKind::Integer(1)]),
+            ("//\n//\n2 // two\n", vec![TokenKind::Integer(2)]),
+            (
+                "1 /* a /* b */ c */ / 2 /**/",
+                vec![
+                    TokenKind::Integer(1),
+                    TokenKind::Slash,
+                    TokenKind::Integer(2),
+                ],
+            ),
+            ("/* // */ 3", vec![TokenKind::Integer(3)]),
+            (
+                "a /= 2",
+                vec![
+                    TokenKind::Identifier("a".to_string()),
+                    TokenKind::SlashEquals,
+                    TokenKind::Integer(2),
+                ],
+            ),
+            ("/// doc", vec![TokenKind::DocComment(" doc".to_string())]),
+            ("////plain\n///", vec![TokenKind::DocComment(String::new())]),
+            (
+                "1 /* a /* b */",
+                vec![TokenKind::Integer(1), TokenKind::UnterminatedComment],
+            ),
+            ("/*", vec![TokenKind::UnterminatedComment]),
+        ];
+
+        for (input, expected) in tests {
+            let kinds = Lexer::new(input.to_string())
+                .map(|token| token.kind)
+                .collect::<Vec<TokenKind>>();
+            assert_eq!(kinds, expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_comment_spans() {
+        let input = String::from("/// Doc.\nx /* a\n/* b */\n");
+        let mut lexer = Lexer::new(input);
+
+        assert_eq!(
+            lexer.next(),
+            Some(Token::spanning(
+                TokenKind::DocComment(" Doc.".to_string()),
+                Position(1, 1),
+                Position(1, 8)
+            ))
+        );
+        assert_eq!(
+            lexer.next(),
+            Some(Token::new(
+                TokenKind::Identifier("x".to_string()),
+                Position(2, 1)
+            ))
+        );
+        assert_eq!(
+            lexer.next(),
+            Some(Token::spanning(
+                TokenKind::UnterminatedComment,
+                Position(2, 3),
+                Position(4, 0)
+            ))
+        );
+        assert_eq!(lexer.next(), None);
+    }
+
+    #[test]
+    fn test_token_bytes() {
+        let input = "let café = \"ü ${naïve + 1}\"; /* ß */ x /= 0x1F;\n/// ok\nr#\"∑\"#";
+        let tokens = Lexer::new(input.to_string()).collect::<Vec<Token>>();
+        let texts = tokens
+            .iter()
+            .map(|token| &input[token.bytes.clone()])
+            .collect::<Vec<&str>>();
+
+        assert_eq!(
+            texts,
+            vec![
+                "let",
+                "café",
+                "=",
+                "\"ü ${naïve + 1}\"",
+                ";",
+                "x",
+                "/=",
+                "0x1F",
+                ";",
+                "/// ok",
+                "r#\"∑\"#",
+            ]
+        );
+
+        let TokenKind::Template(parts) = &tokens[3].kind else {
+            panic!("Expected template, got {}", tokens[3].kind);
+        };
+        let TemplatePart::Interpolation { offset, .. } = parts[1] else {
+            panic!("Expected interpolation, got {:?}", parts[1]);
+        };
+        let naive = Lexer::starting_after("naïve + 1".to_string(), Position(1, 17), offset)
+            .next()
+            .unwrap();
+        assert_eq!(&input[naive.bytes], "naïve");
+    }
 }
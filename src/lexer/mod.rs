@@ -1,3 +1,7 @@
+#[allow(
+    clippy::module_inception,
+    reason = "private module, its items are re-exported as crate::lexer::*"
+)]
 mod lexer;
 mod token;
 
@@ -1,4 +1,6 @@
-use std::fmt::Display;
+use std::{fmt::Display, ops::Range};
+
+use num_bigint::BigInt;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum TokenKind {
@@ -21,16 +23,32 @@ pub enum TokenKind {
     RightBracket,
     Comma,
     Dot,
+    DotDot,
+    DotDotEquals,
 
     EqualsEquals,
     BangEquals,
     GreaterThanEquals,
     LessThanEquals,
+    PlusEquals,
+    MinusEquals,
+    AsteriskEquals,
+    SlashEquals,
+    AndAnd,
+    OrOr,
+    /// `=>` between a `match` pattern and its body.
+    FatArrow,
+    /// `|` between alternative `match` patterns.
+    Pipe,
 
     Identifier(String),
     Integer(i64),
+    /// Integer literal that doesn't fit in `i64`.
+    BigInt(BigInt),
     Float(f64),
     String(String),
+    /// String literal with `${...}` interpolations.
+    Template(Vec<TemplatePart>),
 
     True,
     False,
@@ -40,7 +58,23 @@ pub enum TokenKind {
     Return,
     If,
     Else,
+    While,
+    For,
+    In,
+    Break,
+    Continue,
+    Match,
 
+    /// Numeric literal that can't be read as a number, like `1.2.3`.
+    MalformedNumber(String),
+    /// Escape sequence in a string that doesn't stand for a character, like `\q`.
+    InvalidEscape(String),
+    /// String literal without the closing quote, holds the text read so far.
+    UnterminatedString(String),
+    /// `/*` comment without the closing `*/`.
+    UnterminatedComment,
+    /// Text of a `///` comment, after the slashes.
+    DocComment(String),
     ILLEGAL(char),
 }
 
@@ -54,6 +88,12 @@ impl TokenKind {
             "return" => Self::Return,
             "if" => Self::If,
             "else" => Self::Else,
+            "while" => Self::While,
+            "for" => Self::For,
+            "in" => Self::In,
+            "break" => Self::Break,
+            "continue" => Self::Continue,
+            "match" => Self::Match,
             "true" => Self::True,
             "false" => Self::False,
 
@@ -82,16 +122,29 @@ impl TokenKind {
             Self::RightBracket => "]".to_string(),
             Self::Comma => ",".to_string(),
             Self::Dot => ".".to_string(),
+            Self::DotDot => "..".to_string(),
+            Self::DotDotEquals => "..=".to_string(),
 
             Self::EqualsEquals => "==".to_string(),
             Self::BangEquals => "!=".to_string(),
             Self::GreaterThanEquals => ">=".to_string(),
             Self::LessThanEquals => "<=".to_string(),
+            Self::PlusEquals => "+=".to_string(),
+            Self::MinusEquals => "-=".to_string(),
+            Self::AsteriskEquals => "*=".to_string(),
+            Self::SlashEquals => "/=".to_string(),
+            Self::AndAnd => "&&".to_string(),
+            Self::OrOr => "||".to_string(),
+            Self::FatArrow => "=>".to_string(),
+            Self::Pipe => "|".to_string(),
 
             Self::Identifier(str) => str.clone(),
             Self::Integer(integer) => integer.to_string(),
-            Self::Float(float) => float.to_string(),
+            Self::BigInt(integer) => integer.to_string(),
+            // debug formatting keeps a fraction or an exponent, so it's lexed as a float again
+            Self::Float(float) => format!("{float:?}"),
             Self::String(string) => string.to_string(),
+            Self::Template(parts) => parts.iter().map(TemplatePart::literal).collect(),
 
             Self::True => "true".to_string(),
             Self::False => "false".to_string(),
@@ -101,7 +154,18 @@ impl TokenKind {
             Self::Return => "return".to_string(),
             Self::If => "if".to_string(),
             Self::Else => "else".to_string(),
+            Self::While => "while".to_string(),
+            Self::For => "for".to_string(),
+            Self::In => "in".to_string(),
+            Self::Break => "break".to_string(),
+            Self::Continue => "continue".to_string(),
+            Self::Match => "match".to_string(),
 
+            Self::MalformedNumber(text) => text.clone(),
+            Self::InvalidEscape(text) => text.clone(),
+            Self::UnterminatedString(text) => format!("\"{text}"),
+            Self::UnterminatedComment => "/*".to_string(),
+            Self::DocComment(text) => format!("///{text}"),
             Self::ILLEGAL(char) => char.to_string(),
         }
     }
@@ -112,13 +176,42 @@ impl Display for TokenKind {
         match self {
             Self::Identifier(name) => write!(f, "Identifier({})", name),
             Self::Integer(value) => write!(f, "Integer({})", value),
+            Self::BigInt(value) => write!(f, "BigInt({})", value),
             Self::Float(value) => write!(f, "Float({})", value),
+            Self::MalformedNumber(text) => write!(f, "MalformedNumber({})", text),
+            Self::InvalidEscape(text) => write!(f, "InvalidEscape({})", text),
+            Self::UnterminatedString(text) => write!(f, "UnterminatedString({})", text),
+            Self::DocComment(text) => write!(f, "DocComment({})", text),
             Self::ILLEGAL(c) => write!(f, "Illegal({})", c),
             _ => write!(f, "{}", self.literal()),
         }
     }
 }
 
+/// Piece of a template string.
+#[derive(Debug, PartialEq, Clone)]
+pub enum TemplatePart {
+    /// Text with escapes already replaced.
+    Text(String),
+    /// Source of a `${...}` expression, lexed again by the parser.
+    Interpolation {
+        source: String,
+        /// Position of the `{` before the source.
+        position: Position,
+        /// Byte offset of the source.
+        offset: usize,
+    },
+}
+
+impl TemplatePart {
+    pub fn literal(&self) -> String {
+        match self {
+            Self::Text(text) => text.clone(),
+            Self::Interpolation { source, .. } => format!("${{{source}}}"),
+        }
+    }
+}
+
 /// Represents the position of a token in the source code.
 /// 0 - line; 1 - column
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -130,10 +223,41 @@ impl Display for Position {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// Source range of a token, both ends are inclusive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    pub fn new(start: Position, end: Position) -> Self {
+        Self { start, end }
+    }
+
+    /// Span of a single character.
+    pub fn point(position: Position) -> Self {
+        Self::new(position, position)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Token {
     pub kind: TokenKind,
+    /// Position of the first character.
     pub position: Position,
+    /// Position of the last character.
+    pub end: Position,
+    /// Byte range of the token in the source, empty for tokens that weren't lexed.
+    pub bytes: Range<usize>,
+}
+
+/// Byte offsets follow from the positions in the same source, so they aren't compared.
+/// Tokens built by hand don't have them.
+impl PartialEq for Token {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind && self.position == other.position && self.end == other.end
+    }
 }
 
 impl Default for Token {
@@ -141,13 +265,39 @@ impl Default for Token {
         Self {
             kind: TokenKind::ILLEGAL(' '),
             position: Position(0, 0),
+            end: Position(0, 0),
+            bytes: 0..0,
         }
     }
 }
 
 impl Token {
+    /// Creates a token whose source text is its literal, or a quoted literal for strings.
     pub fn new(kind: TokenKind, position: Position) -> Self {
-        Self { kind, position }
+        let width = match &kind {
+            TokenKind::String(_) | TokenKind::Template(_) => kind.literal().chars().count() + 2,
+            kind => kind.literal().chars().count().max(1),
+        };
+        let end = Position(position.0, position.1 + width - 1);
+        Self::spanning(kind, position, end)
+    }
+
+    pub fn spanning(kind: TokenKind, position: Position, end: Position) -> Self {
+        Self {
+            kind,
+            position,
+            end,
+            bytes: 0..0,
+        }
+    }
+
+    pub fn with_bytes(mut self, bytes: Range<usize>) -> Self {
+        self.bytes = bytes;
+        self
+    }
+
+    pub fn span(&self) -> Span {
+        Span::new(self.position, self.end)
     }
 
     pub fn literal(&self) -> String {
@@ -0,0 +1,14 @@
+mod diagnostic;
+mod engine;
+mod eval;
+mod lexer;
+mod parser;
+
+pub use diagnostic::Diagnostic;
+pub use engine::{Engine, EngineError, Script};
+pub use eval::{
+    Builtin, BuiltinCall, EvalError, FromValue, IntoBuiltin, IntoValue, IntoValueResult, MapKey,
+    MapKeyType, StackFrame, Value, DEFAULT_MAX_CALL_DEPTH,
+};
+pub use lexer::{Lexer, Position, Span, TemplatePart, Token, TokenKind};
+pub use parser::ParserError;
@@ -1,47 +1,35 @@
 use anyhow::Result;
 use clap::Parser;
-use eval::{Eval, ExecutionEnvironment};
-use lexer::Lexer;
+use tsykr::{Engine, DEFAULT_MAX_CALL_DEPTH};
 
-mod eval;
-mod lexer;
-mod parser;
 mod repl;
 
 #[derive(Debug, Parser)]
 struct Args {
     path: Option<String>,
+    /// Number of nested function calls after which a script fails with a stack overflow.
+    #[arg(long, default_value_t = DEFAULT_MAX_CALL_DEPTH)]
+    max_call_depth: usize,
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
 
+    let mut engine = Engine::new();
+    engine.set_max_call_depth(args.max_call_depth);
+
     match args.path {
         Some(path) => {
             let content = std::fs::read_to_string(path)?;
 
-            let lexer = Lexer::new(content);
-            let mut parser = parser::Parser::new(lexer);
-
-            let env = ExecutionEnvironment::new_global();
-
-            match parser.parse() {
-                Ok(program) => {
-                    if let Err(err) = program.eval(env) {
-                        eprintln!("Runtime error: {err}")
-                    }
-                }
-                Err(errs) => {
-                    eprintln!("Parser errors:");
-                    for err in errs {
-                        eprintln!("\t{err}");
-                    }
-                    eprintln!()
+            if let Err(err) = engine.eval(&content) {
+                for diagnostic in err.diagnostics() {
+                    eprintln!("{}", diagnostic.render(&content));
                 }
             }
         }
         None => {
-            repl::run();
+            repl::run(engine);
         }
     }
 
@@ -1,7 +1,7 @@
-use std::{cell::RefCell, rc::Rc};
+use std::fmt::Display;
 
 use crate::{
-    eval::{Eval, EvalError, ExecutionEnvironment, Value},
+    eval::{Compile, Compiler, OpCode},
     lexer::{Token, TokenKind},
 };
 
@@ -11,11 +11,11 @@ use super::{Expression, Node};
 #[derive(Debug, PartialEq)]
 pub struct Array {
     pub token: Token,
-    pub elements: Vec<Box<Expression>>,
+    pub elements: Vec<Expression>,
 }
 
 impl Array {
-    pub fn new(token: Token, elements: Vec<Box<Expression>>) -> Self {
+    pub fn new(token: Token, elements: Vec<Expression>) -> Self {
         assert_eq!(
             token.kind,
             TokenKind::LeftBracket,
@@ -25,19 +25,18 @@ impl Array {
     }
 }
 
-impl ToString for Array {
-    fn to_string(&self) -> String {
-        let mut out = self.token.literal();
-        out.push_str(
-            &self
-                .elements
+impl Display for Array {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}]",
+            self.token.literal(),
+            self.elements
                 .iter()
                 .map(|el| el.to_string())
                 .collect::<Vec<String>>()
-                .join(", "),
-        );
-        out.push(']');
-        out
+                .join(", ")
+        )
     }
 }
 
@@ -51,19 +50,21 @@ impl Node for Array {
     }
 }
 
-impl Eval for Array {
-    fn eval(&self, env: Rc<RefCell<ExecutionEnvironment>>) -> Result<Rc<Value>, EvalError> {
-        let mut els = vec![];
+impl Compile for Array {
+    fn compile(&self, compiler: &mut Compiler) {
         for el in &self.elements {
-            els.push(el.eval(Rc::clone(&env))?);
+            el.compile(compiler);
         }
-        Ok(Value::new_array(els))
+        compiler.emit(OpCode::Array(self.elements.len()), self.token.span());
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{eval::TRUE, lexer::Position};
+    use crate::{
+        eval::{eval_expression, ExecutionEnvironment, Value, TRUE},
+        lexer::Position,
+    };
 
     use super::*;
 
@@ -72,10 +73,10 @@ mod tests {
         let token = Token::new(TokenKind::LeftBracket, Position(0, 0));
         let arr = Array::new(
             token.clone(),
-            vec![Box::new(Expression::new_boolean(Token::new(
+            vec![Expression::new_boolean(Token::new(
                 TokenKind::True,
                 Position(0, 0),
-            )))],
+            ))],
         );
 
         assert!(arr.as_any().is::<Array>());
@@ -86,20 +87,34 @@ mod tests {
     }
 
     #[test]
-    fn test_array_eval() {
+    fn test_array_compile() {
         let token = Token::new(TokenKind::LeftBracket, Position(0, 0));
         let arr = Array::new(
             token.clone(),
-            vec![Box::new(Expression::new_boolean(Token::new(
+            vec![Expression::new_boolean(Token::new(
                 TokenKind::True,
                 Position(0, 0),
-            )))],
+            ))],
         );
 
-        let env = ExecutionEnvironment::new_global();
-        let result = arr.eval(env);
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        arr.compile(&mut compiler);
+
+        assert_eq!(compiler.chunk().code, vec![OpCode::True, OpCode::Array(1)]);
+    }
+
+    #[test]
+    fn test_array_eval() {
+        let token = Token::new(TokenKind::LeftBracket, Position(0, 0));
+        let arr = Array::new(
+            token,
+            vec![Expression::new_boolean(Token::new(
+                TokenKind::True,
+                Position(0, 0),
+            ))],
+        );
 
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), Value::new_array(vec![TRUE.rc()]))
+        let result = eval_expression(&arr, ExecutionEnvironment::new_global());
+        assert_eq!(result.unwrap(), Value::new_array(vec![TRUE.rc()]));
     }
 }
@@ -1,7 +1,7 @@
-use std::{cell::RefCell, rc::Rc};
+use std::fmt::Display;
 
 use crate::{
-    eval::{Eval, EvalError, ExecutionEnvironment, Value, FALSE, TRUE},
+    eval::{Compile, Compiler, OpCode},
     lexer::{Token, TokenKind},
 };
 
@@ -23,9 +23,9 @@ impl Boolean {
     }
 }
 
-impl ToString for Boolean {
-    fn to_string(&self) -> String {
-        self.token.literal()
+impl Display for Boolean {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.token.literal())
     }
 }
 
@@ -39,19 +39,22 @@ impl Node for Boolean {
     }
 }
 
-impl Eval for Boolean {
-    fn eval(&self, _env: Rc<RefCell<ExecutionEnvironment>>) -> Result<Rc<Value>, EvalError> {
+impl Compile for Boolean {
+    fn compile(&self, compiler: &mut Compiler) {
         match self.token.kind {
-            TokenKind::True => Ok(TRUE.rc()),
-            TokenKind::False => Ok(FALSE.rc()),
+            TokenKind::True => compiler.emit(OpCode::True, self.token.span()),
+            TokenKind::False => compiler.emit(OpCode::False, self.token.span()),
             _ => unreachable!(),
-        }
+        };
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::lexer::Position;
+    use crate::{
+        eval::{eval_expression, ExecutionEnvironment, TRUE},
+        lexer::Position,
+    };
 
     use super::*;
 
@@ -67,14 +70,21 @@ mod tests {
     }
 
     #[test]
-    fn test_bool_eval() {
+    fn test_bool_compile() {
         let token = Token::new(TokenKind::True, Position(0, 0));
         let boolean = Boolean::new(token);
 
-        let env = ExecutionEnvironment::new_global();
-        let result = boolean.eval(env);
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        boolean.compile(&mut compiler);
+
+        assert_eq!(compiler.chunk().code, vec![OpCode::True]);
+    }
+
+    #[test]
+    fn test_bool_eval() {
+        let boolean = Boolean::new(Token::new(TokenKind::True, Position(0, 0)));
 
-        assert!(result.is_ok());
+        let result = eval_expression(&boolean, ExecutionEnvironment::new_global());
         assert_eq!(result.unwrap(), TRUE.rc());
     }
 }
@@ -1,7 +1,7 @@
-use std::{cell::RefCell, rc::Rc};
+use std::fmt::Display;
 
 use crate::{
-    eval::{Environment, Eval, EvalError, ExecutionEnvironment, Value},
+    eval::{Compile, Compiler, OpCode},
     lexer::{Token, TokenKind},
 };
 
@@ -11,11 +11,11 @@ use super::{Expression, Node};
 pub struct FunctionCall {
     pub token: Token,
     pub function: Box<Expression>,
-    pub arguments: Vec<Box<Expression>>,
+    pub arguments: Vec<Expression>,
 }
 
 impl FunctionCall {
-    pub fn new(token: Token, function: Box<Expression>, arguments: Vec<Box<Expression>>) -> Self {
+    pub fn new(token: Token, function: Box<Expression>, arguments: Vec<Expression>) -> Self {
         assert_eq!(
             token.kind,
             TokenKind::LeftParen,
@@ -29,20 +29,18 @@ impl FunctionCall {
     }
 }
 
-impl ToString for FunctionCall {
-    fn to_string(&self) -> String {
-        let mut out = String::from(self.function.to_string());
-        out.push('(');
-        out.push_str(
-            &self
-                .arguments
+impl Display for FunctionCall {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}({})",
+            self.function,
+            self.arguments
                 .iter()
                 .map(|arg| arg.to_string())
                 .collect::<Vec<String>>()
-                .join(", "),
-        );
-        out.push(')');
-        out
+                .join(", ")
+        )
     }
 }
 
@@ -56,46 +54,32 @@ impl Node for FunctionCall {
     }
 }
 
-impl Eval for FunctionCall {
-    fn eval(&self, env: Rc<RefCell<ExecutionEnvironment>>) -> Result<Rc<Value>, EvalError> {
-        let func = self.function.eval(Rc::clone(&env))?;
+impl FunctionCall {
+    /// Compiles a call whose result is returned right away.
+    pub fn compile_tail(&self, compiler: &mut Compiler) {
+        self.compile_operands(compiler);
+        compiler.emit(OpCode::TailCall(self.arguments.len()), self.token.span());
+    }
 
-        let mut args = vec![];
+    fn compile_operands(&self, compiler: &mut Compiler) {
+        self.function.compile(compiler);
         for arg in &self.arguments {
-            let arg_value = arg.eval(Rc::clone(&env))?;
-            if arg_value.is_void() {
-                return Err(EvalError::VoidValueAsArgument(self.token.position));
-            }
-            args.push(arg_value);
-        }
-
-        let callee = match &*func {
-            Value::Function(f) => f,
-            Value::Builtin(builtin) => return builtin(args, self.token.position),
-            _ => return Err(EvalError::NotAFunction(self.token.position)),
-        };
-
-        if args.len() != callee.params.len() {
-            return Err(EvalError::WrongNumberOfArguments(
-                callee.params.len(),
-                args.len(),
-                self.token.position,
-            ));
-        }
-
-        let local_env = ExecutionEnvironment::new_local(Rc::clone(&callee.captured_env));
-        for (param, arg) in callee.params.iter().zip(args) {
-            local_env.borrow_mut().set(param.clone(), arg);
+            arg.compile(compiler);
         }
+    }
+}
 
-        let result = callee.body.eval(local_env)?;
-        Ok(result.unwrap_returned())
+impl Compile for FunctionCall {
+    fn compile(&self, compiler: &mut Compiler) {
+        self.compile_operands(compiler);
+        compiler.emit(OpCode::Call(self.arguments.len()), self.token.span());
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
+        eval::{eval_expression, ExecutionEnvironment, Value},
         lexer::Position,
         parser::{Block, FunctionExpression, Statement},
     };
@@ -110,10 +94,10 @@ mod tests {
                 TokenKind::Identifier("my_func".to_string()),
                 Position(0, 0),
             ))),
-            vec![Box::new(Expression::new_integer(Token::new(
+            vec![Expression::new_integer(Token::new(
                 TokenKind::Integer(42),
                 Position(0, 0),
-            )))],
+            ))],
         );
 
         assert!(call.as_any().is::<FunctionCall>());
@@ -125,16 +109,16 @@ mod tests {
     }
 
     #[test]
-    fn test_call_eval() {
+    fn test_call_compile() {
         let block = Block::new(
             Token::new(TokenKind::LeftCurly, Position(0, 0)),
-            vec![Box::new(Statement::new_return(
+            vec![Statement::new_return(
                 Token::new(TokenKind::Return, Position(0, 0)),
                 Box::new(Expression::new_integer(Token::new(
                     TokenKind::Integer(42),
                     Position(0, 0),
                 ))),
-            ))],
+            )],
         );
         let params = vec![];
         let function = FunctionExpression::new(
@@ -149,9 +133,48 @@ mod tests {
             vec![],
         );
 
-        let env = ExecutionEnvironment::new_global();
-        let result = call.eval(env);
-        assert!(result.is_ok());
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        call.compile(&mut compiler);
+
+        assert_eq!(
+            compiler.chunk().code,
+            vec![OpCode::Closure(0), OpCode::Call(0)]
+        );
+        assert_eq!(
+            compiler.chunk().functions[0].chunk.code,
+            vec![
+                OpCode::Constant(0),
+                OpCode::Return,
+                OpCode::Void,
+                OpCode::Return
+            ]
+        );
+    }
+
+    #[test]
+    fn test_call_eval() {
+        let block = Block::new(
+            Token::new(TokenKind::LeftCurly, Position(0, 0)),
+            vec![Statement::new_return(
+                Token::new(TokenKind::Return, Position(0, 0)),
+                Box::new(Expression::new_integer(Token::new(
+                    TokenKind::Integer(42),
+                    Position(0, 0),
+                ))),
+            )],
+        );
+        let function = FunctionExpression::new(
+            Token::new(TokenKind::Function, Position(0, 0)),
+            vec![],
+            block,
+        );
+        let call = FunctionCall::new(
+            Token::new(TokenKind::LeftParen, Position(0, 0)),
+            Box::new(Expression::Function(function)),
+            vec![],
+        );
+
+        let result = eval_expression(&call, ExecutionEnvironment::new_global());
         assert_eq!(result.unwrap(), Value::new_integer(42));
     }
 }
@@ -0,0 +1,206 @@
+use std::fmt::Display;
+
+use crate::{
+    eval::{Compile, Compiler, OpCode},
+    lexer::{Token, TokenKind},
+};
+
+use super::{Block, Expression, Node};
+
+/// Branch taken when the condition of an `if` is false.
+#[derive(Debug, PartialEq)]
+pub enum Else {
+    /// `else { ... }`
+    Block(Block),
+    /// `else if ... { ... }`, chained without nesting blocks.
+    If(Box<Conditional>),
+}
+
+impl Display for Else {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Else::Block(block) => write!(f, "{block}"),
+            Else::If(nested) => write!(f, "{nested}"),
+        }
+    }
+}
+
+/// Conditional expression ast node, `if x > 0 { x } else { -x }`.
+///
+/// Its value is the value of the last expression of the taken branch,
+/// or void when the branch does not end with an expression or there is no `else`.
+/// In statement position it's an expression statement and the value is discarded.
+#[derive(Debug, PartialEq)]
+pub struct Conditional {
+    pub token: Token,
+    pub condition: Box<Expression>,
+    pub consequence: Block,
+    pub alternative: Option<Else>,
+}
+
+impl Conditional {
+    pub fn new(
+        token: Token,
+        condition: Box<Expression>,
+        consequence: Block,
+        alternative: Option<Else>,
+    ) -> Self {
+        assert_eq!(token.kind, TokenKind::If, "expected if token");
+
+        Conditional {
+            token,
+            condition,
+            consequence,
+            alternative,
+        }
+    }
+}
+
+impl Display for Conditional {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}) {}",
+            self.token.literal(),
+            self.condition,
+            self.consequence
+        )?;
+        if let Some(alternative) = &self.alternative {
+            write!(f, " else {alternative}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Node for Conditional {
+    fn token_literal(&self) -> String {
+        self.token.literal()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Compile for Conditional {
+    fn compile(&self, compiler: &mut Compiler) {
+        self.condition.compile(compiler);
+        let to_else = compiler.emit(OpCode::JumpIfFalse(0), self.token.span());
+
+        compiler.compile_block_value(&self.consequence);
+        let to_end = compiler.emit(OpCode::Jump(0), self.token.span());
+        compiler.patch_jump(to_else);
+
+        match &self.alternative {
+            Some(Else::Block(block)) => compiler.compile_block_value(block),
+            Some(Else::If(nested)) => nested.compile(compiler),
+            None => {
+                compiler.emit(OpCode::Void, self.token.span());
+            }
+        }
+        compiler.patch_jump(to_end);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        eval::{eval_expression, ExecutionEnvironment, Value},
+        lexer::Position,
+        parser::Statement,
+    };
+
+    fn block(statements: Vec<Statement>) -> Block {
+        Block::new(Token::new(TokenKind::LeftCurly, Position(0, 0)), statements)
+    }
+
+    fn integer_statement(value: i64) -> Box<Statement> {
+        let token = Token::new(TokenKind::Integer(value), Position(0, 0));
+        Box::new(Statement::new_expression(
+            token.clone(),
+            Box::new(Expression::new_integer(token)),
+        ))
+    }
+
+    fn boolean(kind: TokenKind) -> Box<Expression> {
+        Box::new(Expression::new_boolean(Token::new(kind, Position(0, 0))))
+    }
+
+    #[test]
+    fn test_conditional_node() {
+        let token = Token::new(TokenKind::If, Position(0, 0));
+        let nested = Conditional::new(
+            token.clone(),
+            boolean(TokenKind::False),
+            block(vec![*integer_statement(2)]),
+            None,
+        );
+        let conditional = Conditional::new(
+            token.clone(),
+            boolean(TokenKind::True),
+            block(vec![*integer_statement(1)]),
+            Some(Else::If(Box::new(nested))),
+        );
+
+        assert!(conditional.as_any().is::<Conditional>());
+        assert_eq!(conditional.token_literal(), token.literal());
+        assert_eq!(
+            conditional.to_string(),
+            "if (true) {\n  1\n} else if (false) {\n  2\n}"
+        );
+    }
+
+    #[test]
+    fn test_conditional_compile() {
+        let conditional = Conditional::new(
+            Token::new(TokenKind::If, Position(0, 0)),
+            boolean(TokenKind::True),
+            block(vec![*integer_statement(1)]),
+            None,
+        );
+
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        conditional.compile(&mut compiler);
+
+        assert_eq!(
+            compiler.chunk().code,
+            vec![
+                OpCode::True,
+                OpCode::JumpIfFalse(4),
+                OpCode::Constant(0),
+                OpCode::Jump(5),
+                OpCode::Void,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_conditional_eval() {
+        let token = Token::new(TokenKind::If, Position(0, 0));
+        let nested = Conditional::new(
+            token.clone(),
+            boolean(TokenKind::False),
+            block(vec![*integer_statement(2)]),
+            Some(Else::Block(block(vec![*integer_statement(3)]))),
+        );
+        let conditional = Conditional::new(
+            token.clone(),
+            boolean(TokenKind::False),
+            block(vec![*integer_statement(1)]),
+            Some(Else::If(Box::new(nested))),
+        );
+
+        let result = eval_expression(&conditional, ExecutionEnvironment::new_global());
+        assert_eq!(result.unwrap(), Value::new_integer(3));
+
+        let conditional = Conditional::new(
+            token,
+            boolean(TokenKind::False),
+            block(vec![*integer_statement(1)]),
+            None,
+        );
+        let result = eval_expression(&conditional, ExecutionEnvironment::new_global());
+        assert!(result.unwrap().is_void());
+    }
+}
@@ -1,7 +1,7 @@
-use std::{cell::RefCell, rc::Rc};
+use std::fmt::Display;
 
 use crate::{
-    eval::{Eval, EvalError, ExecutionEnvironment, Value},
+    eval::{Compile, Compiler, Value},
     lexer::{Token, TokenKind},
 };
 
@@ -26,9 +26,9 @@ impl Float {
     }
 }
 
-impl ToString for Float {
-    fn to_string(&self) -> String {
-        self.token.literal()
+impl Display for Float {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.token.literal())
     }
 }
 
@@ -42,10 +42,12 @@ impl Node for Float {
     }
 }
 
-impl Eval for Float {
-    fn eval(&self, _env: Rc<RefCell<ExecutionEnvironment>>) -> Result<Rc<Value>, EvalError> {
+impl Compile for Float {
+    fn compile(&self, compiler: &mut Compiler) {
         match self.token.kind {
-            TokenKind::Float(value) => Ok(Value::new_float(value)),
+            TokenKind::Float(value) => {
+                compiler.emit_constant(Value::new_float(value), self.token.span())
+            }
             _ => unreachable!(),
         }
     }
@@ -54,7 +56,7 @@ impl Eval for Float {
 #[cfg(test)]
 mod tests {
     use crate::{
-        eval::{ExecutionEnvironment, Value},
+        eval::{eval_expression, ExecutionEnvironment, OpCode, Value},
         lexer::Position,
     };
 
@@ -72,14 +74,22 @@ mod tests {
     }
 
     #[test]
-    fn test_float_eval() {
+    fn test_float_compile() {
         let token = Token::new(TokenKind::Float(3.22), Position(0, 0));
         let float = Float::new(token);
 
-        let env = ExecutionEnvironment::new_global();
-        let result = float.eval(env);
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        float.compile(&mut compiler);
+
+        assert_eq!(compiler.chunk().code, vec![OpCode::Constant(0)]);
+        assert_eq!(compiler.chunk().constants, vec![Value::new_float(3.22)]);
+    }
+
+    #[test]
+    fn test_float_eval() {
+        let float = Float::new(Token::new(TokenKind::Float(3.22), Position(0, 0)));
 
-        assert!(result.is_ok());
+        let result = eval_expression(&float, ExecutionEnvironment::new_global());
         assert_eq!(result.unwrap(), Value::new_float(3.22));
     }
 }
@@ -1,8 +1,8 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{fmt::Display, rc::Rc};
 
 use super::{Block, Identifier, Node};
 use crate::{
-    eval::{Eval, EvalError, ExecutionEnvironment, Value},
+    eval::{Compile, Compiler, OpCode},
     lexer::{Token, TokenKind},
 };
 
@@ -24,24 +24,19 @@ impl FunctionExpression {
     }
 }
 
-impl ToString for FunctionExpression {
-    fn to_string(&self) -> String {
-        let mut out = String::from(self.token_literal());
-        out.push(' ');
-
-        out.push('(');
-        out.push_str(
-            &self
-                .parameters
+impl Display for FunctionExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}) {}",
+            self.token_literal(),
+            self.parameters
                 .iter()
                 .map(|ident| ident.to_string())
                 .collect::<Vec<String>>()
                 .join(", "),
-        );
-        out.push_str(") ");
-        out.push_str(&self.body.to_string());
-
-        out
+            self.body
+        )
     }
 }
 
@@ -55,20 +50,17 @@ impl Node for FunctionExpression {
     }
 }
 
-impl Eval for FunctionExpression {
-    fn eval(&self, env: Rc<RefCell<ExecutionEnvironment>>) -> Result<Rc<Value>, EvalError> {
-        let params = self
-            .parameters
-            .iter()
-            .map(|ident| ident.to_string())
-            .collect::<Vec<String>>();
-        Ok(Value::new_function(params, Rc::clone(&self.body), env))
+impl Compile for FunctionExpression {
+    fn compile(&self, compiler: &mut Compiler) {
+        let idx = compiler.compile_function(None, &self.parameters, &self.body, self.token.span());
+        compiler.emit(OpCode::Closure(idx), self.token.span());
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
+        eval::{eval_expression, ExecutionEnvironment, Value},
         lexer::Position,
         parser::{Expression, Statement},
     };
@@ -80,7 +72,7 @@ mod tests {
         let block = Block::new(
             Token::new(TokenKind::LeftCurly, Position(0, 0)),
             vec![
-                Box::new(Statement::new_let(
+                Statement::new_let(
                     Token::new(TokenKind::Let, Position(0, 0)),
                     Identifier::new(Token::new(
                         TokenKind::Identifier("x".to_string()),
@@ -90,14 +82,15 @@ mod tests {
                         TokenKind::Integer(5),
                         Position(0, 0),
                     ))),
-                )),
-                Box::new(Statement::new_return(
+                    None,
+                ),
+                Statement::new_return(
                     Token::new(TokenKind::Return, Position(0, 0)),
                     Box::new(Expression::new_identifier(Token::new(
                         TokenKind::Identifier("x".to_string()),
                         Position(0, 0),
                     ))),
-                )),
+                ),
             ],
         );
         let params = vec![
@@ -129,20 +122,39 @@ mod tests {
     }
 
     #[test]
-    fn test_fn_expr_eval() {
+    fn test_fn_expr_compile() {
         let block = Block::new(Token::new(TokenKind::LeftCurly, Position(0, 0)), vec![]);
-        let params = vec![];
+        let params = vec![Identifier::new(Token::new(
+            TokenKind::Identifier("a".to_string()),
+            Position(0, 0),
+        ))];
         let function = FunctionExpression::new(
             Token::new(TokenKind::Function, Position(0, 0)),
             params,
             block,
         );
 
-        let env = ExecutionEnvironment::new_global();
-        let result = function.eval(env);
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        function.compile(&mut compiler);
+
+        assert_eq!(compiler.chunk().code, vec![OpCode::Closure(0)]);
+
+        let proto = &compiler.chunk().functions[0];
+        assert_eq!(proto.params, vec!["a".to_string()]);
+        assert_eq!(proto.slots, 1);
+        assert_eq!(proto.chunk.code, vec![OpCode::Void, OpCode::Return]);
+    }
+
+    #[test]
+    fn test_fn_expr_eval() {
+        let block = Block::new(Token::new(TokenKind::LeftCurly, Position(0, 0)), vec![]);
+        let function = FunctionExpression::new(
+            Token::new(TokenKind::Function, Position(0, 0)),
+            vec![],
+            block,
+        );
 
-        assert!(result.is_ok());
-        let matched = matches!(&*result.unwrap(), Value::Function(_));
-        assert_eq!(matched, true);
+        let result = eval_expression(&function, ExecutionEnvironment::new_global());
+        assert!(matches!(&*result.unwrap(), Value::Function(_)));
     }
 }
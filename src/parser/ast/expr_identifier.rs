@@ -1,7 +1,7 @@
-use std::{cell::RefCell, rc::Rc};
+use std::fmt::Display;
 
 use crate::{
-    eval::{Environment, Eval, EvalError, ExecutionEnvironment, Value},
+    eval::{Compile, Compiler},
     lexer::{Token, TokenKind},
 };
 
@@ -24,9 +24,9 @@ impl Identifier {
     }
 }
 
-impl ToString for Identifier {
-    fn to_string(&self) -> String {
-        self.token.literal()
+impl Display for Identifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.token.literal())
     }
 }
 
@@ -40,19 +40,18 @@ impl Node for Identifier {
     }
 }
 
-impl Eval for Identifier {
-    fn eval(&self, env: Rc<RefCell<ExecutionEnvironment>>) -> Result<Rc<Value>, EvalError> {
-        let name = self.token.literal();
-        match env.borrow().get(&name) {
-            Some(value) => Ok(value),
-            None => Err(EvalError::NotDefined(name, self.token.position)),
-        }
+impl Compile for Identifier {
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.emit_get(&self.token.literal(), self.token.span());
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{eval::TRUE, lexer::Position};
+    use crate::{
+        eval::{eval_expression, Binding, Environment, ExecutionEnvironment, OpCode, TRUE},
+        lexer::Position,
+    };
 
     use super::*;
 
@@ -66,6 +65,25 @@ mod tests {
         assert_eq!(ident.token_literal(), token.literal());
     }
 
+    #[test]
+    fn test_compile_identifier() {
+        let token = Token::new(TokenKind::Identifier("cool".to_string()), Position(0, 0));
+        let ident = Identifier::new(token.clone());
+
+        let env = ExecutionEnvironment::new_global();
+        let slot = env.borrow_mut().intern(&token.literal());
+
+        let mut compiler = Compiler::new(env);
+        ident.compile(&mut compiler);
+
+        assert_eq!(compiler.chunk().code, vec![OpCode::Get(0)]);
+        assert_eq!(compiler.chunk().lookups[0].name, "cool");
+        assert_eq!(
+            compiler.chunk().lookups[0].bindings,
+            vec![Binding::Global(slot)]
+        );
+    }
+
     #[test]
     fn test_eval_identifier() {
         let token = Token::new(TokenKind::Identifier("cool".to_string()), Position(0, 0));
@@ -74,8 +92,7 @@ mod tests {
         let env = ExecutionEnvironment::new_global();
         env.borrow_mut().set(token.literal(), TRUE.rc());
 
-        let result = ident.eval(env);
-        assert!(result.is_ok());
+        let result = eval_expression(&ident, env);
         assert_eq!(result.unwrap(), TRUE.rc());
     }
 }
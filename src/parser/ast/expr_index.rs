@@ -1,7 +1,7 @@
-use std::{cell::RefCell, rc::Rc};
+use std::fmt::Display;
 
 use crate::{
-    eval::{Eval, EvalError, ExecutionEnvironment, Value},
+    eval::{Compile, Compiler, OpCode},
     lexer::{Token, TokenKind},
 };
 
@@ -26,13 +26,9 @@ impl Index {
     }
 }
 
-impl ToString for Index {
-    fn to_string(&self) -> String {
-        let mut out = self.of.to_string();
-        out.push('[');
-        out.push_str(&self.index.to_string());
-        out.push(']');
-        out
+impl Display for Index {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}[{}]", self.of, self.index)
     }
 }
 
@@ -46,64 +42,20 @@ impl Node for Index {
     }
 }
 
-impl Eval for Index {
-    fn eval(&self, env: Rc<RefCell<ExecutionEnvironment>>) -> Result<Rc<Value>, EvalError> {
-        let of = self.of.eval(Rc::clone(&env))?;
-        let index = self.index.eval(env)?;
-
-        match &*of {
-            Value::Array(arr) => {
-                if let Value::Integer(int) = *index {
-                    if int as usize >= arr.len() {
-                        Err(EvalError::IndexOutOfBounds(
-                            int as usize,
-                            arr.len(),
-                            self.token.position,
-                        ))
-                    } else {
-                        Ok(arr[int as usize].clone())
-                    }
-                } else {
-                    Err(EvalError::InvalidIndexExpression(
-                        of.to_string(),
-                        index.to_string(),
-                        self.token.position,
-                    ))
-                }
-            }
-            Value::String(s) => {
-                if let Value::Integer(int) = *index {
-                    if int as usize >= s.len() {
-                        Err(EvalError::IndexOutOfBounds(
-                            int as usize,
-                            s.len(),
-                            self.token.position,
-                        ))
-                    } else {
-                        Ok(Value::new_string(
-                            s.chars().nth(int as usize).unwrap().to_string(),
-                        ))
-                    }
-                } else {
-                    Err(EvalError::InvalidIndexExpression(
-                        of.to_string(),
-                        index.to_string(),
-                        self.token.position,
-                    ))
-                }
-            }
-            _ => Err(EvalError::InvalidIndexExpression(
-                of.to_string(),
-                index.to_string(),
-                self.token.position,
-            )),
-        }
+impl Compile for Index {
+    fn compile(&self, compiler: &mut Compiler) {
+        self.of.compile(compiler);
+        self.index.compile(compiler);
+        compiler.emit(OpCode::Index, self.token.span());
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{eval::TRUE, lexer::Position};
+    use crate::{
+        eval::{eval_expression, ExecutionEnvironment, TRUE},
+        lexer::Position,
+    };
 
     use super::*;
 
@@ -112,10 +64,10 @@ mod tests {
         let token = Token::new(TokenKind::LeftBracket, Position(0, 0));
         let arr = Expression::new_array(
             token.clone(),
-            vec![Box::new(Expression::new_boolean(Token::new(
+            vec![Expression::new_boolean(Token::new(
                 TokenKind::True,
                 Position(0, 0),
-            )))],
+            ))],
         );
 
         let idx = Index::new(
@@ -134,14 +86,14 @@ mod tests {
     }
 
     #[test]
-    fn test_index_eval() {
+    fn test_index_compile() {
         let token = Token::new(TokenKind::LeftBracket, Position(0, 0));
         let arr = Expression::new_array(
             token.clone(),
-            vec![Box::new(Expression::new_boolean(Token::new(
+            vec![Expression::new_boolean(Token::new(
                 TokenKind::True,
                 Position(0, 0),
-            )))],
+            ))],
         );
 
         let idx = Index::new(
@@ -153,10 +105,40 @@ mod tests {
             ))),
         );
 
-        let env = ExecutionEnvironment::new_global();
-        let result = idx.eval(env);
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        idx.compile(&mut compiler);
+
+        assert_eq!(
+            compiler.chunk().code,
+            vec![
+                OpCode::True,
+                OpCode::Array(1),
+                OpCode::Constant(0),
+                OpCode::Index
+            ]
+        );
+    }
+
+    #[test]
+    fn test_index_eval() {
+        let token = Token::new(TokenKind::LeftBracket, Position(0, 0));
+        let arr = Expression::new_array(
+            token.clone(),
+            vec![Expression::new_boolean(Token::new(
+                TokenKind::True,
+                Position(0, 0),
+            ))],
+        );
+        let idx = Index::new(
+            token,
+            Box::new(arr),
+            Box::new(Expression::new_integer(Token::new(
+                TokenKind::Integer(0),
+                Position(0, 0),
+            ))),
+        );
 
-        assert!(result.is_ok());
+        let result = eval_expression(&idx, ExecutionEnvironment::new_global());
         assert_eq!(result.unwrap(), TRUE.rc());
     }
 }
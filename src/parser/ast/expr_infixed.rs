@@ -1,7 +1,7 @@
-use std::{cell::RefCell, rc::Rc};
+use std::fmt::Display;
 
 use crate::{
-    eval::{Eval, EvalError, ExecutionEnvironment, Value},
+    eval::{Compile, Compiler, InfixOp, OpCode},
     lexer::{Token, TokenKind},
 };
 
@@ -22,14 +22,9 @@ impl Infixed {
     }
 }
 
-impl ToString for Infixed {
-    fn to_string(&self) -> String {
-        let mut out = String::from("(");
-        out.push_str(&self.left.to_string());
-        out.push_str(&self.op.literal());
-        out.push_str(&self.right.to_string());
-        out.push_str(")");
-        out
+impl Display for Infixed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}{}{})", self.left, self.op.literal(), self.right)
     }
 }
 
@@ -43,129 +38,65 @@ impl Node for Infixed {
     }
 }
 
-impl Eval for Infixed {
-    fn eval(&self, env: Rc<RefCell<ExecutionEnvironment>>) -> Result<Rc<Value>, EvalError> {
-        let left_value = self.left.eval(Rc::clone(&env))?;
-        let right_value = self.right.eval(env)?;
-
-        match (&*left_value, &*right_value) {
-            (Value::String(left), Value::String(right)) => eval_string_infix(left, &self.op, right),
-            (Value::Integer(left), Value::Integer(right)) => {
-                eval_int_infix(*left, &self.op, *right)
-            }
-            (Value::Boolean(left), Value::Boolean(right)) => {
-                eval_bool_infix(*left, &self.op, *right)
-            }
-            (Value::Float(left), Value::Float(right)) => eval_float_infix(*left, &self.op, *right),
-            (Value::Integer(left), Value::Float(right)) => {
-                eval_float_infix(*left as f64, &self.op, *right)
-            }
-            (Value::Float(left), Value::Integer(right)) => {
-                eval_float_infix(*left, &self.op, *right as f64)
-            }
-            _ => Err(EvalError::InvalidInfixOperation(
-                left_value.to_string(),
-                self.op.literal(),
-                right_value.to_string(),
-                self.op.position,
-            )),
+/// `&&` and `||` only evaluate the right operand if the left one doesn't decide the result.
+/// Both operands must be booleans, just like conditions.
+impl Compile for Infixed {
+    fn compile(&self, compiler: &mut Compiler) {
+        match self.op.kind {
+            TokenKind::AndAnd => return self.compile_and(compiler),
+            TokenKind::OrOr => return self.compile_or(compiler),
+            _ => {}
         }
-    }
-}
 
-fn eval_string_infix(left: &str, op: &Token, right: &str) -> Result<Rc<Value>, EvalError> {
-    match op.kind {
-        TokenKind::Plus => Ok(Value::new_string(format!("{left}{right}"))),
-        TokenKind::EqualsEquals => Ok(Value::from_native_bool(left == right)),
-        TokenKind::BangEquals => Ok(Value::from_native_bool(left != right)),
-        _ => Err(EvalError::InvalidInfixOperation(
-            left.to_string(),
-            op.literal(),
-            right.to_string(),
-            op.position,
-        )),
-    }
-}
+        self.left.compile(compiler);
+        self.right.compile(compiler);
 
-fn eval_float_infix(left: f64, op: &Token, right: f64) -> Result<Rc<Value>, EvalError> {
-    if op.kind == TokenKind::Slash && right == 0.0 {
-        return Err(EvalError::DivisionByZero(
-            left.to_string(),
-            right.to_string(),
-            op.position,
-        ));
-    };
-
-    match op.kind {
-        TokenKind::Plus => Ok(Value::new_float(left + right)),
-        TokenKind::Minus => Ok(Value::new_float(left - right)),
-        TokenKind::Asterisk => Ok(Value::new_float(left * right)),
-        TokenKind::Slash => Ok(Value::new_float(left / right)),
-
-        TokenKind::EqualsEquals => Ok(Value::from_native_bool(left == right)),
-        TokenKind::BangEquals => Ok(Value::from_native_bool(left != right)),
-        TokenKind::LessThan => Ok(Value::from_native_bool(left < right)),
-        TokenKind::GreaterThan => Ok(Value::from_native_bool(left > right)),
-        TokenKind::LessThanEquals => Ok(Value::from_native_bool(left <= right)),
-        TokenKind::GreaterThanEquals => Ok(Value::from_native_bool(left >= right)),
-
-        _ => Err(EvalError::InvalidInfixOperation(
-            left.to_string(),
-            op.literal(),
-            right.to_string(),
-            op.position,
-        )),
+        let op = InfixOp::from_token_kind(&self.op.kind).expect("expected infix operator token");
+        compiler.emit(OpCode::Infix(op), self.op.span());
     }
 }
 
-fn eval_int_infix(left: i64, op: &Token, right: i64) -> Result<Rc<Value>, EvalError> {
-    if op.kind == TokenKind::Slash && right == 0 {
-        return Err(EvalError::DivisionByZero(
-            left.to_string(),
-            right.to_string(),
-            op.position,
-        ));
-    };
-
-    match op.kind {
-        TokenKind::Plus => Ok(Value::new_integer(left + right)),
-        TokenKind::Minus => Ok(Value::new_integer(left - right)),
-        TokenKind::Asterisk => Ok(Value::new_integer(left * right)),
-        TokenKind::Slash => Ok(Value::new_integer(left / right)),
-
-        TokenKind::EqualsEquals => Ok(Value::from_native_bool(left == right)),
-        TokenKind::BangEquals => Ok(Value::from_native_bool(left != right)),
-        TokenKind::LessThan => Ok(Value::from_native_bool(left < right)),
-        TokenKind::GreaterThan => Ok(Value::from_native_bool(left > right)),
-        TokenKind::LessThanEquals => Ok(Value::from_native_bool(left <= right)),
-        TokenKind::GreaterThanEquals => Ok(Value::from_native_bool(left >= right)),
-
-        _ => Err(EvalError::InvalidInfixOperation(
-            left.to_string(),
-            op.literal(),
-            right.to_string(),
-            op.position,
-        )),
+impl Infixed {
+    fn compile_and(&self, compiler: &mut Compiler) {
+        self.left.compile(compiler);
+        let left_false = compiler.emit(OpCode::JumpIfFalse(0), self.op.span());
+        self.right.compile(compiler);
+        let right_false = compiler.emit(OpCode::JumpIfFalse(0), self.op.span());
+
+        compiler.emit(OpCode::True, self.op.span());
+        let to_end = compiler.emit(OpCode::Jump(0), self.op.span());
+
+        compiler.patch_jump(left_false);
+        compiler.patch_jump(right_false);
+        compiler.emit(OpCode::False, self.op.span());
+        compiler.patch_jump(to_end);
     }
-}
 
-fn eval_bool_infix(left: bool, op: &Token, right: bool) -> Result<Rc<Value>, EvalError> {
-    match op.kind {
-        TokenKind::EqualsEquals => Ok(Value::from_native_bool(left == right)),
-        TokenKind::BangEquals => Ok(Value::from_native_bool(left != right)),
-        _ => Err(EvalError::InvalidInfixOperation(
-            left.to_string(),
-            op.literal(),
-            right.to_string(),
-            op.position,
-        )),
+    fn compile_or(&self, compiler: &mut Compiler) {
+        self.left.compile(compiler);
+        let to_right = compiler.emit(OpCode::JumpIfFalse(0), self.op.span());
+        compiler.emit(OpCode::True, self.op.span());
+        let left_true = compiler.emit(OpCode::Jump(0), self.op.span());
+
+        compiler.patch_jump(to_right);
+        self.right.compile(compiler);
+        let right_false = compiler.emit(OpCode::JumpIfFalse(0), self.op.span());
+        compiler.emit(OpCode::True, self.op.span());
+        let right_true = compiler.emit(OpCode::Jump(0), self.op.span());
+
+        compiler.patch_jump(right_false);
+        compiler.emit(OpCode::False, self.op.span());
+        compiler.patch_jump(left_true);
+        compiler.patch_jump(right_true);
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::rc::Rc;
+
     use crate::{
-        eval::FALSE,
+        eval::{eval_expression, ExecutionEnvironment, Value, FALSE},
         lexer::{Position, TokenKind},
     };
 
@@ -190,7 +121,7 @@ mod tests {
     }
 
     #[test]
-    fn test_infix_eval() {
+    fn test_infix_compile() {
         let token = Token::new(TokenKind::Minus, Position(0, 0));
         let left = Box::new(Expression::new_integer(Token::new(
             TokenKind::Integer(42),
@@ -202,10 +133,88 @@ mod tests {
         )));
         let infixed = Infixed::new(token, left, right);
 
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        infixed.compile(&mut compiler);
+
+        assert_eq!(
+            compiler.chunk().code,
+            vec![
+                OpCode::Constant(0),
+                OpCode::Constant(1),
+                OpCode::Infix(InfixOp::Subtract)
+            ]
+        );
+        assert_eq!(
+            compiler.chunk().constants,
+            vec![Value::new_integer(42), Value::new_integer(42)]
+        );
+
+        let token = Token::new(TokenKind::EqualsEquals, Position(0, 0));
+        let left = Box::new(Expression::new_boolean(Token::new(
+            TokenKind::True,
+            Position(0, 0),
+        )));
+        let right = Box::new(Expression::new_boolean(Token::new(
+            TokenKind::False,
+            Position(0, 0),
+        )));
+        let infixed = Infixed::new(token, left, right);
+
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        infixed.compile(&mut compiler);
+
+        assert_eq!(
+            compiler.chunk().code,
+            vec![OpCode::True, OpCode::False, OpCode::Infix(InfixOp::Equal)]
+        );
+    }
+
+    #[test]
+    fn test_logical_infix_compile() {
+        let token = Token::new(TokenKind::AndAnd, Position(0, 0));
+        let left = Box::new(Expression::new_boolean(Token::new(
+            TokenKind::True,
+            Position(0, 0),
+        )));
+        let right = Box::new(Expression::new_boolean(Token::new(
+            TokenKind::False,
+            Position(0, 0),
+        )));
+        let infixed = Infixed::new(token, left, right);
+
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        infixed.compile(&mut compiler);
+
+        assert_eq!(
+            compiler.chunk().code,
+            vec![
+                OpCode::True,
+                OpCode::JumpIfFalse(6),
+                OpCode::False,
+                OpCode::JumpIfFalse(6),
+                OpCode::True,
+                OpCode::Jump(7),
+                OpCode::False,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_infix_eval() {
         let env = ExecutionEnvironment::new_global();
-        let result = infixed.eval(Rc::clone(&env));
 
-        assert!(result.is_ok());
+        let token = Token::new(TokenKind::Minus, Position(0, 0));
+        let left = Box::new(Expression::new_integer(Token::new(
+            TokenKind::Integer(42),
+            Position(0, 0),
+        )));
+        let right = Box::new(Expression::new_integer(Token::new(
+            TokenKind::Integer(42),
+            Position(0, 0),
+        )));
+        let infixed = Infixed::new(token, left, right);
+
+        let result = eval_expression(&infixed, Rc::clone(&env));
         assert_eq!(result.unwrap(), Value::new_integer(0));
 
         let token = Token::new(TokenKind::Plus, Position(0, 0));
@@ -219,9 +228,7 @@ mod tests {
         )));
         let infixed = Infixed::new(token, left, right);
 
-        let result = infixed.eval(Rc::clone(&env));
-
-        assert!(result.is_ok());
+        let result = eval_expression(&infixed, Rc::clone(&env));
         assert_eq!(result.unwrap(), Value::new_float(84.2));
 
         let token = Token::new(TokenKind::EqualsEquals, Position(0, 0));
@@ -235,9 +242,7 @@ mod tests {
         )));
         let infixed = Infixed::new(token, left, right);
 
-        let result = infixed.eval(Rc::clone(&env));
-
-        assert!(result.is_ok());
+        let result = eval_expression(&infixed, env);
         assert_eq!(result.unwrap(), FALSE.rc());
     }
 }
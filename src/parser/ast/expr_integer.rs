@@ -1,7 +1,7 @@
-use std::{cell::RefCell, rc::Rc};
+use std::fmt::Display;
 
 use crate::{
-    eval::{Eval, EvalError, ExecutionEnvironment, Value},
+    eval::{Compile, Compiler, Value},
     lexer::{Token, TokenKind},
 };
 
@@ -16,7 +16,7 @@ pub struct Integer {
 impl Integer {
     pub fn new(token: Token) -> Self {
         assert!(
-            matches!(token.kind, TokenKind::Integer(_)),
+            matches!(token.kind, TokenKind::Integer(_) | TokenKind::BigInt(_)),
             "expected integer token"
         );
 
@@ -24,9 +24,9 @@ impl Integer {
     }
 }
 
-impl ToString for Integer {
-    fn to_string(&self) -> String {
-        self.token.literal()
+impl Display for Integer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.token.literal())
     }
 }
 
@@ -40,18 +40,23 @@ impl Node for Integer {
     }
 }
 
-impl Eval for Integer {
-    fn eval(&self, _env: Rc<RefCell<ExecutionEnvironment>>) -> Result<Rc<Value>, EvalError> {
-        match self.token.kind {
-            TokenKind::Integer(value) => Ok(Value::new_integer(value)),
+impl Compile for Integer {
+    fn compile(&self, compiler: &mut Compiler) {
+        let value = match &self.token.kind {
+            TokenKind::Integer(value) => Value::new_integer(*value),
+            TokenKind::BigInt(value) => Value::new_bigint(value.clone()),
             _ => unreachable!(),
-        }
+        };
+        compiler.emit_constant(value, self.token.span());
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::lexer::Position;
+    use crate::{
+        eval::{eval_expression, ExecutionEnvironment, OpCode},
+        lexer::Position,
+    };
 
     use super::*;
 
@@ -67,14 +72,22 @@ mod tests {
     }
 
     #[test]
-    fn test_integer_eval() {
+    fn test_integer_compile() {
         let token = Token::new(TokenKind::Integer(42), Position(0, 0));
         let integer = Integer::new(token);
 
-        let env = ExecutionEnvironment::new_global();
-        let result = integer.eval(env);
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        integer.compile(&mut compiler);
+
+        assert_eq!(compiler.chunk().code, vec![OpCode::Constant(0)]);
+        assert_eq!(compiler.chunk().constants, vec![Value::new_integer(42)]);
+    }
+
+    #[test]
+    fn test_integer_eval() {
+        let integer = Integer::new(Token::new(TokenKind::Integer(42), Position(0, 0)));
 
-        assert!(result.is_ok());
+        let result = eval_expression(&integer, ExecutionEnvironment::new_global());
         assert_eq!(result.unwrap(), Value::new_integer(42));
     }
 }
@@ -0,0 +1,106 @@
+use std::fmt::Display;
+
+use crate::{
+    eval::{Compile, Compiler, OpCode},
+    lexer::{Token, TokenKind},
+};
+
+use super::{Expression, Node};
+
+/// Map literal ast node.
+#[derive(Debug, PartialEq)]
+pub struct MapLiteral {
+    pub token: Token,
+    /// Key and value expressions in source order.
+    pub pairs: Vec<(Box<Expression>, Box<Expression>)>,
+}
+
+impl MapLiteral {
+    pub fn new(token: Token, pairs: Vec<(Box<Expression>, Box<Expression>)>) -> Self {
+        assert_eq!(
+            token.kind,
+            TokenKind::LeftCurly,
+            "expected left curly token"
+        );
+        Self { token, pairs }
+    }
+}
+
+impl Display for MapLiteral {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}}}",
+            self.token.literal(),
+            self.pairs
+                .iter()
+                .map(|(key, value)| format!("{key}: {value}"))
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    }
+}
+
+impl Node for MapLiteral {
+    fn token_literal(&self) -> String {
+        self.token.literal()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Compile for MapLiteral {
+    fn compile(&self, compiler: &mut Compiler) {
+        for (key, value) in &self.pairs {
+            key.compile(compiler);
+            value.compile(compiler);
+        }
+        compiler.emit(OpCode::Map(self.pairs.len()), self.token.span());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{eval::ExecutionEnvironment, lexer::Position};
+
+    use super::*;
+
+    fn map_literal() -> MapLiteral {
+        MapLiteral::new(
+            Token::new(TokenKind::LeftCurly, Position(0, 0)),
+            vec![(
+                Box::new(Expression::new_string(Token::new(
+                    TokenKind::String("a".to_string()),
+                    Position(0, 0),
+                ))),
+                Box::new(Expression::new_boolean(Token::new(
+                    TokenKind::True,
+                    Position(0, 0),
+                ))),
+            )],
+        )
+    }
+
+    #[test]
+    fn test_map_node() {
+        let map = map_literal();
+
+        assert!(map.as_any().is::<MapLiteral>());
+        assert_eq!(map.to_string(), "{a: true}");
+        assert_eq!(map.token_literal(), "{");
+        assert_eq!(map.pairs.len(), 1);
+    }
+
+    #[test]
+    fn test_map_compile() {
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        map_literal().compile(&mut compiler);
+
+        assert_eq!(
+            compiler.chunk().code,
+            vec![OpCode::Constant(0), OpCode::True, OpCode::Map(1)]
+        );
+    }
+}
@@ -0,0 +1,204 @@
+use std::fmt::Display;
+
+use crate::{
+    eval::{Compile, Compiler, OpCode},
+    lexer::{Token, TokenKind},
+};
+
+use super::{Block, Expression, Node, Pattern};
+
+/// Body of a `match` arm.
+#[derive(Debug, PartialEq)]
+pub enum ArmBody {
+    /// `=> { ... }`, the value is the last expression of the block.
+    Block(Block),
+    /// `=> expr`
+    Expression(Box<Expression>),
+}
+
+impl Display for ArmBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArmBody::Block(block) => write!(f, "{block}"),
+            ArmBody::Expression(expr) => write!(f, "{expr}"),
+        }
+    }
+}
+
+/// Single `pattern if guard => body` arm of a `match`.
+#[derive(Debug, PartialEq)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Box<Expression>>,
+    pub body: ArmBody,
+}
+
+impl MatchArm {
+    pub fn new(pattern: Pattern, guard: Option<Box<Expression>>, body: ArmBody) -> Self {
+        Self {
+            pattern,
+            guard,
+            body,
+        }
+    }
+}
+
+impl Display for MatchArm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.pattern)?;
+        if let Some(guard) = &self.guard {
+            write!(f, " if {guard}")?;
+        }
+        write!(f, " => {}", self.body)
+    }
+}
+
+/// Match expression ast node, `match value { 0 => "none", [x] => x, _ => "many" }`.
+///
+/// The arms are tried in order, its value is the value of the body of the first arm
+/// whose pattern matches and whose guard holds. No matching arm is a runtime error.
+/// In statement position it's an expression statement and the value is discarded.
+#[derive(Debug, PartialEq)]
+pub struct Match {
+    pub token: Token,
+    pub value: Box<Expression>,
+    pub arms: Vec<MatchArm>,
+}
+
+impl Match {
+    pub fn new(token: Token, value: Box<Expression>, arms: Vec<MatchArm>) -> Self {
+        assert_eq!(token.kind, TokenKind::Match, "expected match token");
+
+        Self { token, value, arms }
+    }
+}
+
+impl Display for Match {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} {} {{", self.token.literal(), self.value)?;
+        for arm in &self.arms {
+            writeln!(f, "  {arm},")?;
+        }
+        write!(f, "}}")
+    }
+}
+
+impl Node for Match {
+    fn token_literal(&self) -> String {
+        self.token.literal()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Compile for Match {
+    fn compile(&self, compiler: &mut Compiler) {
+        self.value.compile(compiler);
+
+        let mut to_end = vec![];
+        for arm in &self.arms {
+            let bindings = arm.pattern.bindings();
+            let pattern = arm.pattern.to_match_pattern(&bindings);
+            let jump = compiler.compile_match_arm(
+                bindings,
+                pattern,
+                arm.guard.as_deref(),
+                |compiler| match &arm.body {
+                    ArmBody::Block(block) => compiler.compile_block_value(block),
+                    ArmBody::Expression(expr) => expr.compile(compiler),
+                },
+                arm.pattern.span(),
+            );
+            to_end.push(jump);
+        }
+
+        compiler.emit(OpCode::NoMatch, self.token.span());
+        for jump in to_end {
+            compiler.patch_jump(jump);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        eval::{ExecutionEnvironment, MatchPattern, Value},
+        lexer::Position,
+        parser::{Identifier, PatternLiteral},
+    };
+
+    fn match_expression() -> Match {
+        let token = |kind| Token::new(kind, Position(1, 1));
+        let x = || token(TokenKind::Identifier("x".to_string()));
+
+        Match::new(
+            token(TokenKind::Match),
+            Box::new(Expression::new_identifier(x())),
+            vec![
+                MatchArm::new(
+                    Pattern::Literal(PatternLiteral::new(None, token(TokenKind::Integer(0)))),
+                    None,
+                    ArmBody::Expression(Box::new(Expression::new_boolean(token(TokenKind::False)))),
+                ),
+                MatchArm::new(
+                    Pattern::Binding(Identifier::new(x())),
+                    Some(Box::new(Expression::new_boolean(token(TokenKind::True)))),
+                    ArmBody::Expression(Box::new(Expression::new_identifier(x()))),
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_match_node() {
+        let expr = match_expression();
+
+        assert!(expr.as_any().is::<Match>());
+        assert_eq!(expr.token_literal(), "match");
+        assert_eq!(
+            expr.to_string(),
+            "match x {\n  0 => false,\n  x if true => x,\n}"
+        );
+    }
+
+    #[test]
+    fn test_match_compile() {
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        match_expression().compile(&mut compiler);
+
+        assert_eq!(
+            compiler.chunk().code,
+            vec![
+                OpCode::Get(0),
+                // 0 => false
+                OpCode::Match(0),
+                OpCode::JumpIfFalse(6),
+                OpCode::Pop,
+                OpCode::False,
+                OpCode::Jump(17),
+                // x if true => x
+                OpCode::PushScope(1),
+                OpCode::Match(1),
+                OpCode::JumpIfFalse(15),
+                OpCode::True,
+                OpCode::JumpIfFalse(15),
+                OpCode::Pop,
+                OpCode::Get(1),
+                OpCode::PopScope,
+                OpCode::Jump(17),
+                OpCode::PopScope,
+                OpCode::NoMatch,
+            ]
+        );
+        assert_eq!(
+            compiler.chunk().patterns,
+            vec![
+                MatchPattern::Constant(Value::new_integer(0)),
+                MatchPattern::Bind(0)
+            ]
+        );
+    }
+}
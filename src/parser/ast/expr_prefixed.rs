@@ -1,7 +1,7 @@
-use std::{cell::RefCell, rc::Rc};
+use std::fmt::Display;
 
 use crate::{
-    eval::{Eval, EvalError, ExecutionEnvironment, Value},
+    eval::{Compile, Compiler, OpCode, PrefixOp},
     lexer::{Token, TokenKind},
 };
 
@@ -26,13 +26,9 @@ impl Prefixed {
     }
 }
 
-impl ToString for Prefixed {
-    fn to_string(&self) -> String {
-        let mut out = String::from("(");
-        out.push_str(&self.op.literal());
-        out.push_str(&self.right.to_string());
-        out.push_str(")");
-        out
+impl Display for Prefixed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}{})", self.op.literal(), self.right)
     }
 }
 
@@ -46,40 +42,24 @@ impl Node for Prefixed {
     }
 }
 
-impl Eval for Prefixed {
-    fn eval(&self, env: Rc<RefCell<ExecutionEnvironment>>) -> Result<Rc<Value>, EvalError> {
-        let value = self.right.eval(env)?;
-
-        match self.op.kind {
-            TokenKind::Bang => match &*value {
-                Value::Boolean(b) => Ok(Value::from_native_bool(!b)),
-                _ => Err(EvalError::InvalidPrefixOperation(
-                    self.op.literal(),
-                    value.to_string(),
-                    self.op.position,
-                )),
-            },
-            TokenKind::Minus => match &*value {
-                Value::Integer(i) => Ok(Value::new_integer(-i)),
-                Value::Float(f) => Ok(Value::new_float(-f)),
-                _ => Err(EvalError::InvalidPrefixOperation(
-                    self.op.literal(),
-                    value.to_string(),
-                    self.op.position,
-                )),
-            },
-            _ => Err(EvalError::InvalidPrefixOperation(
-                self.op.literal(),
-                value.to_string(),
-                self.op.position,
-            )),
-        }
+impl Compile for Prefixed {
+    fn compile(&self, compiler: &mut Compiler) {
+        self.right.compile(compiler);
+
+        let op = PrefixOp::from_token_kind(&self.op.kind).expect("checked in constructor");
+        compiler.emit(OpCode::Prefix(op), self.op.span());
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{eval::FALSE, lexer::Position, parser::Integer};
+    use std::rc::Rc;
+
+    use crate::{
+        eval::{eval_expression, ExecutionEnvironment, Value, FALSE},
+        lexer::Position,
+        parser::Integer,
+    };
 
     use super::*;
 
@@ -98,7 +78,7 @@ mod tests {
     }
 
     #[test]
-    fn test_prefixed_eval() {
+    fn test_prefixed_compile() {
         let token = Token::new(TokenKind::Minus, Position(0, 0));
         let right = Box::new(Expression::Integer(Integer::new(Token::new(
             TokenKind::Integer(42),
@@ -106,10 +86,43 @@ mod tests {
         ))));
         let prefixed = Prefixed::new(token, right);
 
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        prefixed.compile(&mut compiler);
+
+        assert_eq!(
+            compiler.chunk().code,
+            vec![OpCode::Constant(0), OpCode::Prefix(PrefixOp::Negate)]
+        );
+        assert_eq!(compiler.chunk().constants, vec![Value::new_integer(42)]);
+
+        let token = Token::new(TokenKind::Bang, Position(0, 0));
+        let right = Box::new(Expression::new_boolean(Token::new(
+            TokenKind::True,
+            Position(0, 0),
+        )));
+        let prefixed = Prefixed::new(token, right);
+
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        prefixed.compile(&mut compiler);
+
+        assert_eq!(
+            compiler.chunk().code,
+            vec![OpCode::True, OpCode::Prefix(PrefixOp::Not)]
+        );
+    }
+
+    #[test]
+    fn test_prefixed_eval() {
         let env = ExecutionEnvironment::new_global();
-        let result = prefixed.eval(Rc::clone(&env));
 
-        assert!(result.is_ok());
+        let token = Token::new(TokenKind::Minus, Position(0, 0));
+        let right = Box::new(Expression::Integer(Integer::new(Token::new(
+            TokenKind::Integer(42),
+            Position(0, 0),
+        ))));
+        let prefixed = Prefixed::new(token, right);
+
+        let result = eval_expression(&prefixed, Rc::clone(&env));
         assert_eq!(result.unwrap(), Value::new_integer(-42));
 
         let token = Token::new(TokenKind::Bang, Position(0, 0));
@@ -119,9 +132,7 @@ mod tests {
         )));
         let prefixed = Prefixed::new(token, right);
 
-        let result = prefixed.eval(env);
-
-        assert!(result.is_ok());
+        let result = eval_expression(&prefixed, env);
         assert_eq!(result.unwrap(), FALSE.rc());
     }
 }
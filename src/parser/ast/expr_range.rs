@@ -0,0 +1,108 @@
+use std::fmt::Display;
+
+use crate::{
+    eval::{Compile, Compiler, OpCode},
+    lexer::{Token, TokenKind},
+};
+
+use super::{Expression, Node};
+
+/// Integer range ast node. `a..b` excludes the end, `a..=b` includes it.
+#[derive(Debug, PartialEq)]
+pub struct Range {
+    /// `..` or `..=` token
+    pub token: Token,
+    pub start: Box<Expression>,
+    pub end: Box<Expression>,
+}
+
+impl Range {
+    pub fn new(token: Token, start: Box<Expression>, end: Box<Expression>) -> Self {
+        assert!(
+            matches!(token.kind, TokenKind::DotDot | TokenKind::DotDotEquals),
+            "expected range token"
+        );
+        Self { token, start, end }
+    }
+
+    pub fn is_inclusive(&self) -> bool {
+        self.token.kind == TokenKind::DotDotEquals
+    }
+}
+
+impl Display for Range {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}{}{})", self.start, self.token.literal(), self.end)
+    }
+}
+
+impl Node for Range {
+    fn token_literal(&self) -> String {
+        self.token.literal()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Compile for Range {
+    fn compile(&self, compiler: &mut Compiler) {
+        self.start.compile(compiler);
+        self.end.compile(compiler);
+        compiler.emit(
+            OpCode::Range {
+                inclusive: self.is_inclusive(),
+            },
+            self.token.span(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{eval::ExecutionEnvironment, lexer::Position};
+
+    use super::*;
+
+    fn range(kind: TokenKind) -> Range {
+        Range::new(
+            Token::new(kind, Position(0, 0)),
+            Box::new(Expression::new_integer(Token::new(
+                TokenKind::Integer(1),
+                Position(0, 0),
+            ))),
+            Box::new(Expression::new_integer(Token::new(
+                TokenKind::Integer(5),
+                Position(0, 0),
+            ))),
+        )
+    }
+
+    #[test]
+    fn test_range_node() {
+        let exclusive = range(TokenKind::DotDot);
+        assert_eq!(exclusive.to_string(), "(1..5)");
+        assert_eq!(exclusive.token_literal(), "..");
+        assert!(!exclusive.is_inclusive());
+
+        let inclusive = range(TokenKind::DotDotEquals);
+        assert_eq!(inclusive.to_string(), "(1..=5)");
+        assert!(inclusive.is_inclusive());
+    }
+
+    #[test]
+    fn test_range_compile() {
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        range(TokenKind::DotDotEquals).compile(&mut compiler);
+
+        assert_eq!(
+            compiler.chunk().code,
+            vec![
+                OpCode::Constant(0),
+                OpCode::Constant(1),
+                OpCode::Range { inclusive: true },
+            ]
+        );
+    }
+}
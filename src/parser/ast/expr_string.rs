@@ -1,7 +1,7 @@
-use std::{cell::RefCell, rc::Rc};
+use std::fmt::Display;
 
 use crate::{
-    eval::{Eval, EvalError, ExecutionEnvironment, Value},
+    eval::{Compile, Compiler, Value},
     lexer::{Token, TokenKind},
 };
 
@@ -25,9 +25,9 @@ impl StringLiteral {
     }
 }
 
-impl ToString for StringLiteral {
-    fn to_string(&self) -> String {
-        self.token.literal()
+impl Display for StringLiteral {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.token.literal())
     }
 }
 
@@ -41,10 +41,12 @@ impl Node for StringLiteral {
     }
 }
 
-impl Eval for StringLiteral {
-    fn eval(&self, _env: Rc<RefCell<ExecutionEnvironment>>) -> Result<Rc<Value>, EvalError> {
+impl Compile for StringLiteral {
+    fn compile(&self, compiler: &mut Compiler) {
         match &self.token.kind {
-            TokenKind::String(value) => Ok(Value::new_string(value.to_string())),
+            TokenKind::String(value) => {
+                compiler.emit_constant(Value::new_string(value.to_string()), self.token.span())
+            }
             _ => unreachable!(),
         }
     }
@@ -53,7 +55,7 @@ impl Eval for StringLiteral {
 #[cfg(test)]
 mod tests {
     use crate::{
-        eval::{ExecutionEnvironment, Value},
+        eval::{eval_expression, ExecutionEnvironment, OpCode, Value},
         lexer::Position,
     };
 
@@ -71,14 +73,28 @@ mod tests {
     }
 
     #[test]
-    fn test_string_eval() {
+    fn test_string_compile() {
         let token = Token::new(TokenKind::String("who".to_string()), Position(0, 0));
         let string = StringLiteral::new(token);
 
-        let env = ExecutionEnvironment::new_global();
-        let result = string.eval(env);
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        string.compile(&mut compiler);
+
+        assert_eq!(compiler.chunk().code, vec![OpCode::Constant(0)]);
+        assert_eq!(
+            compiler.chunk().constants,
+            vec![Value::new_string("who".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_string_eval() {
+        let string = StringLiteral::new(Token::new(
+            TokenKind::String("who".to_string()),
+            Position(0, 0),
+        ));
 
-        assert!(result.is_ok());
+        let result = eval_expression(&string, ExecutionEnvironment::new_global());
         assert_eq!(result.unwrap(), Value::new_string("who".to_string()));
     }
 }
@@ -0,0 +1,115 @@
+use std::fmt::Display;
+
+use crate::{
+    eval::{Compile, Compiler, OpCode},
+    lexer::{Token, TokenKind},
+};
+
+use super::{Expression, Node};
+
+/// Template string ast node, `"total: ${count * price}"`.
+#[derive(Debug, PartialEq)]
+pub struct Template {
+    pub token: Token,
+    /// Text parts are string literals, the rest are interpolated expressions.
+    pub parts: Vec<Expression>,
+}
+
+impl Template {
+    pub fn new(token: Token, parts: Vec<Expression>) -> Self {
+        assert!(
+            matches!(token.kind, TokenKind::Template(_)),
+            "expected template token"
+        );
+        Self { token, parts }
+    }
+}
+
+impl Display for Template {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for part in &self.parts {
+            match part {
+                Expression::String(string) => write!(f, "{string}")?,
+                expr => write!(f, "${{{expr}}}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Node for Template {
+    fn token_literal(&self) -> String {
+        self.token.literal()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Compile for Template {
+    fn compile(&self, compiler: &mut Compiler) {
+        for part in &self.parts {
+            part.compile(compiler);
+        }
+        compiler.emit(OpCode::Template(self.parts.len()), self.token.span());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        eval::ExecutionEnvironment,
+        lexer::{Position, TemplatePart},
+    };
+
+    use super::*;
+
+    fn template() -> Template {
+        let token = Token::new(
+            TokenKind::Template(vec![
+                TemplatePart::Text("n = ".to_string()),
+                TemplatePart::Interpolation {
+                    source: "n".to_string(),
+                    position: Position(1, 7),
+                    offset: 7,
+                },
+            ]),
+            Position(1, 1),
+        );
+        Template::new(
+            token,
+            vec![
+                Expression::new_string(Token::new(
+                    TokenKind::String("n = ".to_string()),
+                    Position(1, 2),
+                )),
+                Expression::new_identifier(Token::new(
+                    TokenKind::Identifier("n".to_string()),
+                    Position(1, 8),
+                )),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_template_node() {
+        let template = template();
+
+        assert!(template.as_any().is::<Template>());
+        assert_eq!(template.to_string(), "n = ${n}");
+        assert_eq!(template.token_literal(), "n = ${n}");
+        assert_eq!(template.token.end, Position(1, 10));
+    }
+
+    #[test]
+    fn test_template_compile() {
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        template().compile(&mut compiler);
+
+        assert_eq!(
+            compiler.chunk().code,
+            vec![OpCode::Constant(0), OpCode::Get(0), OpCode::Template(2)]
+        );
+    }
+}
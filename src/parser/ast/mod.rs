@@ -1,55 +1,73 @@
 mod expr_array;
 mod expr_boolean;
 mod expr_call;
+mod expr_conditional;
 mod expr_float;
 mod expr_function;
 mod expr_identifier;
 mod expr_index;
 mod expr_infixed;
 mod expr_integer;
+mod expr_map;
+mod expr_match;
 mod expr_prefixed;
+mod expr_range;
 mod expr_string;
+mod expr_template;
+mod pattern;
 mod program;
 mod stmt_assign;
 mod stmt_block;
-mod stmt_condition;
+mod stmt_break;
+mod stmt_continue;
 mod stmt_expr;
+mod stmt_for;
 mod stmt_function;
 mod stmt_let;
 mod stmt_return;
+mod stmt_while;
 
-use std::{cell::RefCell, fmt::Debug, rc::Rc};
+use std::fmt::{Debug, Display};
 
 pub use expr_array::*;
 pub use expr_boolean::*;
 pub use expr_call::*;
+pub use expr_conditional::*;
 pub use expr_float::*;
 pub use expr_function::*;
 pub use expr_identifier::*;
 pub use expr_index::*;
 pub use expr_infixed::*;
 pub use expr_integer::*;
+pub use expr_map::*;
+pub use expr_match::*;
 pub use expr_prefixed::*;
+pub use expr_range::*;
 pub use expr_string::*;
+pub use expr_template::*;
+pub use pattern::*;
 pub use program::*;
 pub use stmt_assign::*;
 pub use stmt_block::*;
-pub use stmt_condition::*;
+pub use stmt_break::*;
+pub use stmt_continue::*;
 pub use stmt_expr::*;
+pub use stmt_for::*;
 pub use stmt_function::*;
 pub use stmt_let::*;
 pub use stmt_return::*;
+pub use stmt_while::*;
 
 use crate::{
-    eval::{Eval, EvalError, ExecutionEnvironment, Value},
+    eval::{Compile, Compiler},
     lexer::Token,
 };
 
 pub trait Node
 where
-    Self: ToString,
+    Self: Display,
     Self: Debug,
-    Self: Eval,
+    Self: Compile,
 {
     /// Returns a token literal of the node.
     fn token_literal(&self) -> String;
@@ -64,59 +82,72 @@ pub enum Statement {
     LetStatement(LetStatement),
     ReturnStatement(ReturnStatement),
     FunctionDeclaration(FunctionDeclaration),
-    Condition(ConditionStatement),
     Assign(AssignStatement),
+    While(WhileStatement),
+    For(ForStatement),
+    Break(BreakStatement),
+    Continue(ContinueStatement),
 }
 
 impl Statement {
-    pub fn new_assign(ident: Identifier, value: Box<Expression>) -> Self {
-        Statement::Assign(AssignStatement::new(ident, value))
+    pub fn new_assign(token: Token, target: Box<Expression>, value: Box<Expression>) -> Self {
+        Statement::Assign(AssignStatement::new(token, target, value))
     }
-    pub fn new_block(token: Token, statements: Vec<Box<Statement>>) -> Self {
+    pub fn new_block(token: Token, statements: Vec<Statement>) -> Self {
         Statement::Block(Block::new(token, statements))
     }
     pub fn new_expression(token: Token, expr: Box<Expression>) -> Self {
         Statement::ExpressionStatement(ExpressionStatement::new(token, expr))
     }
-    pub fn new_let(token: Token, name: Identifier, value: Box<Expression>) -> Self {
-        Statement::LetStatement(LetStatement::new(token, name, value))
+    pub fn new_let(
+        token: Token,
+        name: Identifier,
+        value: Box<Expression>,
+        doc: Option<String>,
+    ) -> Self {
+        Statement::LetStatement(LetStatement::new(token, name, value).with_doc(doc))
     }
     pub fn new_return(token: Token, value: Box<Expression>) -> Self {
         Statement::ReturnStatement(ReturnStatement::new(token, value))
     }
-    pub fn new_condition(
-        token: Token,
-        condition: Box<Expression>,
-        consequence: Block,
-        alternative: Option<Block>,
-    ) -> Self {
-        Statement::Condition(ConditionStatement::new(
-            token,
-            condition,
-            consequence,
-            alternative,
-        ))
-    }
     pub fn new_function(
         token: Token,
         name: Identifier,
         params: Vec<Identifier>,
         body: Block,
+        doc: Option<String>,
     ) -> Self {
-        Statement::FunctionDeclaration(FunctionDeclaration::new(token, name, params, body))
+        Statement::FunctionDeclaration(
+            FunctionDeclaration::new(token, name, params, body).with_doc(doc),
+        )
+    }
+    pub fn new_while(token: Token, condition: Box<Expression>, body: Block) -> Self {
+        Statement::While(WhileStatement::new(token, condition, body))
+    }
+    pub fn new_for(token: Token, item: Identifier, iterable: Box<Expression>, body: Block) -> Self {
+        Statement::For(ForStatement::new(token, item, iterable, body))
+    }
+    pub fn new_break(token: Token) -> Self {
+        Statement::Break(BreakStatement::new(token))
+    }
+    pub fn new_continue(token: Token) -> Self {
+        Statement::Continue(ContinueStatement::new(token))
     }
 }
 
-impl Eval for Statement {
-    fn eval(&self, env: Rc<RefCell<ExecutionEnvironment>>) -> Result<Rc<Value>, EvalError> {
+impl Compile for Statement {
+    fn compile(&self, compiler: &mut Compiler) {
         match self {
-            Statement::Block(block) => block.eval(env),
-            Statement::ExpressionStatement(expr_stmt) => expr_stmt.eval(env),
-            Statement::LetStatement(let_stmt) => let_stmt.eval(env),
-            Statement::ReturnStatement(return_stmt) => return_stmt.eval(env),
-            Statement::FunctionDeclaration(func_decl) => func_decl.eval(env),
-            Statement::Condition(condition) => condition.eval(env),
-            Statement::Assign(assign_stmt) => assign_stmt.eval(env),
+            Statement::Block(block) => block.compile(compiler),
+            Statement::ExpressionStatement(expr_stmt) => expr_stmt.compile(compiler),
+            Statement::LetStatement(let_stmt) => let_stmt.compile(compiler),
+            Statement::ReturnStatement(return_stmt) => return_stmt.compile(compiler),
+            Statement::FunctionDeclaration(func_decl) => func_decl.compile(compiler),
+            Statement::Assign(assign_stmt) => assign_stmt.compile(compiler),
+            Statement::While(while_stmt) => while_stmt.compile(compiler),
+            Statement::For(for_stmt) => for_stmt.compile(compiler),
+            Statement::Break(break_stmt) => break_stmt.compile(compiler),
+            Statement::Continue(continue_stmt) => continue_stmt.compile(compiler),
         }
     }
 }
@@ -129,8 +160,11 @@ impl Node for Statement {
             Statement::LetStatement(let_stmt) => let_stmt.token_literal(),
             Statement::ReturnStatement(return_stmt) => return_stmt.token_literal(),
             Statement::FunctionDeclaration(func_decl) => func_decl.token_literal(),
-            Statement::Condition(condition) => condition.token_literal(),
             Statement::Assign(assign_stmt) => assign_stmt.token_literal(),
+            Statement::While(while_stmt) => while_stmt.token_literal(),
+            Statement::For(for_stmt) => for_stmt.token_literal(),
+            Statement::Break(break_stmt) => break_stmt.token_literal(),
+            Statement::Continue(continue_stmt) => continue_stmt.token_literal(),
         }
     }
 
@@ -141,22 +175,28 @@ impl Node for Statement {
             Statement::LetStatement(let_stmt) => let_stmt,
             Statement::ReturnStatement(return_stmt) => return_stmt,
             Statement::FunctionDeclaration(func_decl) => func_decl,
-            Statement::Condition(condition) => condition,
             Statement::Assign(assign_stmt) => assign_stmt,
+            Statement::While(while_stmt) => while_stmt,
+            Statement::For(for_stmt) => for_stmt,
+            Statement::Break(break_stmt) => break_stmt,
+            Statement::Continue(continue_stmt) => continue_stmt,
         }
     }
 }
 
-impl ToString for Statement {
-    fn to_string(&self) -> String {
+impl Display for Statement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Statement::Block(block) => block.to_string(),
-            Statement::ExpressionStatement(expr_stmt) => expr_stmt.to_string(),
-            Statement::LetStatement(let_stmt) => let_stmt.to_string(),
-            Statement::ReturnStatement(return_stmt) => return_stmt.to_string(),
-            Statement::FunctionDeclaration(func_decl) => func_decl.to_string(),
-            Statement::Condition(condition) => condition.to_string(),
-            Statement::Assign(assign_stmt) => assign_stmt.to_string(),
+            Statement::Block(block) => write!(f, "{block}"),
+            Statement::ExpressionStatement(expr_stmt) => write!(f, "{expr_stmt}"),
+            Statement::LetStatement(let_stmt) => write!(f, "{let_stmt}"),
+            Statement::ReturnStatement(return_stmt) => write!(f, "{return_stmt}"),
+            Statement::FunctionDeclaration(func_decl) => write!(f, "{func_decl}"),
+            Statement::Assign(assign_stmt) => write!(f, "{assign_stmt}"),
+            Statement::While(while_stmt) => write!(f, "{while_stmt}"),
+            Statement::For(for_stmt) => write!(f, "{for_stmt}"),
+            Statement::Break(break_stmt) => write!(f, "{break_stmt}"),
+            Statement::Continue(continue_stmt) => write!(f, "{continue_stmt}"),
         }
     }
 }
@@ -172,19 +212,20 @@ pub enum Expression {
     Integer(Integer),
     Prefixed(Prefixed),
     String(StringLiteral),
+    Template(Template),
+    Conditional(Conditional),
+    Match(Match),
     Array(Array),
     Index(Index),
+    Range(Range),
+    Map(MapLiteral),
 }
 
 impl Expression {
     pub fn new_boolean(token: Token) -> Self {
         Expression::Boolean(Boolean::new(token))
     }
-    pub fn new_function_call(
-        token: Token,
-        func: Box<Expression>,
-        args: Vec<Box<Expression>>,
-    ) -> Self {
+    pub fn new_function_call(token: Token, func: Box<Expression>, args: Vec<Expression>) -> Self {
         Expression::FunctionCall(FunctionCall::new(token, func, args))
     }
     pub fn new_float(token: Token) -> Self {
@@ -208,28 +249,42 @@ impl Expression {
     pub fn new_string(token: Token) -> Self {
         Expression::String(StringLiteral::new(token))
     }
-    pub fn new_array(token: Token, els: Vec<Box<Expression>>) -> Self {
+    pub fn new_template(token: Token, parts: Vec<Expression>) -> Self {
+        Expression::Template(Template::new(token, parts))
+    }
+    pub fn new_array(token: Token, els: Vec<Expression>) -> Self {
         Expression::Array(Array::new(token, els))
     }
     pub fn new_index(token: Token, of: Box<Expression>, index: Box<Expression>) -> Self {
         Expression::Index(Index::new(token, of, index))
     }
+    pub fn new_map(token: Token, pairs: Vec<(Box<Expression>, Box<Expression>)>) -> Self {
+        Expression::Map(MapLiteral::new(token, pairs))
+    }
+    pub fn new_range(token: Token, start: Box<Expression>, end: Box<Expression>) -> Self {
+        Expression::Range(Range::new(token, start, end))
+    }
 }
 
-impl Eval for Expression {
-    fn eval(&self, env: Rc<RefCell<ExecutionEnvironment>>) -> Result<Rc<Value>, EvalError> {
+impl Compile for Expression {
+    fn compile(&self, compiler: &mut Compiler) {
         match self {
-            Expression::Boolean(boolean) => boolean.eval(env),
-            Expression::FunctionCall(call) => call.eval(env),
-            Expression::Float(float) => float.eval(env),
-            Expression::Function(func) => func.eval(env),
-            Expression::Identifier(ident) => ident.eval(env),
-            Expression::Infixed(infix) => infix.eval(env),
-            Expression::Integer(int) => int.eval(env),
-            Expression::Prefixed(prefix) => prefix.eval(env),
-            Expression::String(string) => string.eval(env),
-            Expression::Array(arr) => arr.eval(env),
-            Expression::Index(idx) => idx.eval(env),
+            Expression::Boolean(boolean) => boolean.compile(compiler),
+            Expression::FunctionCall(call) => call.compile(compiler),
+            Expression::Float(float) => float.compile(compiler),
+            Expression::Function(func) => func.compile(compiler),
+            Expression::Identifier(ident) => ident.compile(compiler),
+            Expression::Infixed(infix) => infix.compile(compiler),
+            Expression::Integer(int) => int.compile(compiler),
+            Expression::Prefixed(prefix) => prefix.compile(compiler),
+            Expression::String(string) => string.compile(compiler),
+            Expression::Template(template) => template.compile(compiler),
+            Expression::Conditional(conditional) => conditional.compile(compiler),
+            Expression::Match(expr) => expr.compile(compiler),
+            Expression::Array(arr) => arr.compile(compiler),
+            Expression::Index(idx) => idx.compile(compiler),
+            Expression::Range(range) => range.compile(compiler),
+            Expression::Map(map) => map.compile(compiler),
         }
     }
 }
@@ -246,8 +301,13 @@ impl Node for Expression {
             Expression::Integer(int) => int.token_literal(),
             Expression::Prefixed(prefix) => prefix.token_literal(),
             Expression::String(string) => string.token_literal(),
+            Expression::Template(template) => template.token_literal(),
+            Expression::Conditional(conditional) => conditional.token_literal(),
+            Expression::Match(expr) => expr.token_literal(),
             Expression::Array(arr) => arr.token_literal(),
             Expression::Index(idx) => idx.token_literal(),
+            Expression::Range(range) => range.token_literal(),
+            Expression::Map(map) => map.token_literal(),
         }
     }
 
@@ -262,26 +322,36 @@ impl Node for Expression {
             Expression::Integer(int) => int,
             Expression::Prefixed(prefix) => prefix,
             Expression::String(string) => string,
+            Expression::Template(template) => template,
+            Expression::Conditional(conditional) => conditional,
+            Expression::Match(expr) => expr,
             Expression::Array(arr) => arr,
             Expression::Index(idx) => idx,
+            Expression::Range(range) => range,
+            Expression::Map(map) => map,
         }
     }
 }
 
-impl ToString for Expression {
-    fn to_string(&self) -> String {
+impl Display for Expression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Expression::Boolean(boolean) => boolean.to_string(),
-            Expression::FunctionCall(call) => call.to_string(),
-            Expression::Float(float) => float.to_string(),
-            Expression::Function(func) => func.to_string(),
-            Expression::Identifier(ident) => ident.to_string(),
-            Expression::Infixed(infix) => infix.to_string(),
-            Expression::Integer(int) => int.to_string(),
-            Expression::Prefixed(prefix) => prefix.to_string(),
-            Expression::String(string) => string.to_string(),
-            Expression::Array(arr) => arr.to_string(),
-            Expression::Index(idx) => idx.to_string(),
+            Expression::Boolean(boolean) => write!(f, "{boolean}"),
+            Expression::FunctionCall(call) => write!(f, "{call}"),
+            Expression::Float(float) => write!(f, "{float}"),
+            Expression::Function(func) => write!(f, "{func}"),
+            Expression::Identifier(ident) => write!(f, "{ident}"),
+            Expression::Infixed(infix) => write!(f, "{infix}"),
+            Expression::Integer(int) => write!(f, "{int}"),
+            Expression::Prefixed(prefix) => write!(f, "{prefix}"),
+            Expression::String(string) => write!(f, "{string}"),
+            Expression::Template(template) => write!(f, "{template}"),
+            Expression::Conditional(conditional) => write!(f, "{conditional}"),
+            Expression::Match(expr) => write!(f, "{expr}"),
+            Expression::Array(arr) => write!(f, "{arr}"),
+            Expression::Index(idx) => write!(f, "{idx}"),
+            Expression::Range(range) => write!(f, "{range}"),
+            Expression::Map(map) => write!(f, "{map}"),
         }
     }
 }
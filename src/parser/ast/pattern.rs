@@ -0,0 +1,300 @@
+use std::{fmt::Display, rc::Rc};
+
+use crate::{
+    eval::{MapKey, MatchPattern, Value},
+    lexer::{Span, Token, TokenKind},
+};
+
+use super::Identifier;
+
+/// Number, string or boolean literal of a pattern. Numbers may have a `-` sign.
+#[derive(Debug, PartialEq)]
+pub struct PatternLiteral {
+    /// The `-` token of a negative number.
+    pub sign: Option<Token>,
+    pub token: Token,
+}
+
+impl PatternLiteral {
+    pub fn new(sign: Option<Token>, token: Token) -> Self {
+        assert!(
+            match &token.kind {
+                TokenKind::Integer(_) | TokenKind::BigInt(_) | TokenKind::Float(_) => true,
+                TokenKind::String(_) | TokenKind::True | TokenKind::False => sign.is_none(),
+                _ => false,
+            },
+            "expected literal token"
+        );
+        Self { sign, token }
+    }
+
+    pub fn value(&self) -> Rc<Value> {
+        let negative = self.sign.is_some();
+        match &self.token.kind {
+            TokenKind::Integer(value) if negative => Value::new_integer(-value),
+            TokenKind::Integer(value) => Value::new_integer(*value),
+            TokenKind::BigInt(value) if negative => Value::new_bigint(-value),
+            TokenKind::BigInt(value) => Value::new_bigint(value.clone()),
+            TokenKind::Float(value) if negative => Value::new_float(-value),
+            TokenKind::Float(value) => Value::new_float(*value),
+            TokenKind::String(value) => Value::new_string(value.clone()),
+            TokenKind::True => Value::from_native_bool(true),
+            TokenKind::False => Value::from_native_bool(false),
+            _ => unreachable!("checked in constructor"),
+        }
+    }
+
+    fn span(&self) -> Span {
+        let start = self.sign.as_ref().unwrap_or(&self.token).position;
+        Span::new(start, self.token.end)
+    }
+}
+
+impl Display for PatternLiteral {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.sign {
+            Some(sign) => write!(f, "{}{}", sign.literal(), self.token.literal()),
+            None => write!(f, "{}", self.token.literal()),
+        }
+    }
+}
+
+/// Pattern of a `match` arm.
+#[derive(Debug, PartialEq)]
+pub enum Pattern {
+    /// `_`, matches anything.
+    Wildcard(Token),
+    /// Matches values equal to the literal.
+    Literal(PatternLiteral),
+    /// Matches anything and binds it to the name in the arm.
+    Binding(Identifier),
+    /// `[first, second]`, matches arrays of the same length.
+    Array {
+        token: Token,
+        elements: Vec<Pattern>,
+    },
+    /// `{"key": pattern}`, matches maps having the keys, other keys are ignored.
+    Map {
+        token: Token,
+        entries: Vec<(PatternLiteral, Pattern)>,
+    },
+    /// `1 | 2`, matches if any of the alternatives does.
+    Or(Vec<Pattern>),
+}
+
+impl Pattern {
+    pub fn span(&self) -> Span {
+        match self {
+            Pattern::Wildcard(token) => token.span(),
+            Pattern::Literal(literal) => literal.span(),
+            Pattern::Binding(ident) => ident.token.span(),
+            Pattern::Array { token, .. } | Pattern::Map { token, .. } => token.span(),
+            Pattern::Or(alternatives) => alternatives[0].span(),
+        }
+    }
+
+    /// Names bound by the pattern, each one once, in order of appearance.
+    pub fn bindings(&self) -> Vec<String> {
+        let mut names = vec![];
+        self.collect_bindings(&mut names);
+        names
+    }
+
+    fn collect_bindings(&self, names: &mut Vec<String>) {
+        match self {
+            Pattern::Binding(ident) => {
+                let name = ident.to_string();
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+            Pattern::Array { elements, .. } => {
+                for element in elements {
+                    element.collect_bindings(names);
+                }
+            }
+            Pattern::Map { entries, .. } => {
+                for (_, value) in entries {
+                    value.collect_bindings(names);
+                }
+            }
+            Pattern::Or(alternatives) => {
+                for alternative in alternatives {
+                    alternative.collect_bindings(names);
+                }
+            }
+            Pattern::Wildcard(_) | Pattern::Literal(_) => {}
+        }
+    }
+
+    /// Builds the runtime pattern, a name is bound to its index in `bindings`.
+    pub fn to_match_pattern(&self, bindings: &[String]) -> MatchPattern {
+        match self {
+            Pattern::Wildcard(_) => MatchPattern::Any,
+            Pattern::Literal(literal) => MatchPattern::Constant(literal.value()),
+            Pattern::Binding(ident) => {
+                let name = ident.to_string();
+                let slot = bindings
+                    .iter()
+                    .position(|binding| *binding == name)
+                    .expect("bindings of the pattern");
+                MatchPattern::Bind(slot)
+            }
+            Pattern::Array { elements, .. } => MatchPattern::Array(
+                elements
+                    .iter()
+                    .map(|element| element.to_match_pattern(bindings))
+                    .collect(),
+            ),
+            Pattern::Map { entries, .. } => MatchPattern::Map(
+                entries
+                    .iter()
+                    .map(|(key, value)| {
+                        let key = MapKey::from_value(&key.value())
+                            .expect("map pattern keys are checked by the parser");
+                        (key, value.to_match_pattern(bindings))
+                    })
+                    .collect(),
+            ),
+            Pattern::Or(alternatives) => MatchPattern::Or(
+                alternatives
+                    .iter()
+                    .map(|alternative| alternative.to_match_pattern(bindings))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pattern::Wildcard(token) => write!(f, "{}", token.literal()),
+            Pattern::Literal(literal) => write!(f, "{literal}"),
+            Pattern::Binding(ident) => write!(f, "{ident}"),
+            Pattern::Array { elements, .. } => write!(
+                f,
+                "[{}]",
+                elements
+                    .iter()
+                    .map(|element| element.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Pattern::Map { entries, .. } => write!(
+                f,
+                "{{{}}}",
+                entries
+                    .iter()
+                    .map(|(key, value)| format!("{key}: {value}"))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Pattern::Or(alternatives) => write!(
+                f,
+                "{}",
+                alternatives
+                    .iter()
+                    .map(|alternative| alternative.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" | ")
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Position;
+
+    fn token(kind: TokenKind) -> Token {
+        Token::new(kind, Position(1, 1))
+    }
+
+    fn binding(name: &str) -> Pattern {
+        Pattern::Binding(Identifier::new(token(TokenKind::Identifier(
+            name.to_string(),
+        ))))
+    }
+
+    #[test]
+    fn test_pattern_literal_value() {
+        let tests = [
+            (None, TokenKind::Integer(1), Value::Integer(1)),
+            (
+                Some(TokenKind::Minus),
+                TokenKind::Integer(1),
+                Value::Integer(-1),
+            ),
+            (
+                Some(TokenKind::Minus),
+                TokenKind::BigInt("9223372036854775808".parse().unwrap()),
+                Value::Integer(i64::MIN),
+            ),
+            (
+                Some(TokenKind::Minus),
+                TokenKind::Float(0.5),
+                Value::Float(-0.5),
+            ),
+            (
+                None,
+                TokenKind::String("a".to_string()),
+                Value::String("a".to_string()),
+            ),
+            (None, TokenKind::True, Value::Boolean(true)),
+        ];
+
+        for (sign, kind, expected) in tests {
+            let literal = PatternLiteral::new(sign.map(token), token(kind));
+            assert_eq!(*literal.value(), expected, "{literal}");
+        }
+    }
+
+    #[test]
+    fn test_pattern_bindings() {
+        // [x, _] | [0, x] | {"y": y}
+        let pattern = Pattern::Or(vec![
+            Pattern::Array {
+                token: token(TokenKind::LeftBracket),
+                elements: vec![
+                    binding("x"),
+                    Pattern::Wildcard(token(TokenKind::Identifier("_".to_string()))),
+                ],
+            },
+            Pattern::Array {
+                token: token(TokenKind::LeftBracket),
+                elements: vec![
+                    Pattern::Literal(PatternLiteral::new(None, token(TokenKind::Integer(0)))),
+                    binding("x"),
+                ],
+            },
+            Pattern::Map {
+                token: token(TokenKind::LeftCurly),
+                entries: vec![(
+                    PatternLiteral::new(None, token(TokenKind::String("y".to_string()))),
+                    binding("y"),
+                )],
+            },
+        ]);
+
+        assert_eq!(pattern.to_string(), "[x, _] | [0, x] | {y: y}");
+        assert_eq!(pattern.bindings(), vec!["x", "y"]);
+
+        let bindings = pattern.bindings();
+        assert_eq!(
+            pattern.to_match_pattern(&bindings),
+            MatchPattern::Or(vec![
+                MatchPattern::Array(vec![MatchPattern::Bind(0), MatchPattern::Any]),
+                MatchPattern::Array(vec![
+                    MatchPattern::Constant(Value::new_integer(0)),
+                    MatchPattern::Bind(0),
+                ]),
+                MatchPattern::Map(vec![(
+                    MapKey::String("y".to_string()),
+                    MatchPattern::Bind(1)
+                )]),
+            ])
+        );
+    }
+}
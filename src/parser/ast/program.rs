@@ -1,12 +1,15 @@
-use std::{cell::RefCell, rc::Rc};
+use std::fmt::Display;
 
-use crate::eval::{Eval, EvalError, ExecutionEnvironment, Value, VOID};
+use crate::{
+    eval::{Compile, Compiler, OpCode},
+    lexer::{Position, Span},
+};
 
 use super::{Node, Statement};
 
 #[derive(Debug, PartialEq)]
 pub struct Program {
-    pub statements: Vec<Box<Statement>>,
+    pub statements: Vec<Statement>,
 }
 
 impl Program {
@@ -14,18 +17,22 @@ impl Program {
         Program { statements: vec![] }
     }
 
-    pub fn push_statement(&mut self, statement: Box<Statement>) {
+    pub fn push_statement(&mut self, statement: Statement) {
         self.statements.push(statement);
     }
 }
 
-impl ToString for Program {
-    fn to_string(&self) -> String {
-        self.statements
-            .iter()
-            .map(|stmt| stmt.to_string())
-            .collect::<Vec<String>>()
-            .join("\n")
+impl Display for Program {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.statements
+                .iter()
+                .map(|stmt| stmt.to_string())
+                .collect::<Vec<String>>()
+                .join("\n")
+        )
     }
 }
 
@@ -39,39 +46,38 @@ impl Node for Program {
     }
 }
 
-impl Eval for Program {
-    fn eval(&self, env: Rc<RefCell<ExecutionEnvironment>>) -> Result<Rc<Value>, EvalError> {
-        let mut result = VOID.rc();
-        for stmt in &self.statements {
-            result = stmt.eval(Rc::clone(&env))?;
-        }
-        Ok(result)
+/// Returns the value of the last statement, so the REPL can print it.
+impl Compile for Program {
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.compile_statements_value(&self.statements, Span::point(Position(0, 0)), true);
+
+        compiler.emit(OpCode::Return, Span::point(Position(0, 0)));
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::lexer::{Position, Token, TokenKind};
+    use crate::lexer::{Token, TokenKind};
     use crate::parser::Expression;
 
     #[test]
     fn test_program() {
         let mut program = Program::new();
-        program.push_statement(Box::new(Statement::new_expression(
+        program.push_statement(Statement::new_expression(
             Token::new(TokenKind::Integer(5), Position(0, 0)),
             Box::new(Expression::new_integer(Token::new(
                 TokenKind::Integer(5),
                 Position(0, 0),
             ))),
-        )));
-        program.push_statement(Box::new(Statement::new_return(
+        ));
+        program.push_statement(Statement::new_return(
             Token::new(TokenKind::Return, Position(0, 0)),
             Box::new(Expression::new_boolean(Token::new(
                 TokenKind::True,
                 Position(0, 0),
             ))),
-        )));
+        ));
 
         assert!(program.as_any().is::<Program>());
         assert_eq!(program.statements.len(), 2);
@@ -1,35 +1,57 @@
-use std::{cell::RefCell, rc::Rc};
+use std::fmt::Display;
 
-use crate::eval::{Eval, EvalError, ExecutionEnvironment, Value, VOID};
+use crate::{
+    eval::{Compile, Compiler, InfixOp, OpCode},
+    lexer::{Token, TokenKind},
+};
 
-use super::{Expression, Identifier, Node};
+use super::{Expression, Node};
 
 /// Assign statement ast node.
+///
+/// Covers plain (`x = v`) and compound (`x += v`) assignment to a variable or an index.
 #[derive(Debug, PartialEq)]
 pub struct AssignStatement {
-    pub identifier: Identifier,
+    /// Assignment operator token
+    pub token: Token,
+    /// Identifier or index expression
+    pub target: Box<Expression>,
     pub value: Box<Expression>,
 }
 
 impl AssignStatement {
-    pub fn new(identifier: Identifier, value: Box<Expression>) -> Self {
-        Self { identifier, value }
+    pub fn new(token: Token, target: Box<Expression>, value: Box<Expression>) -> Self {
+        assert!(
+            token.kind == TokenKind::Equals
+                || InfixOp::from_assign_token_kind(&token.kind).is_some(),
+            "expected assignment token"
+        );
+        assert!(
+            Self::is_valid_target(&target),
+            "expected identifier or index target"
+        );
+        Self {
+            token,
+            target,
+            value,
+        }
+    }
+
+    /// Checks if the expression can be assigned to.
+    pub fn is_valid_target(target: &Expression) -> bool {
+        matches!(target, Expression::Identifier(_) | Expression::Index(_))
     }
 }
 
-impl ToString for AssignStatement {
-    fn to_string(&self) -> String {
-        let mut out = String::new();
-        out.push_str(&self.identifier.to_string());
-        out.push_str(" = ");
-        out.push_str(&self.value.to_string());
-        out
+impl Display for AssignStatement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.target, self.token.literal(), self.value)
     }
 }
 
 impl Node for AssignStatement {
     fn token_literal(&self) -> String {
-        self.identifier.token_literal()
+        self.token.literal()
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -37,71 +59,165 @@ impl Node for AssignStatement {
     }
 }
 
-impl Eval for AssignStatement {
-    fn eval(&self, env: Rc<RefCell<ExecutionEnvironment>>) -> Result<Rc<Value>, EvalError> {
-        let to_store = self.value.eval(Rc::clone(&env))?;
-        if to_store == VOID.rc() {
-            return Err(EvalError::TriedToStoreVoid(self.identifier.token.position));
+/// Compound assignment reads the target, applies the operator and stores the result back.
+impl Compile for AssignStatement {
+    fn compile(&self, compiler: &mut Compiler) {
+        let op = InfixOp::from_assign_token_kind(&self.token.kind);
+
+        match self.target.as_ref() {
+            Expression::Identifier(ident) => {
+                if op.is_some() {
+                    compiler.emit_get(&ident.token_literal(), ident.token.span());
+                }
+                self.value.compile(compiler);
+                if let Some(op) = op {
+                    compiler.emit(OpCode::Infix(op), self.token.span());
+                }
+                compiler.emit_set(&ident.token_literal(), ident.token.span());
+            }
+            Expression::Index(index) => {
+                index.of.compile(compiler);
+                index.index.compile(compiler);
+                if op.is_some() {
+                    compiler.emit(OpCode::Dup2, index.token.span());
+                    compiler.emit(OpCode::Index, index.token.span());
+                }
+                self.value.compile(compiler);
+                if let Some(op) = op {
+                    compiler.emit(OpCode::Infix(op), self.token.span());
+                }
+                compiler.emit(OpCode::SetIndex, self.token.span());
+            }
+            _ => unreachable!("checked in constructor"),
         }
-        let name = self.identifier.token_literal();
-        if !env.borrow_mut().assign(&name, to_store) {
-            return Err(EvalError::NotDefined(name, self.identifier.token.position));
-        }
-
-        Ok(VOID.rc())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::rc::Rc;
+
     use super::*;
     use crate::{
-        eval::Environment,
-        lexer::{Position, Token, TokenKind},
+        eval::{eval_statement, Binding, Environment, ExecutionEnvironment, Value},
+        lexer::Position,
     };
 
-    #[test]
-    fn test_assign_statement() {
-        let ident = Identifier::new(Token::new(
+    fn identifier() -> Box<Expression> {
+        Box::new(Expression::new_identifier(Token::new(
             TokenKind::Identifier("who_cares".to_string()),
             Position(0, 0),
-        ));
-        let int = Expression::new_integer(Token::new(TokenKind::Integer(69), Position(0, 0)));
-
-        let stmt = AssignStatement::new(ident, Box::new(int));
+        )))
+    }
 
-        assert_eq!(stmt.token_literal(), "who_cares");
-        assert_eq!(stmt.to_string(), "who_cares = 69");
+    fn integer() -> Box<Expression> {
+        Box::new(Expression::new_integer(Token::new(
+            TokenKind::Integer(69),
+            Position(0, 0),
+        )))
     }
 
     #[test]
-    fn test_assign_eval() {
-        let ident = Identifier::new(Token::new(
-            TokenKind::Identifier("who_cares".to_string()),
-            Position(0, 0),
+    fn test_assign_statement() {
+        let token = Token::new(TokenKind::Equals, Position(0, 0));
+        let stmt = AssignStatement::new(token, identifier(), integer());
+
+        assert_eq!(stmt.token_literal(), "=");
+        assert_eq!(stmt.to_string(), "who_cares = 69");
+
+        let token = Token::new(TokenKind::PlusEquals, Position(0, 0));
+        let target = Box::new(Expression::new_index(
+            Token::new(TokenKind::LeftBracket, Position(0, 0)),
+            identifier(),
+            integer(),
         ));
-        let int = Expression::new_integer(Token::new(TokenKind::Integer(69), Position(0, 0)));
+        let stmt = AssignStatement::new(token, target, integer());
+
+        assert_eq!(stmt.to_string(), "who_cares[69] += 69");
+    }
 
-        let stmt = AssignStatement::new(ident, Box::new(int));
+    #[test]
+    #[should_panic(expected = "expected identifier or index target")]
+    fn test_assign_invalid_target() {
+        let token = Token::new(TokenKind::Equals, Position(0, 0));
+        AssignStatement::new(token, integer(), integer());
+    }
 
-        let global_env = ExecutionEnvironment::new_global();
-        global_env
-            .borrow_mut()
-            .set("who_cares".to_string(), Value::new_integer(1));
+    #[test]
+    fn test_assign_compile() {
+        let token = Token::new(TokenKind::Equals, Position(0, 0));
+        let stmt = AssignStatement::new(token, identifier(), integer());
+
+        let env = ExecutionEnvironment::new_global();
+        let slot = env.borrow_mut().intern("who_cares");
+
+        let mut compiler = Compiler::new(env);
+        stmt.compile(&mut compiler);
+
+        assert_eq!(
+            compiler.chunk().code,
+            vec![OpCode::Constant(0), OpCode::Set(0)]
+        );
+        assert_eq!(
+            compiler.chunk().lookups[0].bindings,
+            vec![Binding::Global(slot)]
+        );
+    }
 
-        let local_env = ExecutionEnvironment::new_local(Rc::clone(&global_env));
-        local_env
-            .borrow_mut()
-            .set("who_cares".to_string(), Value::new_integer(2));
+    #[test]
+    fn test_compound_assign_compile() {
+        let token = Token::new(TokenKind::MinusEquals, Position(0, 0));
+        let stmt = AssignStatement::new(token, identifier(), integer());
+
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        stmt.compile(&mut compiler);
+
+        assert_eq!(
+            compiler.chunk().code,
+            vec![
+                OpCode::Get(0),
+                OpCode::Constant(0),
+                OpCode::Infix(InfixOp::Subtract),
+                OpCode::Set(1),
+            ]
+        );
+
+        let token = Token::new(TokenKind::AsteriskEquals, Position(0, 0));
+        let target = Box::new(Expression::new_index(
+            Token::new(TokenKind::LeftBracket, Position(0, 0)),
+            identifier(),
+            integer(),
+        ));
+        let stmt = AssignStatement::new(token, target, integer());
+
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        stmt.compile(&mut compiler);
+
+        assert_eq!(
+            compiler.chunk().code,
+            vec![
+                OpCode::Get(0),
+                OpCode::Constant(0),
+                OpCode::Dup2,
+                OpCode::Index,
+                OpCode::Constant(1),
+                OpCode::Infix(InfixOp::Multiply),
+                OpCode::SetIndex,
+            ]
+        );
+    }
 
-        let result = stmt.eval(Rc::clone(&local_env));
+    #[test]
+    fn test_assign_eval() {
+        let token = Token::new(TokenKind::Equals, Position(0, 0));
+        let stmt = AssignStatement::new(token, identifier(), integer());
 
-        let global_stored = global_env.borrow().get("who_cares");
-        let local_stored = local_env.borrow().get("who_cares");
+        let env = ExecutionEnvironment::new_global();
+        env.borrow_mut()
+            .set("who_cares".to_string(), Value::new_integer(1));
 
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), VOID.rc());
-        assert_eq!(global_stored, Some(Value::new_integer(1)));
-        assert_eq!(local_stored, Some(Value::new_integer(69)));
+        let result = eval_statement(&stmt, Rc::clone(&env));
+        assert!(result.unwrap().is_void());
+        assert_eq!(env.borrow().get("who_cares"), Some(Value::new_integer(69)));
     }
 }
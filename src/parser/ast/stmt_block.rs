@@ -1,8 +1,8 @@
-use std::{cell::RefCell, rc::Rc};
+use std::fmt::Display;
 
 use super::{Node, Statement};
 use crate::{
-    eval::{Eval, EvalError, ExecutionEnvironment, Value, VOID},
+    eval::{Compile, Compiler},
     lexer::{Token, TokenKind},
 };
 
@@ -10,26 +10,23 @@ use crate::{
 #[derive(Debug, PartialEq)]
 pub struct Block {
     pub token: Token,
-    pub statements: Vec<Box<Statement>>,
+    pub statements: Vec<Statement>,
 }
 
 impl Block {
-    pub fn new(token: Token, statements: Vec<Box<Statement>>) -> Self {
+    pub fn new(token: Token, statements: Vec<Statement>) -> Self {
         assert_eq!(token.kind, TokenKind::LeftCurly);
         Block { token, statements }
     }
 }
 
-impl ToString for Block {
-    fn to_string(&self) -> String {
-        let mut result = String::from("{\n");
+impl Display for Block {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{{")?;
         for statement in &self.statements {
-            result.push_str("  ");
-            result.push_str(&statement.to_string());
-            result.push('\n');
+            writeln!(f, "  {statement}")?;
         }
-        result.push('}');
-        result
+        write!(f, "}}")
     }
 }
 
@@ -43,24 +40,22 @@ impl Node for Block {
     }
 }
 
-impl Eval for Block {
-    fn eval(&self, env: Rc<RefCell<ExecutionEnvironment>>) -> Result<Rc<Value>, EvalError> {
-        let mut result = VOID.rc();
-
+/// Compiles the statements in the current scope.
+/// See `Compiler::compile_scoped_block` for blocks that open a scope.
+impl Compile for Block {
+    fn compile(&self, compiler: &mut Compiler) {
         for stmt in &self.statements {
-            result = stmt.eval(Rc::clone(&env))?;
-            if result.is_returned() {
-                break;
-            }
+            stmt.compile(compiler);
         }
-
-        Ok(result)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::rc::Rc;
+
     use crate::{
+        eval::{eval_compiled, ExecutionEnvironment, OpCode, Value},
         lexer::Position,
         parser::{Expression, Identifier},
     };
@@ -72,7 +67,7 @@ mod tests {
         let block = Block::new(
             Token::new(TokenKind::LeftCurly, Position(0, 0)),
             vec![
-                Box::new(Statement::new_let(
+                Statement::new_let(
                     Token::new(TokenKind::Let, Position(0, 0)),
                     Identifier::new(Token::new(
                         TokenKind::Identifier("x".to_string()),
@@ -82,14 +77,15 @@ mod tests {
                         TokenKind::Integer(5),
                         Position(0, 0),
                     ))),
-                )),
-                Box::new(Statement::new_return(
+                    None,
+                ),
+                Statement::new_return(
                     Token::new(TokenKind::Return, Position(0, 0)),
                     Box::new(Expression::new_identifier(Token::new(
                         TokenKind::Identifier("x".to_string()),
                         Position(0, 0),
                     ))),
-                )),
+                ),
             ],
         );
 
@@ -102,11 +98,11 @@ mod tests {
     }
 
     #[test]
-    fn test_block_eval() {
+    fn test_block_compile() {
         let block = Block::new(
             Token::new(TokenKind::LeftCurly, Position(0, 0)),
             vec![
-                Box::new(Statement::new_let(
+                Statement::new_let(
                     Token::new(TokenKind::Let, Position(0, 0)),
                     Identifier::new(Token::new(
                         TokenKind::Identifier("x".to_string()),
@@ -116,37 +112,80 @@ mod tests {
                         TokenKind::Integer(5),
                         Position(0, 0),
                     ))),
-                )),
-                Box::new(Statement::new_return(
+                    None,
+                ),
+                Statement::new_return(
                     Token::new(TokenKind::Return, Position(0, 0)),
                     Box::new(Expression::new_identifier(Token::new(
                         TokenKind::Identifier("x".to_string()),
                         Position(0, 0),
                     ))),
-                )),
+                ),
             ],
         );
 
         let env = ExecutionEnvironment::new_global();
-        let result = block.eval(Rc::clone(&env));
+        let slot = env.borrow_mut().intern("x");
+
+        let mut compiler = Compiler::new(env);
+        block.compile(&mut compiler);
 
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), Value::new_returned(Value::new_integer(5)));
+        assert_eq!(
+            compiler.chunk().code,
+            vec![
+                OpCode::Constant(0),
+                OpCode::DefineGlobal(slot),
+                OpCode::Get(0),
+                OpCode::Return
+            ]
+        );
+    }
 
+    #[test]
+    fn test_block_eval() {
         let block = Block::new(
             Token::new(TokenKind::LeftCurly, Position(0, 0)),
-            vec![Box::new(Statement::new_expression(
+            vec![
+                Statement::new_let(
+                    Token::new(TokenKind::Let, Position(0, 0)),
+                    Identifier::new(Token::new(
+                        TokenKind::Identifier("x".to_string()),
+                        Position(0, 0),
+                    )),
+                    Box::new(Expression::new_integer(Token::new(
+                        TokenKind::Integer(5),
+                        Position(0, 0),
+                    ))),
+                    None,
+                ),
+                Statement::new_return(
+                    Token::new(TokenKind::Return, Position(0, 0)),
+                    Box::new(Expression::new_identifier(Token::new(
+                        TokenKind::Identifier("x".to_string()),
+                        Position(0, 0),
+                    ))),
+                ),
+            ],
+        );
+
+        let env = ExecutionEnvironment::new_global();
+        let result = eval_compiled(Rc::clone(&env), |compiler| {
+            compiler.compile_block_value(&block)
+        });
+        assert_eq!(result.unwrap(), Value::new_integer(5));
+
+        let block = Block::new(
+            Token::new(TokenKind::LeftCurly, Position(0, 0)),
+            vec![Statement::new_expression(
                 Token::new(TokenKind::Integer(5), Position(0, 0)),
                 Box::new(Expression::new_integer(Token::new(
                     TokenKind::Integer(5),
                     Position(0, 0),
                 ))),
-            ))],
+            )],
         );
 
-        let result = block.eval(env);
-
-        assert!(result.is_ok());
+        let result = eval_compiled(env, |compiler| compiler.compile_block_value(&block));
         assert_eq!(result.unwrap(), Value::new_integer(5));
     }
 }
@@ -0,0 +1,58 @@
+use std::fmt::Display;
+
+use crate::{
+    eval::{Compile, Compiler},
+    lexer::{Token, TokenKind},
+};
+
+use super::Node;
+
+/// Break statement ast node.
+#[derive(Debug, PartialEq)]
+pub struct BreakStatement {
+    pub token: Token,
+}
+
+impl BreakStatement {
+    pub fn new(token: Token) -> Self {
+        assert_eq!(token.kind, TokenKind::Break, "expected break token");
+        BreakStatement { token }
+    }
+}
+
+impl Display for BreakStatement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.token.literal())
+    }
+}
+
+impl Node for BreakStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Compile for BreakStatement {
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.emit_break(self.token.span());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Position;
+
+    #[test]
+    fn test_break_statement() {
+        let token = Token::new(TokenKind::Break, Position(0, 0));
+        let stmt = BreakStatement::new(token.clone());
+
+        assert_eq!(stmt.token_literal(), token.literal());
+        assert_eq!(stmt.to_string(), "break");
+    }
+}
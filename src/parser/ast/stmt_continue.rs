@@ -0,0 +1,58 @@
+use std::fmt::Display;
+
+use crate::{
+    eval::{Compile, Compiler},
+    lexer::{Token, TokenKind},
+};
+
+use super::Node;
+
+/// Continue statement ast node.
+#[derive(Debug, PartialEq)]
+pub struct ContinueStatement {
+    pub token: Token,
+}
+
+impl ContinueStatement {
+    pub fn new(token: Token) -> Self {
+        assert_eq!(token.kind, TokenKind::Continue, "expected continue token");
+        ContinueStatement { token }
+    }
+}
+
+impl Display for ContinueStatement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.token.literal())
+    }
+}
+
+impl Node for ContinueStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Compile for ContinueStatement {
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.emit_continue(self.token.span());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Position;
+
+    #[test]
+    fn test_continue_statement() {
+        let token = Token::new(TokenKind::Continue, Position(0, 0));
+        let stmt = ContinueStatement::new(token.clone());
+
+        assert_eq!(stmt.token_literal(), token.literal());
+        assert_eq!(stmt.to_string(), "continue");
+    }
+}
@@ -1,8 +1,8 @@
-use std::{cell::RefCell, rc::Rc};
+use std::fmt::Display;
 
 use crate::{
-    eval::{Eval, EvalError, ExecutionEnvironment, Value},
-    lexer::Token,
+    eval::{Compile, Compiler, OpCode},
+    lexer::{Token, TokenKind},
 };
 
 use super::{Expression, Node};
@@ -18,11 +18,17 @@ impl ExpressionStatement {
     pub fn new(token: Token, expression: Box<Expression>) -> Self {
         Self { token, expression }
     }
+
+    /// Whether the statement gives its value to the program it ends.
+    /// An `if` or a `match` at the top level discards the values of its branches.
+    pub fn has_value(&self) -> bool {
+        !matches!(self.token.kind, TokenKind::If | TokenKind::Match)
+    }
 }
 
-impl ToString for ExpressionStatement {
-    fn to_string(&self) -> String {
-        self.expression.to_string()
+impl Display for ExpressionStatement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.expression)
     }
 }
 
@@ -36,9 +42,10 @@ impl Node for ExpressionStatement {
     }
 }
 
-impl Eval for ExpressionStatement {
-    fn eval(&self, env: Rc<RefCell<ExecutionEnvironment>>) -> Result<Rc<Value>, EvalError> {
-        self.expression.eval(env)
+impl Compile for ExpressionStatement {
+    fn compile(&self, compiler: &mut Compiler) {
+        self.expression.compile(compiler);
+        compiler.emit(OpCode::Pop, self.token.span());
     }
 }
 
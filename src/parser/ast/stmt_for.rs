@@ -0,0 +1,125 @@
+use std::{fmt::Display, rc::Rc};
+
+use crate::{
+    eval::{Compile, Compiler, OpCode},
+    lexer::{Token, TokenKind},
+};
+
+use super::{Block, Expression, Identifier, Node};
+
+/// For loop statement ast node.
+#[derive(Debug, PartialEq)]
+pub struct ForStatement {
+    pub token: Token,
+    /// Variable bound to the current element.
+    pub item: Identifier,
+    pub iterable: Box<Expression>,
+    pub body: Rc<Block>,
+}
+
+impl ForStatement {
+    pub fn new(token: Token, item: Identifier, iterable: Box<Expression>, body: Block) -> Self {
+        assert_eq!(token.kind, TokenKind::For, "expected for token");
+
+        ForStatement {
+            token,
+            item,
+            iterable,
+            body: Rc::new(body),
+        }
+    }
+}
+
+impl Display for ForStatement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} in {} {}",
+            self.token.literal(),
+            self.item,
+            self.iterable,
+            self.body
+        )
+    }
+}
+
+impl Node for ForStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// The iterable and the iteration cursor stay on the stack while the loop runs.
+impl Compile for ForStatement {
+    fn compile(&self, compiler: &mut Compiler) {
+        self.iterable.compile(compiler);
+        compiler.emit(OpCode::IterStart, self.token.span());
+
+        let start = compiler.begin_loop(self.token.span());
+        let to_end = compiler.emit(OpCode::IterNext(0), self.token.span());
+
+        compiler.compile_for_body(&self.item, &self.body);
+        compiler.emit(OpCode::Jump(start), self.token.span());
+
+        compiler.patch_jump(to_end);
+        compiler.end_loop(self.token.span());
+        compiler.emit(OpCode::Pop, self.token.span());
+        compiler.emit(OpCode::Pop, self.token.span());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{eval::ExecutionEnvironment, lexer::Position};
+
+    fn for_statement() -> ForStatement {
+        ForStatement::new(
+            Token::new(TokenKind::For, Position(0, 0)),
+            Identifier::new(Token::new(
+                TokenKind::Identifier("x".to_string()),
+                Position(0, 0),
+            )),
+            Box::new(Expression::new_identifier(Token::new(
+                TokenKind::Identifier("xs".to_string()),
+                Position(0, 0),
+            ))),
+            Block::new(Token::new(TokenKind::LeftCurly, Position(0, 0)), vec![]),
+        )
+    }
+
+    #[test]
+    fn test_for_statement() {
+        let stmt = for_statement();
+
+        assert_eq!(stmt.token_literal(), "for");
+        assert_eq!(stmt.to_string(), "for x in xs {\n}");
+    }
+
+    #[test]
+    fn test_for_statement_compile() {
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        for_statement().compile(&mut compiler);
+
+        assert_eq!(
+            compiler.chunk().code,
+            vec![
+                OpCode::Get(0),
+                OpCode::IterStart,
+                OpCode::EnterLoop,
+                OpCode::IterNext(8),
+                OpCode::PushScope(1),
+                OpCode::Define(0),
+                OpCode::PopScope,
+                OpCode::Jump(3),
+                OpCode::ExitLoop,
+                OpCode::Pop,
+                OpCode::Pop,
+            ]
+        );
+    }
+}
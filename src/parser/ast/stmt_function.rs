@@ -1,8 +1,8 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{fmt::Display, rc::Rc};
 
 use super::{Block, Identifier, Node};
 use crate::{
-    eval::{Environment, Eval, EvalError, ExecutionEnvironment, Value, VOID},
+    eval::{Compile, Compiler, OpCode},
     lexer::{Token, TokenKind},
 };
 
@@ -12,6 +12,8 @@ pub struct FunctionDeclaration {
     pub identifier: Identifier,
     pub parameters: Vec<Identifier>,
     pub body: Rc<Block>,
+    /// Text of the `///` comments before the declaration.
+    pub doc: Option<String>,
 }
 
 impl FunctionDeclaration {
@@ -27,29 +29,30 @@ impl FunctionDeclaration {
             identifier,
             parameters,
             body: Rc::new(body),
+            doc: None,
         }
     }
-}
 
-impl ToString for FunctionDeclaration {
-    fn to_string(&self) -> String {
-        let mut out = String::from(self.token_literal());
-        out.push(' ');
+    pub fn with_doc(mut self, doc: Option<String>) -> Self {
+        self.doc = doc;
+        self
+    }
+}
 
-        out.push_str(&self.identifier.to_string());
-        out.push('(');
-        out.push_str(
-            &self
-                .parameters
+impl Display for FunctionDeclaration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}({}) {}",
+            self.token_literal(),
+            self.identifier,
+            self.parameters
                 .iter()
                 .map(|ident| ident.to_string())
                 .collect::<Vec<String>>()
                 .join(", "),
-        );
-        out.push_str(") ");
-        out.push_str(&self.body.to_string());
-
-        out
+            self.body
+        )
     }
 }
 
@@ -63,24 +66,26 @@ impl Node for FunctionDeclaration {
     }
 }
 
-impl Eval for FunctionDeclaration {
-    fn eval(&self, env: Rc<RefCell<ExecutionEnvironment>>) -> Result<Rc<Value>, EvalError> {
-        let params = self
-            .parameters
-            .iter()
-            .map(|ident| ident.to_string())
-            .collect::<Vec<String>>();
-
-        let func = Value::new_function(params, Rc::clone(&self.body), Rc::clone(&env));
-        env.borrow_mut().set(self.identifier.to_string(), func);
+impl Compile for FunctionDeclaration {
+    fn compile(&self, compiler: &mut Compiler) {
+        let name = self.identifier.to_string();
 
-        Ok(VOID.rc())
+        compiler.mark_defined(&name);
+        let idx = compiler.compile_function(
+            Some(name.clone()),
+            &self.parameters,
+            &self.body,
+            self.token.span(),
+        );
+        compiler.emit(OpCode::Closure(idx), self.token.span());
+        compiler.emit_define(&name, self.token.span());
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
+        eval::{eval_statement, Environment, ExecutionEnvironment, Value},
         lexer::Position,
         parser::{Expression, Statement},
     };
@@ -92,7 +97,7 @@ mod tests {
         let block = Block::new(
             Token::new(TokenKind::LeftCurly, Position(0, 0)),
             vec![
-                Box::new(Statement::new_let(
+                Statement::new_let(
                     Token::new(TokenKind::Let, Position(0, 0)),
                     Identifier::new(Token::new(
                         TokenKind::Identifier("x".to_string()),
@@ -102,14 +107,15 @@ mod tests {
                         TokenKind::Integer(5),
                         Position(0, 0),
                     ))),
-                )),
-                Box::new(Statement::new_return(
+                    None,
+                ),
+                Statement::new_return(
                     Token::new(TokenKind::Return, Position(0, 0)),
                     Box::new(Expression::new_identifier(Token::new(
                         TokenKind::Identifier("x".to_string()),
                         Position(0, 0),
                     ))),
-                )),
+                ),
             ],
         );
         let params = vec![
@@ -145,7 +151,7 @@ mod tests {
     }
 
     #[test]
-    fn test_fn_declaration_eval() {
+    fn test_fn_declaration_compile() {
         let block = Block::new(Token::new(TokenKind::LeftCurly, Position(0, 0)), vec![]);
         let params = vec![];
         let function = FunctionDeclaration::new(
@@ -159,12 +165,36 @@ mod tests {
         );
 
         let env = ExecutionEnvironment::new_global();
-        let result = function.eval(Rc::clone(&env));
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), VOID.rc());
+        let slot = env.borrow_mut().intern(&function.identifier.to_string());
+
+        let mut compiler = Compiler::new(env);
+        function.compile(&mut compiler);
+
+        assert_eq!(
+            compiler.chunk().code,
+            vec![OpCode::Closure(0), OpCode::DefineGlobal(slot)]
+        );
+        assert!(compiler.chunk().functions[0].params.is_empty());
+    }
+
+    #[test]
+    fn test_fn_declaration_eval() {
+        let block = Block::new(Token::new(TokenKind::LeftCurly, Position(0, 0)), vec![]);
+        let function = FunctionDeclaration::new(
+            Token::new(TokenKind::Function, Position(0, 0)),
+            Identifier::new(Token::new(
+                TokenKind::Identifier("add".to_string()),
+                Position(0, 0),
+            )),
+            vec![],
+            block,
+        );
+
+        let env = ExecutionEnvironment::new_global();
+        let result = eval_statement(&function, Rc::clone(&env));
+        assert!(result.unwrap().is_void());
 
         let stored = env.borrow().get(&function.identifier.to_string());
-        assert!(stored.is_some());
-        assert!(matches!(&*stored.unwrap(), Value::Function(_)))
+        assert!(matches!(stored.as_deref(), Some(Value::Function(_))));
     }
 }
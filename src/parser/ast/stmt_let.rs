@@ -1,7 +1,7 @@
-use std::{cell::RefCell, rc::Rc};
+use std::fmt::Display;
 
 use crate::{
-    eval::{Environment, Eval, EvalError, ExecutionEnvironment, Value, VOID},
+    eval::{Compile, Compiler},
     lexer::{Token, TokenKind},
 };
 
@@ -13,6 +13,8 @@ pub struct LetStatement {
     pub token: Token,
     pub identifier: Identifier,
     pub value: Box<Expression>,
+    /// Text of the `///` comments before the statement.
+    pub doc: Option<String>,
 }
 
 impl LetStatement {
@@ -22,20 +24,25 @@ impl LetStatement {
             token,
             identifier,
             value,
+            doc: None,
         }
     }
-}
-
-impl ToString for LetStatement {
-    fn to_string(&self) -> String {
-        let mut out = self.token.literal();
-        out.push_str(" ");
 
-        out.push_str(&self.identifier.to_string());
-        out.push_str(" = ");
-        out.push_str(&self.value.to_string());
+    pub fn with_doc(mut self, doc: Option<String>) -> Self {
+        self.doc = doc;
+        self
+    }
+}
 
-        out
+impl Display for LetStatement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} = {}",
+            self.token.literal(),
+            self.identifier,
+            self.value
+        )
     }
 }
 
@@ -49,26 +56,22 @@ impl Node for LetStatement {
     }
 }
 
-impl Eval for LetStatement {
-    fn eval(&self, env: Rc<RefCell<ExecutionEnvironment>>) -> Result<Rc<Value>, EvalError> {
-        let to_store = self.value.eval(Rc::clone(&env))?;
-
-        if to_store == VOID.rc() {
-            return Err(EvalError::TriedToStoreVoid(self.token.position));
-        }
-
-        let name = self.identifier.token_literal();
-
-        env.borrow_mut().set(name, to_store);
-
-        Ok(VOID.rc())
+impl Compile for LetStatement {
+    fn compile(&self, compiler: &mut Compiler) {
+        self.value.compile(compiler);
+        compiler.emit_define(&self.identifier.token_literal(), self.token.span());
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::rc::Rc;
+
     use super::*;
-    use crate::{eval::Environment, lexer::Position};
+    use crate::{
+        eval::{eval_statement, Environment, ExecutionEnvironment, OpCode, Value},
+        lexer::Position,
+    };
 
     #[test]
     fn test_let_statement() {
@@ -86,7 +89,7 @@ mod tests {
     }
 
     #[test]
-    fn test_let_eval() {
+    fn test_let_compile() {
         let token = Token::new(TokenKind::Let, Position(0, 0));
         let ident = Identifier::new(Token::new(
             TokenKind::Identifier("who_cares".to_string()),
@@ -97,11 +100,31 @@ mod tests {
         let stmt = LetStatement::new(token, ident, Box::new(int));
 
         let env = ExecutionEnvironment::new_global();
-        let result = stmt.eval(Rc::clone(&env));
-        let stored = env.borrow().get("who_cares");
+        let slot = env.borrow_mut().intern("who_cares");
+
+        let mut compiler = Compiler::new(env);
+        stmt.compile(&mut compiler);
 
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), VOID.rc());
-        assert_eq!(stored, Some(Value::new_integer(69)))
+        assert_eq!(
+            compiler.chunk().code,
+            vec![OpCode::Constant(0), OpCode::DefineGlobal(slot)]
+        );
+    }
+
+    #[test]
+    fn test_let_eval() {
+        let token = Token::new(TokenKind::Let, Position(0, 0));
+        let ident = Identifier::new(Token::new(
+            TokenKind::Identifier("who_cares".to_string()),
+            Position(0, 0),
+        ));
+        let int = Expression::new_integer(Token::new(TokenKind::Integer(69), Position(0, 0)));
+
+        let stmt = LetStatement::new(token, ident, Box::new(int));
+
+        let env = ExecutionEnvironment::new_global();
+        let result = eval_statement(&stmt, Rc::clone(&env));
+        assert!(result.unwrap().is_void());
+        assert_eq!(env.borrow().get("who_cares"), Some(Value::new_integer(69)));
     }
 }
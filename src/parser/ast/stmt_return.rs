@@ -1,13 +1,13 @@
-use std::{cell::RefCell, rc::Rc};
+use std::fmt::Display;
 
 use crate::{
-    eval::{Eval, EvalError, ExecutionEnvironment, Value},
+    eval::{Compile, Compiler, OpCode},
     lexer::{Token, TokenKind},
 };
 
 use super::{Expression, Node};
 
-/// Let statement ast node.
+/// Return statement ast node.
 #[derive(Debug, PartialEq)]
 pub struct ReturnStatement {
     pub token: Token,
@@ -21,12 +21,9 @@ impl ReturnStatement {
     }
 }
 
-impl ToString for ReturnStatement {
-    fn to_string(&self) -> String {
-        let mut out = self.token.literal();
-        out.push_str(" ");
-        out.push_str(&self.value.to_string());
-        out
+impl Display for ReturnStatement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.token.literal(), self.value)
     }
 }
 
@@ -40,17 +37,23 @@ impl Node for ReturnStatement {
     }
 }
 
-impl Eval for ReturnStatement {
-    fn eval(&self, env: Rc<RefCell<ExecutionEnvironment>>) -> Result<Rc<Value>, EvalError> {
-        let value = self.value.eval(env)?;
-        Ok(Value::new_returned(value))
+impl Compile for ReturnStatement {
+    fn compile(&self, compiler: &mut Compiler) {
+        match &*self.value {
+            Expression::FunctionCall(call) => call.compile_tail(compiler),
+            value => value.compile(compiler),
+        }
+        compiler.emit(OpCode::Return, self.token.span());
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{eval::TRUE, lexer::Position};
+    use crate::{
+        eval::{eval_statement, ExecutionEnvironment, TRUE},
+        lexer::Position,
+    };
 
     #[test]
     fn test_return_statement() {
@@ -64,15 +67,49 @@ mod tests {
     }
 
     #[test]
-    fn test_return_eval() {
+    fn test_return_compile() {
         let token = Token::new(TokenKind::Return, Position(0, 0));
         let bool = Expression::new_boolean(Token::new(TokenKind::True, Position(0, 0)));
 
         let stmt = ReturnStatement::new(token.clone(), Box::new(bool));
 
-        let env = ExecutionEnvironment::new_global();
-        let result = stmt.eval(env);
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), Value::new_returned(TRUE.rc()));
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        stmt.compile(&mut compiler);
+
+        assert_eq!(compiler.chunk().code, vec![OpCode::True, OpCode::Return]);
+    }
+
+    #[test]
+    fn test_return_tail_call_compile() {
+        let token = Token::new(TokenKind::Return, Position(0, 0));
+        let call = Expression::new_function_call(
+            Token::new(TokenKind::LeftParen, Position(0, 0)),
+            Box::new(Expression::new_boolean(Token::new(
+                TokenKind::True,
+                Position(0, 0),
+            ))),
+            vec![],
+        );
+
+        let stmt = ReturnStatement::new(token, Box::new(call));
+
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        stmt.compile(&mut compiler);
+
+        assert_eq!(
+            compiler.chunk().code,
+            vec![OpCode::True, OpCode::TailCall(0), OpCode::Return]
+        );
+    }
+
+    #[test]
+    fn test_return_eval() {
+        let token = Token::new(TokenKind::Return, Position(0, 0));
+        let bool = Expression::new_boolean(Token::new(TokenKind::True, Position(0, 0)));
+
+        let stmt = ReturnStatement::new(token, Box::new(bool));
+
+        let result = eval_statement(&stmt, ExecutionEnvironment::new_global());
+        assert_eq!(result.unwrap(), TRUE.rc());
     }
 }
@@ -0,0 +1,115 @@
+use std::{fmt::Display, rc::Rc};
+
+use crate::{
+    eval::{Compile, Compiler, OpCode},
+    lexer::{Token, TokenKind},
+};
+
+use super::{Block, Expression, Node};
+
+/// While loop statement ast node.
+#[derive(Debug, PartialEq)]
+pub struct WhileStatement {
+    pub token: Token,
+    pub condition: Box<Expression>,
+    pub body: Rc<Block>,
+}
+
+impl WhileStatement {
+    pub fn new(token: Token, condition: Box<Expression>, body: Block) -> Self {
+        assert_eq!(token.kind, TokenKind::While, "expected while token");
+
+        WhileStatement {
+            token,
+            condition,
+            body: Rc::new(body),
+        }
+    }
+}
+
+impl Display for WhileStatement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}) {}",
+            self.token.literal(),
+            self.condition,
+            self.body
+        )
+    }
+}
+
+impl Node for WhileStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// Every iteration runs the body in a fresh scope.
+impl Compile for WhileStatement {
+    fn compile(&self, compiler: &mut Compiler) {
+        let start = compiler.begin_loop(self.token.span());
+
+        self.condition.compile(compiler);
+        let to_end = compiler.emit(OpCode::JumpIfFalse(0), self.token.span());
+
+        compiler.compile_scoped_block(&self.body);
+        compiler.emit(OpCode::Jump(start), self.token.span());
+
+        compiler.patch_jump(to_end);
+        compiler.end_loop(self.token.span());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{eval::ExecutionEnvironment, lexer::Position, parser::Statement};
+
+    #[test]
+    fn test_while_statement() {
+        let token = Token::new(TokenKind::While, Position(0, 0));
+        let cond = Expression::new_boolean(Token::new(TokenKind::True, Position(0, 0)));
+        let body = Block::new(Token::new(TokenKind::LeftCurly, Position(0, 0)), vec![]);
+
+        let stmt = WhileStatement::new(token.clone(), Box::new(cond), body);
+
+        assert_eq!(stmt.token_literal(), token.literal());
+        assert_eq!(stmt.to_string(), "while (true) {\n}");
+    }
+
+    #[test]
+    fn test_while_statement_compile() {
+        let token = Token::new(TokenKind::While, Position(0, 0));
+        let cond = Expression::new_boolean(Token::new(TokenKind::True, Position(0, 0)));
+        let body = Block::new(
+            Token::new(TokenKind::LeftCurly, Position(0, 0)),
+            vec![Statement::new_break(Token::new(
+                TokenKind::Break,
+                Position(0, 0),
+            ))],
+        );
+
+        let stmt = WhileStatement::new(token, Box::new(cond), body);
+
+        let mut compiler = Compiler::new(ExecutionEnvironment::new_global());
+        stmt.compile(&mut compiler);
+
+        assert_eq!(
+            compiler.chunk().code,
+            vec![
+                OpCode::EnterLoop,
+                OpCode::True,
+                OpCode::JumpIfFalse(6),
+                OpCode::Unwind,
+                OpCode::Jump(6),
+                OpCode::Jump(1),
+                OpCode::ExitLoop,
+            ]
+        );
+    }
+}
@@ -1,4 +1,8 @@
 mod ast;
+#[allow(
+    clippy::module_inception,
+    reason = "private module, its items are re-exported as crate::parser::*"
+)]
 mod parser;
 mod precedence;
 
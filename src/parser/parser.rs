@@ -1,8 +1,16 @@
+use std::mem;
+
 use thiserror::Error;
 
-use crate::lexer::{Lexer, Token, TokenKind};
+use crate::{
+    eval::{InfixOp, MapKey},
+    lexer::{Lexer, Position, TemplatePart, Token, TokenKind},
+};
 
-use super::{precedence::Precedence, Expression, Identifier, Program, Statement};
+use super::{
+    precedence::Precedence, ArmBody, AssignStatement, Block, Conditional, Else, Expression,
+    Identifier, Match, MatchArm, Pattern, PatternLiteral, Program, Statement,
+};
 
 #[derive(Debug, PartialEq, Error)]
 pub enum ParserError {
@@ -14,6 +22,44 @@ pub enum ParserError {
     UnexpectedEOFWithExpectation(TokenKind),
     #[error("Unexpected end of input")]
     UnexpectedEOF,
+    #[error("Outside of a loop: {0}")]
+    OutsideOfLoop(Token),
+    #[error("Invalid assignment target: {0}")]
+    InvalidAssignmentTarget(Token),
+    #[error("Malformed number literal: {0}")]
+    MalformedNumber(Token),
+    #[error("Invalid escape sequence: {0}")]
+    InvalidEscape(Token),
+    #[error("Unterminated string: {0}")]
+    UnterminatedString(Token),
+    #[error("Incomplete interpolation: {0}")]
+    IncompleteInterpolation(Token),
+    #[error("Unterminated block comment: {0}")]
+    UnterminatedComment(Token),
+    #[error("Name bound twice in a pattern: {0}")]
+    DuplicateBinding(Token),
+    #[error("Name not bound by every alternative: {0}")]
+    AlternativeBinding(Token),
+}
+
+impl ParserError {
+    /// Token the error points at, `None` for errors at the end of input.
+    pub fn token(&self) -> Option<&Token> {
+        match self {
+            ParserError::InvalidToken(token)
+            | ParserError::UnexpectedToken { actual: token, .. }
+            | ParserError::OutsideOfLoop(token)
+            | ParserError::InvalidAssignmentTarget(token)
+            | ParserError::MalformedNumber(token)
+            | ParserError::InvalidEscape(token)
+            | ParserError::UnterminatedString(token)
+            | ParserError::IncompleteInterpolation(token)
+            | ParserError::UnterminatedComment(token)
+            | ParserError::DuplicateBinding(token)
+            | ParserError::AlternativeBinding(token) => Some(token),
+            ParserError::UnexpectedEOFWithExpectation(_) | ParserError::UnexpectedEOF => None,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -21,6 +67,16 @@ pub struct Parser {
     lexer: Lexer,
     current_token: Option<Token>,
     peek_token: Option<Token>,
+    /// Number of loops around the current token within the current function.
+    loop_depth: usize,
+    /// Errors that don't stop parsing of the statement they're found in.
+    errors: Vec<ParserError>,
+    /// Doc comments right before the current token.
+    current_doc: Option<String>,
+    /// Doc comments right before the peek token.
+    peek_doc: Option<String>,
+    /// Number of `{` minus number of `}` up to and including the current token.
+    brace_depth: isize,
 }
 
 impl Parser {
@@ -30,6 +86,11 @@ impl Parser {
             lexer,
             current_token: None,
             peek_token: None,
+            loop_depth: 0,
+            errors: vec![],
+            current_doc: None,
+            peek_doc: None,
+            brace_depth: 0,
         };
         parser.next_token();
         parser.next_token();
@@ -41,19 +102,31 @@ impl Parser {
     pub fn parse(&mut self) -> Result<Program, Vec<ParserError>> {
         let mut program = Program::new();
 
-        let mut errors = vec![];
-
-        while self.current_token.is_some() {
-            let stmt = match self.parse_statement() {
-                Ok(stmt) => stmt,
-                Err(err) => {
-                    errors.push(err);
+        while let Some(start) = self.current_token.as_ref().map(|token| token.position) {
+            let depth = self.depth_before_current();
+            match self.parse_statement() {
+                Ok(stmt) => {
+                    program.push_statement(*stmt);
                     self.next_token();
-                    continue;
                 }
-            };
-            program.push_statement(stmt);
-            self.next_token();
+                Err(err) => {
+                    self.errors.push(err);
+                    self.loop_depth = 0;
+                    self.synchronize(start, depth);
+                }
+            }
+        }
+
+        // recovery can run into the same bad token more than once
+        let mut errors: Vec<ParserError> = vec![];
+        for err in self.errors.drain(..) {
+            let position = err.token().map(|token| token.position);
+            if !errors.iter().any(|reported| {
+                reported.token().map(|token| token.position) == position
+                    && mem::discriminant(reported) == mem::discriminant(&err)
+            }) {
+                errors.push(err);
+            }
         }
 
         if !errors.is_empty() {
@@ -63,6 +136,78 @@ impl Parser {
         Ok(program)
     }
 
+    /// Skips the rest of a statement that failed to parse.
+    ///
+    /// Stops past the next `;`, or at a `}` of an enclosing block or a keyword that starts a statement.
+    /// Blocks are skipped whole, they belong to the failed statement.
+    ///
+    /// `statement_depth` is the brace depth before the first token of the failed statement.
+    fn synchronize(&mut self, statement_start: Position, statement_depth: isize) {
+        // the statement failed on its first token, it can't start one
+        if self.current_token.as_ref().map(|token| token.position) == Some(statement_start) {
+            self.next_token();
+        }
+
+        loop {
+            match self.current_token.as_ref().map(|token| &token.kind) {
+                None if self.peek_token.is_none() => return,
+                Some(TokenKind::SemiColon) => {
+                    self.next_token();
+                    return;
+                }
+                // closes a brace opened by the failed statement
+                Some(TokenKind::RightCurly) if self.brace_depth >= statement_depth => {
+                    self.next_token()
+                }
+                Some(
+                    TokenKind::RightCurly
+                    | TokenKind::Let
+                    | TokenKind::Function
+                    | TokenKind::If
+                    | TokenKind::Match
+                    | TokenKind::Return
+                    | TokenKind::While
+                    | TokenKind::For,
+                ) => return,
+                Some(TokenKind::LeftCurly) => {
+                    self.skip_braces();
+                    if self.current_token.as_ref().map(|token| &token.kind)
+                        != Some(&TokenKind::Else)
+                    {
+                        return;
+                    }
+                }
+                // `None` is a token taken by the failed parse
+                None | Some(_) => self.next_token(),
+            }
+        }
+    }
+
+    /// Brace depth before the current token.
+    fn depth_before_current(&self) -> isize {
+        match self.current_token.as_ref().map(|token| &token.kind) {
+            Some(TokenKind::LeftCurly) => self.brace_depth - 1,
+            Some(TokenKind::RightCurly) => self.brace_depth + 1,
+            _ => self.brace_depth,
+        }
+    }
+
+    /// Skips from the current `{` past the matching `}`.
+    fn skip_braces(&mut self) {
+        let mut depth = 0;
+        while let Some(token) = &self.current_token {
+            match token.kind {
+                TokenKind::LeftCurly => depth += 1,
+                TokenKind::RightCurly => depth -= 1,
+                _ => {}
+            }
+            self.next_token();
+            if depth == 0 {
+                return;
+            }
+        }
+    }
+
     /// Parses a statement starting from the current token.
     /// Calling this function takes the current token.
     fn parse_statement(&mut self) -> Result<Box<Statement>, ParserError> {
@@ -71,6 +216,12 @@ impl Parser {
                 TokenKind::Let => Ok(Box::new(self.parse_let_statement()?)),
                 TokenKind::Return => Ok(Box::new(self.parse_return_statement()?)),
                 TokenKind::If => Ok(Box::new(self.parse_condition_statement()?)),
+                TokenKind::Match => Ok(Box::new(self.parse_match_statement()?)),
+                TokenKind::While => Ok(Box::new(self.parse_while_statement()?)),
+                TokenKind::For => Ok(Box::new(self.parse_for_statement()?)),
+                TokenKind::Break | TokenKind::Continue => {
+                    Ok(Box::new(self.parse_loop_control_statement()?))
+                }
 
                 TokenKind::Function
                     if self.peek_token.is_some()
@@ -82,14 +233,6 @@ impl Parser {
                     Ok(Box::new(self.parse_function_declaration_statement()?))
                 }
 
-                TokenKind::Identifier(_)
-                    if self.peek_token.is_some()
-                        && self.peek_token.as_ref().expect("checked before").kind
-                            == TokenKind::Equals =>
-                {
-                    Ok(Box::new(self.parse_assign_statement()?))
-                }
-
                 TokenKind::ILLEGAL(_) => Err(ParserError::InvalidToken(token.clone())),
                 _ => Ok(Box::new(self.parse_expression_statement()?)),
             }
@@ -98,17 +241,22 @@ impl Parser {
         }
     }
 
-    fn parse_assign_statement(&mut self) -> Result<Statement, ParserError> {
-        let identifier = match self.parse_identifier() {
-            Ok(identifier) => match identifier {
-                Expression::Identifier(identifier) => identifier,
-                _ => unreachable!(),
-            },
-            Err(err) => return Err(err),
-        };
+    /// Parses an assignment to an already parsed target.
+    /// The current token is the last token of the target.
+    fn parse_assign_statement(
+        &mut self,
+        target_token: Token,
+        target: Box<Expression>,
+    ) -> Result<Statement, ParserError> {
+        if !AssignStatement::is_valid_target(&target) {
+            return Err(ParserError::InvalidAssignmentTarget(target_token));
+        }
         self.next_token();
 
-        self.expect_token_kind(&self.current_token, TokenKind::Equals)?;
+        self.expect_token_fn(&self.current_token, |t| {
+            t.kind == TokenKind::Equals || InfixOp::from_assign_token_kind(&t.kind).is_some()
+        })?;
+        let assign_token = self.current_token.take().expect("checked before");
         self.next_token();
 
         let value = self.parse_expression(Precedence::Lowest)?;
@@ -122,15 +270,26 @@ impl Parser {
             self.next_token();
         }
 
-        Ok(Statement::new_assign(identifier, value))
+        Ok(Statement::new_assign(assign_token, target, value))
     }
 
+    /// Parses an `if` in statement position.
+    /// Unlike other expression statements it ends with its last block.
     fn parse_condition_statement(&mut self) -> Result<Statement, ParserError> {
+        let conditional = self.parse_conditional()?;
+        Ok(Statement::new_expression(
+            conditional.token.clone(),
+            Box::new(Expression::Conditional(conditional)),
+        ))
+    }
+
+    /// Parses `if cond { ... }`, optionally followed by `else if` branches and a final `else`.
+    fn parse_conditional(&mut self) -> Result<Conditional, ParserError> {
         self.expect_token_kind(&self.current_token, TokenKind::If)?;
         let if_token = self.current_token.take().expect("checked before");
         self.next_token();
 
-        let condition = self.parse_grouped_expression()?;
+        let condition = self.parse_expression(Precedence::Lowest)?;
         self.next_token();
 
         let then_block = match self.parse_block_statement()? {
@@ -138,29 +297,366 @@ impl Parser {
             _ => unreachable!(),
         };
 
-        // self.next_token();
-
-        let else_block = if self.peek_token.is_some()
-            && self.peek_token.as_ref().expect("checked before").kind == TokenKind::Else
+        let else_branch = if self
+            .peek_token
+            .as_ref()
+            .is_some_and(|t| t.kind == TokenKind::Else)
         {
             self.next_token();
             self.next_token();
-            Some(match self.parse_block_statement()? {
-                Statement::Block(block) => block,
-                _ => unreachable!(),
-            })
+            if self
+                .current_token
+                .as_ref()
+                .is_some_and(|t| t.kind == TokenKind::If)
+            {
+                Some(Else::If(Box::new(self.parse_conditional()?)))
+            } else {
+                Some(match self.parse_block_statement()? {
+                    Statement::Block(block) => Else::Block(block),
+                    _ => unreachable!(),
+                })
+            }
         } else {
             None
         };
 
-        Ok(Statement::new_condition(
-            if_token, condition, then_block, else_block,
+        Ok(Conditional::new(
+            if_token,
+            condition,
+            then_block,
+            else_branch,
         ))
     }
 
+    /// Parses a `match` in statement position.
+    /// Unlike other expression statements it ends with its closing `}`.
+    fn parse_match_statement(&mut self) -> Result<Statement, ParserError> {
+        let expression = self.parse_match()?;
+        let token = expression.token.clone();
+
+        if self
+            .peek_token
+            .as_ref()
+            .is_some_and(|t| t.kind == TokenKind::SemiColon)
+        {
+            self.next_token();
+        }
+
+        Ok(Statement::new_expression(
+            token,
+            Box::new(Expression::Match(expression)),
+        ))
+    }
+
+    /// Parses `match value { pattern if guard => body, ... }`.
+    ///
+    /// A body starting with `{` is a block, a map literal body has to be put in parentheses.
+    /// The comma after a block body is optional.
+    fn parse_match(&mut self) -> Result<Match, ParserError> {
+        self.expect_token_kind(&self.current_token, TokenKind::Match)?;
+        let match_token = self.current_token.take().expect("checked before");
+        self.next_token();
+
+        let value = self.parse_expression(Precedence::Lowest)?;
+        self.expect_token_kind(&self.peek_token, TokenKind::LeftCurly)?;
+        self.next_token();
+
+        let mut arms = vec![];
+        while self
+            .peek_token
+            .as_ref()
+            .is_some_and(|t| t.kind != TokenKind::RightCurly)
+        {
+            self.next_token();
+            let pattern = self.parse_pattern()?;
+            Self::pattern_bindings(&pattern)?;
+
+            let guard = if self
+                .peek_token
+                .as_ref()
+                .is_some_and(|t| t.kind == TokenKind::If)
+            {
+                self.next_token();
+                self.next_token();
+                Some(self.parse_expression(Precedence::Lowest)?)
+            } else {
+                None
+            };
+
+            self.expect_token_kind(&self.peek_token, TokenKind::FatArrow)?;
+            self.next_token();
+            self.next_token();
+
+            let body = match self.current_token.as_ref().map(|t| &t.kind) {
+                Some(TokenKind::LeftCurly) => match self.parse_block_statement()? {
+                    Statement::Block(block) => ArmBody::Block(block),
+                    _ => unreachable!(),
+                },
+                _ => ArmBody::Expression(self.parse_expression(Precedence::Lowest)?),
+            };
+            let block_body = matches!(body, ArmBody::Block(_));
+            arms.push(MatchArm::new(pattern, guard, body));
+
+            if self
+                .peek_token
+                .as_ref()
+                .is_some_and(|t| t.kind == TokenKind::Comma)
+            {
+                self.next_token();
+            } else if !block_body {
+                break;
+            }
+        }
+
+        self.expect_token_kind(&self.peek_token, TokenKind::RightCurly)?;
+        self.next_token();
+
+        Ok(Match::new(match_token, value, arms))
+    }
+
+    /// Parses a pattern of a `match` arm, with its `|` alternatives.
+    fn parse_pattern(&mut self) -> Result<Pattern, ParserError> {
+        let mut alternatives = vec![self.parse_single_pattern()?];
+        while self
+            .peek_token
+            .as_ref()
+            .is_some_and(|t| t.kind == TokenKind::Pipe)
+        {
+            self.next_token();
+            self.next_token();
+            alternatives.push(self.parse_single_pattern()?);
+        }
+
+        Ok(match alternatives.len() {
+            1 => alternatives.pop().expect("checked before"),
+            _ => Pattern::Or(alternatives),
+        })
+    }
+
+    /// Collects the names bound by the pattern. A name bound twice, or bound by
+    /// some of the alternatives of an `|` but not by all of them, is an error.
+    fn pattern_bindings(pattern: &Pattern) -> Result<Vec<&Identifier>, ParserError> {
+        match pattern {
+            Pattern::Wildcard(_) | Pattern::Literal(_) => Ok(vec![]),
+            Pattern::Binding(ident) => Ok(vec![ident]),
+            Pattern::Array { elements, .. } => Self::join_bindings(elements),
+            Pattern::Map { entries, .. } => {
+                Self::join_bindings(entries.iter().map(|(_, pattern)| pattern))
+            }
+            Pattern::Or(alternatives) => {
+                let first = Self::pattern_bindings(&alternatives[0])?;
+                for alternative in &alternatives[1..] {
+                    let other = Self::pattern_bindings(alternative)?;
+                    let missing = |from: &[&Identifier], to: &[&Identifier]| {
+                        from.iter()
+                            .find(|ident| {
+                                !to.iter().any(|other| other.token.kind == ident.token.kind)
+                            })
+                            .map(|ident| ParserError::AlternativeBinding(ident.token.clone()))
+                    };
+                    if let Some(err) = missing(&other, &first).or_else(|| missing(&first, &other)) {
+                        return Err(err);
+                    }
+                }
+                Ok(first)
+            }
+        }
+    }
+
+    fn join_bindings<'a>(
+        patterns: impl IntoIterator<Item = &'a Pattern>,
+    ) -> Result<Vec<&'a Identifier>, ParserError> {
+        let mut names: Vec<&Identifier> = vec![];
+        for pattern in patterns {
+            for ident in Self::pattern_bindings(pattern)? {
+                if names.iter().any(|name| name.token.kind == ident.token.kind) {
+                    return Err(ParserError::DuplicateBinding(ident.token.clone()));
+                }
+                names.push(ident);
+            }
+        }
+        Ok(names)
+    }
+
+    /// Parses a pattern without alternatives.
+    fn parse_single_pattern(&mut self) -> Result<Pattern, ParserError> {
+        match self.current_token.as_ref().map(|t| &t.kind) {
+            Some(TokenKind::Identifier(name)) if name == "_" => Ok(Pattern::Wildcard(
+                self.current_token.take().expect("checked before"),
+            )),
+            Some(TokenKind::Identifier(_)) => Ok(Pattern::Binding(Identifier::new(
+                self.current_token.take().expect("checked before"),
+            ))),
+            Some(TokenKind::LeftBracket) => {
+                let token = self.current_token.take().expect("checked before");
+                let mut elements = vec![];
+                while self
+                    .peek_token
+                    .as_ref()
+                    .is_some_and(|t| t.kind != TokenKind::RightBracket)
+                {
+                    self.next_token();
+                    elements.push(self.parse_pattern()?);
+                    if self
+                        .peek_token
+                        .as_ref()
+                        .is_some_and(|t| t.kind == TokenKind::Comma)
+                    {
+                        self.next_token();
+                    } else {
+                        break;
+                    }
+                }
+                self.expect_token_kind(&self.peek_token, TokenKind::RightBracket)?;
+                self.next_token();
+
+                Ok(Pattern::Array { token, elements })
+            }
+            Some(TokenKind::LeftCurly) => {
+                let token = self.current_token.take().expect("checked before");
+                let mut entries = vec![];
+                while self
+                    .peek_token
+                    .as_ref()
+                    .is_some_and(|t| t.kind != TokenKind::RightCurly)
+                {
+                    self.next_token();
+                    let key = self.parse_pattern_literal()?;
+                    if MapKey::from_value(&key.value()).is_none() {
+                        return Err(ParserError::InvalidToken(key.token));
+                    }
+                    self.expect_token_kind(&self.peek_token, TokenKind::Colon)?;
+                    self.next_token();
+                    self.next_token();
+                    entries.push((key, self.parse_pattern()?));
+                    if self
+                        .peek_token
+                        .as_ref()
+                        .is_some_and(|t| t.kind == TokenKind::Comma)
+                    {
+                        self.next_token();
+                    } else {
+                        break;
+                    }
+                }
+                self.expect_token_kind(&self.peek_token, TokenKind::RightCurly)?;
+                self.next_token();
+
+                Ok(Pattern::Map { token, entries })
+            }
+            _ => Ok(Pattern::Literal(self.parse_pattern_literal()?)),
+        }
+    }
+
+    /// Parses a literal of a pattern, a number may be preceded by `-`.
+    fn parse_pattern_literal(&mut self) -> Result<PatternLiteral, ParserError> {
+        let sign = match self.current_token.as_ref().map(|t| &t.kind) {
+            Some(TokenKind::Minus) => {
+                let sign = self.current_token.take();
+                self.next_token();
+                sign
+            }
+            _ => None,
+        };
+
+        self.expect_token_fn(&self.current_token, |t| match t.kind {
+            TokenKind::Integer(_) | TokenKind::BigInt(_) | TokenKind::Float(_) => true,
+            TokenKind::String(_) | TokenKind::True | TokenKind::False => sign.is_none(),
+            _ => false,
+        })?;
+        let token = self.current_token.take().expect("checked before");
+
+        Ok(PatternLiteral::new(sign, token))
+    }
+
+    /// Parses a while loop statement.
+    fn parse_while_statement(&mut self) -> Result<Statement, ParserError> {
+        self.expect_token_kind(&self.current_token, TokenKind::While)?;
+        let while_token = self.current_token.take().expect("checked before");
+        self.next_token();
+
+        let condition = self.parse_expression(Precedence::Lowest)?;
+        self.next_token();
+
+        self.loop_depth += 1;
+        let body = match self.parse_block_statement()? {
+            Statement::Block(block) => block,
+            _ => unreachable!(),
+        };
+        self.loop_depth -= 1;
+
+        Ok(Statement::new_while(while_token, condition, body))
+    }
+
+    /// Parses a for loop statement.
+    fn parse_for_statement(&mut self) -> Result<Statement, ParserError> {
+        self.expect_token_kind(&self.current_token, TokenKind::For)?;
+        let for_token = self.current_token.take().expect("checked before");
+        self.next_token();
+
+        let item = match self.parse_identifier()? {
+            Expression::Identifier(identifier) => identifier,
+            _ => unreachable!(),
+        };
+        self.expect_token_kind(&self.peek_token, TokenKind::In)?;
+        self.next_token();
+        self.next_token();
+
+        let iterable = self.parse_expression(Precedence::Lowest)?;
+        self.next_token();
+
+        self.loop_depth += 1;
+        let body = match self.parse_block_statement()? {
+            Statement::Block(block) => block,
+            _ => unreachable!(),
+        };
+        self.loop_depth -= 1;
+
+        Ok(Statement::new_for(for_token, item, iterable, body))
+    }
+
+    /// Parses a `break` or `continue` statement.
+    fn parse_loop_control_statement(&mut self) -> Result<Statement, ParserError> {
+        self.expect_token_fn(&self.current_token, |t| {
+            matches!(t.kind, TokenKind::Break | TokenKind::Continue)
+        })?;
+        let token = self.current_token.take().expect("checked before");
+
+        if self
+            .peek_token
+            .as_ref()
+            .map(|t| t.kind == TokenKind::SemiColon)
+            .unwrap_or(false)
+        {
+            self.next_token();
+        }
+
+        if self.loop_depth == 0 {
+            return Err(ParserError::OutsideOfLoop(token));
+        }
+
+        match token.kind {
+            TokenKind::Break => Ok(Statement::new_break(token)),
+            _ => Ok(Statement::new_continue(token)),
+        }
+    }
+
+    /// Parses a function body, which starts outside of any loop.
+    fn parse_function_body(&mut self) -> Result<Block, ParserError> {
+        let loop_depth = std::mem::take(&mut self.loop_depth);
+        let body = match self.parse_block_statement()? {
+            Statement::Block(block) => block,
+            _ => unreachable!(),
+        };
+        self.loop_depth = loop_depth;
+
+        Ok(body)
+    }
+
     /// Parses a function declaration statement.
     fn parse_function_declaration_statement(&mut self) -> Result<Statement, ParserError> {
         self.expect_token_kind(&self.current_token, TokenKind::Function)?;
+        let doc = self.current_doc.take();
         let fn_token = self.current_token.take().expect("checked before");
         self.next_token();
 
@@ -177,15 +673,11 @@ impl Parser {
         let params = self.parse_function_parameters()?;
         self.next_token();
 
-        let body = match self.parse_block_statement() {
-            Ok(block) => match block {
-                Statement::Block(block) => block,
-                _ => unreachable!(),
-            },
-            Err(err) => return Err(err),
-        };
+        let body = self.parse_function_body()?;
 
-        Ok(Statement::new_function(fn_token, identifier, params, body))
+        Ok(Statement::new_function(
+            fn_token, identifier, params, body, doc,
+        ))
     }
 
     /// Parses a block statement.
@@ -196,15 +688,26 @@ impl Parser {
 
         let mut statements = vec![];
 
-        while self
+        while let Some(start) = self
             .current_token
             .as_ref()
-            .map(|t| t.kind != TokenKind::RightCurly)
-            .unwrap_or(false)
+            .filter(|t| t.kind != TokenKind::RightCurly)
+            .map(|t| t.position)
         {
-            let stmt = self.parse_statement()?;
-            statements.push(stmt);
-            self.next_token();
+            let loop_depth = self.loop_depth;
+            let depth = self.depth_before_current();
+            match self.parse_statement() {
+                Ok(stmt) => {
+                    statements.push(*stmt);
+                    self.next_token();
+                }
+                // the rest of the block is still checked
+                Err(err) => {
+                    self.errors.push(err);
+                    self.loop_depth = loop_depth;
+                    self.synchronize(start, depth);
+                }
+            }
         }
 
         self.expect_token_kind(&self.current_token, TokenKind::RightCurly)?;
@@ -272,6 +775,17 @@ impl Parser {
         let expr_token = self.current_token.clone().expect("checked before");
         let expr = self.parse_expression(Precedence::Lowest)?;
 
+        if self
+            .peek_token
+            .as_ref()
+            .map(|t| {
+                t.kind == TokenKind::Equals || InfixOp::from_assign_token_kind(&t.kind).is_some()
+            })
+            .unwrap_or(false)
+        {
+            return self.parse_assign_statement(expr_token, expr);
+        }
+
         if self
             .peek_token
             .as_ref()
@@ -293,13 +807,20 @@ impl Parser {
         let mut expr: Box<Expression> =
             match self.current_token.as_ref().expect("checked before").kind {
                 TokenKind::Identifier(_) => Box::new(self.parse_identifier()?),
-                TokenKind::Integer(_) => Box::new(self.parse_integer()?),
+                TokenKind::Integer(_) | TokenKind::BigInt(_) => Box::new(self.parse_integer()?),
+                TokenKind::MalformedNumber(_)
+                | TokenKind::InvalidEscape(_)
+                | TokenKind::UnterminatedString(_) => Box::new(self.parse_invalid_literal()),
                 TokenKind::Float(_) => Box::new(self.parse_float()?),
                 TokenKind::True | TokenKind::False => Box::new(self.parse_boolean()?),
                 TokenKind::Bang | TokenKind::Minus => Box::new(self.parse_prefixed_expression()?),
                 TokenKind::Function => Box::new(self.parse_function_expression()?),
                 TokenKind::String(_) => Box::new(self.parse_string_literal()?),
+                TokenKind::Template(_) => Box::new(self.parse_template_literal()),
+                TokenKind::If => Box::new(Expression::Conditional(self.parse_conditional()?)),
+                TokenKind::Match => Box::new(Expression::Match(self.parse_match()?)),
                 TokenKind::LeftBracket => Box::new(self.parse_array_literal()?),
+                TokenKind::LeftCurly => Box::new(self.parse_map_literal()?),
 
                 TokenKind::LeftParen => self.parse_grouped_expression()?,
 
@@ -324,10 +845,16 @@ impl Parser {
                 | TokenKind::GreaterThan
                 | TokenKind::GreaterThanEquals
                 | TokenKind::EqualsEquals
-                | TokenKind::BangEquals => {
+                | TokenKind::BangEquals
+                | TokenKind::AndAnd
+                | TokenKind::OrOr => {
                     self.next_token();
                     expr = Box::new(self.parse_infixed_expression(expr)?);
                 }
+                TokenKind::DotDot | TokenKind::DotDotEquals => {
+                    self.next_token();
+                    expr = Box::new(self.parse_range_expression(expr)?);
+                }
                 TokenKind::LeftParen => {
                     self.next_token();
                     expr = Box::new(self.parse_function_call(expr)?);
@@ -351,6 +878,44 @@ impl Parser {
         Ok(Expression::new_array(arr_token, els))
     }
 
+    /// Parses a map literal.
+    fn parse_map_literal(&mut self) -> Result<Expression, ParserError> {
+        self.expect_token_kind(&self.current_token, TokenKind::LeftCurly)?;
+        let map_token = self.current_token.take().expect("checked before");
+        let mut pairs = vec![];
+
+        while self
+            .peek_token
+            .as_ref()
+            .map(|t| t.kind != TokenKind::RightCurly)
+            .unwrap_or(false)
+        {
+            self.next_token();
+            let key = self.parse_expression(Precedence::Lowest)?;
+            self.expect_token_kind(&self.peek_token, TokenKind::Colon)?;
+            self.next_token();
+            self.next_token();
+            let value = self.parse_expression(Precedence::Lowest)?;
+            pairs.push((key, value));
+
+            if self
+                .peek_token
+                .as_ref()
+                .map(|t| t.kind == TokenKind::Comma)
+                .unwrap_or(false)
+            {
+                self.next_token();
+            } else {
+                break;
+            }
+        }
+
+        self.expect_token_kind(&self.peek_token, TokenKind::RightCurly)?;
+        self.next_token();
+
+        Ok(Expression::new_map(map_token, pairs))
+    }
+
     /// Parser a string literal
     fn parse_string_literal(&mut self) -> Result<Expression, ParserError> {
         self.expect_token_fn(&self.current_token, |t| {
@@ -362,6 +927,68 @@ impl Parser {
         ))
     }
 
+    /// Parses a template string, takes the current token.
+    fn parse_template_literal(&mut self) -> Expression {
+        let token = self.current_token.take().expect("checked before");
+        let TokenKind::Template(template_parts) = &token.kind else {
+            unreachable!("called for template tokens only")
+        };
+
+        let parts = template_parts
+            .iter()
+            .map(|part| match part {
+                TemplatePart::Text(text) => Expression::new_string(Token::spanning(
+                    TokenKind::String(text.clone()),
+                    token.position,
+                    token.end,
+                )),
+                TemplatePart::Interpolation {
+                    source,
+                    position,
+                    offset,
+                } => self.parse_interpolation(source, *position, *offset, &token),
+            })
+            .collect();
+
+        Expression::new_template(token, parts)
+    }
+
+    /// Parses the source of an interpolation with a parser of its own.
+    /// Its errors are recorded and an empty string stands in for the expression,
+    /// so the rest of the statement is checked.
+    fn parse_interpolation(
+        &mut self,
+        source: &str,
+        position: Position,
+        offset: usize,
+        template: &Token,
+    ) -> Expression {
+        let mut parser = Parser::new(Lexer::starting_after(source.to_string(), position, offset));
+        let expr = parser
+            .parse_expression(Precedence::Lowest)
+            .and_then(|expr| match parser.peek_token.take() {
+                Some(token) => Err(ParserError::InvalidToken(token)),
+                None => Ok(*expr),
+            });
+        self.errors.append(&mut parser.errors);
+
+        expr.unwrap_or_else(|err| {
+            // the end of the interpolation has no token, the error points at the whole template
+            let err = match err {
+                ParserError::UnexpectedEOF | ParserError::UnexpectedEOFWithExpectation(_) => {
+                    ParserError::IncompleteInterpolation(template.clone())
+                }
+                err => err,
+            };
+            self.errors.push(err);
+            Expression::new_string(Token::spanning(
+                TokenKind::String(String::new()),
+                template.position,
+                template.end,
+            ))
+        })
+    }
+
     fn parse_index_expression(
         &mut self,
         index_of: Box<Expression>,
@@ -370,8 +997,7 @@ impl Parser {
         let index_token = self.current_token.take().expect("checked before");
         self.next_token();
         let index = self.parse_expression(Precedence::Lowest)?;
-        self.next_token();
-        self.expect_token_kind(&self.current_token, TokenKind::RightBracket)?;
+        self.expect_token_kind(&self.peek_token, TokenKind::RightBracket)?;
         self.next_token();
 
         Ok(Expression::new_index(index_token, index_of, index))
@@ -391,7 +1017,7 @@ impl Parser {
     fn parse_expression_list(
         &mut self,
         end_token_kind: TokenKind,
-    ) -> Result<Vec<Box<Expression>>, ParserError> {
+    ) -> Result<Vec<Expression>, ParserError> {
         let mut list = vec![];
 
         if self
@@ -405,7 +1031,7 @@ impl Parser {
         }
 
         self.next_token();
-        list.push(self.parse_expression(Precedence::Lowest)?);
+        list.push(*self.parse_expression(Precedence::Lowest)?);
 
         while self
             .peek_token
@@ -415,7 +1041,7 @@ impl Parser {
         {
             self.next_token();
             self.next_token();
-            list.push(self.parse_expression(Precedence::Lowest)?);
+            list.push(*self.parse_expression(Precedence::Lowest)?);
         }
 
         self.expect_token_kind(&self.peek_token, end_token_kind)?;
@@ -433,13 +1059,7 @@ impl Parser {
         let params = self.parse_function_parameters()?;
         self.next_token();
 
-        let body = match self.parse_block_statement() {
-            Ok(body) => match body {
-                Statement::Block(block) => block,
-                _ => unreachable!(),
-            },
-            Err(err) => return Err(err),
-        };
+        let body = self.parse_function_body()?;
 
         Ok(Expression::new_function(fn_token, params, body))
     }
@@ -465,6 +1085,17 @@ impl Parser {
         Ok(Expression::new_infixed(op_token, left, right))
     }
 
+    /// Parses a range expression.
+    fn parse_range_expression(
+        &mut self,
+        start: Box<Expression>,
+    ) -> Result<Expression, ParserError> {
+        let range_token = self.current_token.take().expect("checked before");
+        self.next_token();
+        let end = self.parse_expression(Precedence::Range)?;
+        Ok(Expression::new_range(range_token, start, end))
+    }
+
     /// Parses a prefixed expression.
     fn parse_prefixed_expression(&mut self) -> Result<Expression, ParserError> {
         self.expect_token_fn(&self.current_token, |t| {
@@ -507,13 +1138,46 @@ impl Parser {
     /// Takes the current token and returns an Integer.
     fn parse_integer(&mut self) -> Result<Expression, ParserError> {
         self.expect_token_fn(&self.current_token, |t| {
-            matches!(t.kind, TokenKind::Integer(_))
+            matches!(t.kind, TokenKind::Integer(_) | TokenKind::BigInt(_))
         })?;
         Ok(Expression::new_integer(
             self.current_token.take().expect("checked before"),
         ))
     }
 
+    /// Records the lexer error and parses the literal as `0` or `""`,
+    /// so the rest of the statement is checked.
+    ///
+    /// Takes the current token and returns an Integer or a StringLiteral.
+    fn parse_invalid_literal(&mut self) -> Expression {
+        let token = self.current_token.take().expect("checked before");
+        let (position, end) = (token.position, token.end);
+
+        match token.kind {
+            TokenKind::MalformedNumber(_) => {
+                self.errors.push(ParserError::MalformedNumber(token));
+                Expression::new_integer(Token::spanning(TokenKind::Integer(0), position, end))
+            }
+            TokenKind::InvalidEscape(_) => {
+                self.errors.push(ParserError::InvalidEscape(token));
+                Expression::new_string(Token::spanning(
+                    TokenKind::String(String::new()),
+                    position,
+                    end,
+                ))
+            }
+            TokenKind::UnterminatedString(_) => {
+                self.errors.push(ParserError::UnterminatedString(token));
+                Expression::new_string(Token::spanning(
+                    TokenKind::String(String::new()),
+                    position,
+                    end,
+                ))
+            }
+            _ => unreachable!("called for invalid literals only"),
+        }
+    }
+
     /// Parses an identifier.
     ///
     /// Takes the current token and returns an Identifier.
@@ -549,6 +1213,7 @@ impl Parser {
     /// Parses a let statement
     fn parse_let_statement(&mut self) -> Result<Statement, ParserError> {
         self.expect_token_kind(&self.current_token, TokenKind::Let)?;
+        let doc = self.current_doc.take();
         let let_token = self.current_token.take().expect("checked before");
         self.next_token();
 
@@ -575,14 +1240,39 @@ impl Parser {
             self.next_token();
         }
 
-        Ok(Statement::new_let(let_token, identifier, value))
+        Ok(Statement::new_let(let_token, identifier, value, doc))
     }
 
     /// Populates the current token and the peek token.
     /// Advances the lexer to the next token.
+    /// Doc comments are kept for the token that follows them.
     fn next_token(&mut self) {
         self.current_token = self.peek_token.take();
-        self.peek_token = self.lexer.next();
+        self.current_doc = self.peek_doc.take();
+        match self.current_token.as_ref().map(|token| &token.kind) {
+            Some(TokenKind::LeftCurly) => self.brace_depth += 1,
+            Some(TokenKind::RightCurly) => self.brace_depth -= 1,
+            _ => {}
+        }
+
+        let mut doc = vec![];
+        self.peek_token = loop {
+            match self.lexer.next() {
+                Some(Token {
+                    kind: TokenKind::DocComment(text),
+                    ..
+                }) => doc.push(text.strip_prefix(' ').unwrap_or(&text).to_string()),
+                // the comment runs to the end of input
+                Some(
+                    token @ Token {
+                        kind: TokenKind::UnterminatedComment,
+                        ..
+                    },
+                ) => self.errors.push(ParserError::UnterminatedComment(token)),
+                token => break token,
+            }
+        };
+        self.peek_doc = (!doc.is_empty()).then(|| doc.join("\n"));
     }
 
     /// Checks if the current token matches the expected token kind.
@@ -623,6 +1313,7 @@ impl Parser {
 #[cfg(test)]
 mod parser_tests {
     use super::*;
+    use crate::lexer::Position;
 
     /// Helper function to create a simple lexer from a string input
     fn create_lexer(input: &str) -> Lexer {
@@ -630,7 +1321,7 @@ mod parser_tests {
     }
 
     /// Helper function to parse and extract the first statement
-    fn parse_first_statement(input: &str) -> Result<Box<Statement>, Vec<ParserError>> {
+    fn parse_first_statement(input: &str) -> Result<Statement, Vec<ParserError>> {
         let lexer = create_lexer(input);
         let mut parser = Parser::new(lexer);
         let program = parser.parse()?;
@@ -642,9 +1333,9 @@ mod parser_tests {
         let input = "x = 5;";
         let statement = parse_first_statement(input).unwrap();
 
-        match *statement {
+        match statement {
             Statement::Assign(assign_stmt) => {
-                assert_eq!(assign_stmt.identifier.token.literal(), "x");
+                assert_eq!(assign_stmt.target.to_string(), "x");
 
                 match assign_stmt.value.as_ref() {
                     Expression::Integer(int_expr) => {
@@ -657,12 +1348,39 @@ mod parser_tests {
         }
     }
 
+    #[test]
+    fn test_index_and_compound_assign_statement() {
+        let tests = vec![
+            ("arr[0] = 1;", "arr[0] = 1"),
+            ("m[\"a\"][1] += 2", "m[a][1] += 2"),
+            ("x -= 1 + 2;", "x -= (1+2)"),
+            ("x *= 3", "x *= 3"),
+            ("x /= 4", "x /= 4"),
+        ];
+
+        for (input, expected) in tests {
+            let statement = parse_first_statement(input).unwrap();
+            match statement {
+                Statement::Assign(assign_stmt) => assert_eq!(assign_stmt.to_string(), expected),
+                _ => panic!("Expected assign statement"),
+            }
+        }
+
+        for input in ["1 = 2;", "f() = 2;", "x + 1 += 2;"] {
+            let errors = parse_first_statement(input).unwrap_err();
+            assert!(
+                matches!(errors[0], ParserError::InvalidAssignmentTarget(_)),
+                "{input}: {errors:?}"
+            );
+        }
+    }
+
     #[test]
     fn test_string_index_expression() {
         let input = "[1,2,3][1];";
         let statement = parse_first_statement(input).unwrap();
 
-        match *statement {
+        match statement {
             Statement::ExpressionStatement(expr_stmt) => match expr_stmt.expression.as_ref() {
                 Expression::Index(idx_expr) => {
                     assert_eq!(idx_expr.token.kind, TokenKind::LeftBracket);
@@ -673,12 +1391,25 @@ mod parser_tests {
         }
     }
 
+    #[test]
+    fn test_chained_index_expression() {
+        let input = "m[\"a\"][0] + len(m[1], x)";
+        let statement = parse_first_statement(input).unwrap();
+
+        match statement {
+            Statement::ExpressionStatement(expr_stmt) => {
+                assert_eq!(expr_stmt.expression.to_string(), "(m[a][0]+len(m[1], x))");
+            }
+            _ => panic!("Expected expression statement"),
+        }
+    }
+
     #[test]
     fn test_string_literal_expression() {
         let input = "\"what\";";
         let statement = parse_first_statement(input).unwrap();
 
-        match *statement {
+        match statement {
             Statement::ExpressionStatement(expr_stmt) => match expr_stmt.expression.as_ref() {
                 Expression::String(str_expr) => {
                     assert_eq!(str_expr.token.literal(), "what");
@@ -689,31 +1420,213 @@ mod parser_tests {
         }
     }
 
+    /// Parses the input and returns the conditional of the `if` statement it starts with.
+    fn parse_condition_statement(input: &str) -> Conditional {
+        match parse_first_statement(input).unwrap() {
+            Statement::ExpressionStatement(stmt) => match *stmt.expression {
+                Expression::Conditional(cond) => cond,
+                other => panic!("Expected conditional expression, got {other:?}"),
+            },
+            other => panic!("Expected expression statement, got {other:?}"),
+        }
+    }
+
     #[test]
     fn test_condition_statement_parsing() {
-        let input = "if (true) { 2 } else { 4; 2 + 2 }";
+        let cond = parse_condition_statement("if (true) { 2 } else { 4; 2 + 2 }");
+        assert_eq!(cond.condition.to_string(), "true");
+        assert_eq!(cond.consequence.statements.len(), 1);
+        match cond.alternative {
+            Some(Else::Block(block)) => assert_eq!(block.statements.len(), 2),
+            other => panic!("Expected else block, got {other:?}"),
+        }
+
+        let cond = parse_condition_statement("if (true) { 2 }");
+        assert_eq!(cond.condition.to_string(), "true");
+        assert_eq!(cond.consequence.statements.len(), 1);
+        assert!(cond.alternative.is_none());
+
+        // the statement ends with the block, `-1` is a statement of its own
+        let program = Parser::new(create_lexer("if a { 1 } -1")).parse().unwrap();
+        assert_eq!(program.statements.len(), 2);
+    }
+
+    #[test]
+    fn test_else_if_parsing() {
+        let cond = parse_condition_statement("if a { 1 } else if b { 2 } else { 3 }");
+        assert_eq!(cond.condition.to_string(), "a");
+        match cond.alternative {
+            Some(Else::If(nested)) => {
+                assert_eq!(nested.condition.to_string(), "b");
+                assert!(matches!(nested.alternative, Some(Else::Block(_))));
+            }
+            other => panic!("Expected else if, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_conditional_expression_parsing() {
+        let input = "let x = if (a) { 1 } else if b { 2 };";
         let statement = parse_first_statement(input).unwrap();
 
-        match *statement {
-            Statement::Condition(cond) => {
-                assert_eq!(cond.condition.to_string(), "true");
-                assert_eq!(cond.if_true.statements.len(), 1);
-                assert!(cond.if_false.is_some());
-                assert_eq!(cond.if_false.unwrap().statements.len(), 2);
+        match statement {
+            Statement::LetStatement(let_stmt) => match *let_stmt.value {
+                Expression::Conditional(conditional) => {
+                    assert_eq!(
+                        conditional.to_string(),
+                        "if (a) {\n  1\n} else if (b) {\n  2\n}"
+                    );
+                }
+                other => panic!("Expected conditional expression, got {other:?}"),
+            },
+            _ => panic!("Expected let statement"),
+        }
+
+        let mut parser = Parser::new(Lexer::new("let x = if a { 1 } else;".to_string()));
+        assert!(parser.parse().is_err());
+    }
+
+    #[test]
+    fn test_match_parsing() {
+        let input = r#"let x = match v {
+            0 | -1 => "low",
+            [a, _] if a > 1 => { a }
+            {"k": [b], 2: true} => b,
+            n => n
+        };"#;
+        let statement = parse_first_statement(input).unwrap();
+
+        let Statement::LetStatement(let_stmt) = statement else {
+            panic!("Expected let statement");
+        };
+        let Expression::Match(expr) = *let_stmt.value else {
+            panic!("Expected match expression");
+        };
+        assert_eq!(expr.value.to_string(), "v");
+        assert_eq!(
+            expr.arms
+                .iter()
+                .map(|arm| arm.pattern.to_string())
+                .collect::<Vec<String>>(),
+            vec!["0 | -1", "[a, _]", "{k: [b], 2: true}", "n"]
+        );
+        assert_eq!(
+            expr.arms[1].guard.as_ref().map(|guard| guard.to_string()),
+            Some("(a>1)".to_string())
+        );
+        assert!(matches!(expr.arms[1].body, ArmBody::Block(_)));
+        assert!(matches!(expr.arms[3].body, ArmBody::Expression(_)));
+
+        let statement = parse_first_statement("match v { _ => {} };").unwrap();
+        assert!(matches!(
+            statement,
+            Statement::ExpressionStatement(stmt) if matches!(*stmt.expression, Expression::Match(_))
+        ));
+    }
+
+    #[test]
+    fn test_match_errors() {
+        let tests = [
+            ("match v { 1 2 }", Position(1, 13)),
+            ("match v { x + 1 => x }", Position(1, 13)),
+            ("match v { 1.5: x => x }", Position(1, 14)),
+            ("match v { {1.5: x} => x }", Position(1, 12)),
+            ("match v { -x => x }", Position(1, 12)),
+            ("match v { 1 => 1 2 => 2 }", Position(1, 18)),
+            // names bound twice or by only some of the alternatives
+            ("match v { [x, x] => x }", Position(1, 15)),
+            (r#"match v { {"a": x, "b": [1, x]} => x }"#, Position(1, 29)),
+            ("match v { [x, _] | [0, y] => x }", Position(1, 24)),
+            ("match v { [x, y] | [x, 0] => x }", Position(1, 15)),
+            ("match v { x | 1 => 1 }", Position(1, 11)),
+        ];
+
+        for (input, position) in tests {
+            let mut parser = Parser::new(create_lexer(input));
+            let errors = parser.parse().unwrap_err();
+            assert_eq!(
+                errors[0].token().map(|t| t.position),
+                Some(position),
+                "{input}: {errors:?}"
+            );
+        }
+
+        let mut parser = Parser::new(create_lexer("match v { [x, x] => x }"));
+        let errors = parser.parse().unwrap_err();
+        assert!(matches!(errors[0], ParserError::DuplicateBinding(_)));
+        let mut parser = Parser::new(create_lexer("match v { x | 1 => 1 }"));
+        let errors = parser.parse().unwrap_err();
+        assert!(matches!(errors[0], ParserError::AlternativeBinding(_)));
+
+        // the same name in every alternative is fine, in any order
+        let mut parser = Parser::new(create_lexer(
+            "match v { [x, y] | [y, x] | {\"x\": x, \"y\": y} => x }",
+        ));
+        assert!(parser.parse().is_ok());
+    }
+
+    #[test]
+    fn test_while_statement_parsing() {
+        let input = "while x < 10 { x = x + 1; continue; break }";
+        let statement = parse_first_statement(input).unwrap();
+
+        match statement {
+            Statement::While(while_stmt) => {
+                assert_eq!(while_stmt.condition.to_string(), "(x<10)");
+                assert_eq!(while_stmt.body.statements.len(), 3);
+                assert!(matches!(
+                    while_stmt.body.statements[1],
+                    Statement::Continue(_)
+                ));
+                assert!(matches!(while_stmt.body.statements[2], Statement::Break(_)));
             }
-            _ => panic!("Expected condition statement"),
+            _ => panic!("Expected while statement"),
         }
 
-        let input = "if (true) { 2 }";
+        let input = "while (true) { }";
         let statement = parse_first_statement(input).unwrap();
+        assert!(matches!(statement, Statement::While(_)));
+    }
 
-        match *statement {
-            Statement::Condition(cond) => {
-                assert_eq!(cond.condition.to_string(), "true");
-                assert_eq!(cond.if_true.statements.len(), 1);
-                assert!(cond.if_false.is_none());
+    #[test]
+    fn test_for_statement_parsing() {
+        let input = "for x in 0..=n + 1 { println(x); break; }";
+        let statement = parse_first_statement(input).unwrap();
+
+        match statement {
+            Statement::For(for_stmt) => {
+                assert_eq!(for_stmt.item.to_string(), "x");
+                assert_eq!(for_stmt.iterable.to_string(), "(0..=(n+1))");
+                assert_eq!(for_stmt.body.statements.len(), 2);
             }
-            _ => panic!("Expected condition statement"),
+            _ => panic!("Expected for statement"),
+        }
+
+        let input = "for ch in \"abc\" { }";
+        let statement = parse_first_statement(input).unwrap();
+        assert!(matches!(statement, Statement::For(_)));
+
+        let input = "for x [1] { }";
+        assert!(parse_first_statement(input).is_err());
+    }
+
+    #[test]
+    fn test_loop_control_outside_of_loop() {
+        let inputs = vec![
+            "break;",
+            "continue;",
+            "if (true) { break; }",
+            "while (true) { fn f() { continue; } }",
+            "while (true) { let f = fn() { break; }; }",
+        ];
+
+        for input in inputs {
+            let result = Parser::new(create_lexer(input)).parse();
+            let errors = result.expect_err(input);
+            assert!(
+                matches!(errors[0], ParserError::OutsideOfLoop(_)),
+                "{input}: {errors:?}"
+            );
         }
     }
 
@@ -722,7 +1635,7 @@ mod parser_tests {
         let input = "let x = 5;";
         let statement = parse_first_statement(input).unwrap();
 
-        match *statement {
+        match statement {
             Statement::LetStatement(let_stmt) => {
                 assert_eq!(let_stmt.identifier.token.literal(), "x");
 
@@ -742,7 +1655,7 @@ mod parser_tests {
         let input = "return 5;";
         let statement = parse_first_statement(input).unwrap();
 
-        match *statement {
+        match statement {
             Statement::ReturnStatement(return_stmt) => match return_stmt.value.as_ref() {
                 Expression::Integer(int_expr) => {
                     assert_eq!(int_expr.token.literal(), "5");
@@ -758,7 +1671,7 @@ mod parser_tests {
         let input = "foobar;";
         let statement = parse_first_statement(input).unwrap();
 
-        match *statement {
+        match statement {
             Statement::ExpressionStatement(expr_stmt) => match expr_stmt.expression.as_ref() {
                 Expression::Identifier(ident) => {
                     assert_eq!(ident.token.literal(), "foobar");
@@ -774,7 +1687,7 @@ mod parser_tests {
         let input = "[1,2];";
         let statement = parse_first_statement(input).unwrap();
 
-        match *statement {
+        match statement {
             Statement::ExpressionStatement(expr_stmt) => match expr_stmt.expression.as_ref() {
                 Expression::Array(arr) => {
                     assert_eq!(arr.token.literal(), "[");
@@ -786,12 +1699,41 @@ mod parser_tests {
         }
     }
 
+    #[test]
+    fn test_map_literal_expression() {
+        let input = "{\"a\": 1, 2: \"b\", true: [1 + 2]};";
+        let statement = parse_first_statement(input).unwrap();
+
+        match statement {
+            Statement::ExpressionStatement(expr_stmt) => match expr_stmt.expression.as_ref() {
+                Expression::Map(map) => {
+                    assert_eq!(map.pairs.len(), 3);
+                    assert_eq!(map.to_string(), "{a: 1, 2: b, true: [(1+2)]}");
+                }
+                _ => panic!("Expected map expression"),
+            },
+            _ => panic!("Expected expression statement"),
+        }
+
+        let statement = parse_first_statement("{}").unwrap();
+        match statement {
+            Statement::ExpressionStatement(expr_stmt) => {
+                assert_eq!(expr_stmt.expression.to_string(), "{}")
+            }
+            _ => panic!("Expected expression statement"),
+        }
+
+        assert!(parse_first_statement("{\"a\" 1}").is_err());
+        assert!(parse_first_statement("{\"a\": 1,}").is_ok());
+        assert!(parse_first_statement("{,}").is_err());
+    }
+
     #[test]
     fn test_integer_literal_expression() {
         let input = "5;";
         let statement = parse_first_statement(input).unwrap();
 
-        match *statement {
+        match statement {
             Statement::ExpressionStatement(expr_stmt) => match expr_stmt.expression.as_ref() {
                 Expression::Integer(int_expr) => {
                     assert_eq!(int_expr.token.literal(), "5");
@@ -807,7 +1749,7 @@ mod parser_tests {
         let input = "5.5;";
         let statement = parse_first_statement(input).unwrap();
 
-        match *statement {
+        match statement {
             Statement::ExpressionStatement(expr_stmt) => match expr_stmt.expression.as_ref() {
                 Expression::Float(float_expr) => {
                     assert_eq!(float_expr.token.literal(), "5.5");
@@ -825,7 +1767,7 @@ mod parser_tests {
         for input in inputs {
             let statement = parse_first_statement(input).unwrap();
 
-            match *statement {
+            match statement {
                 Statement::ExpressionStatement(expr_stmt) => match expr_stmt.expression.as_ref() {
                     Expression::Boolean(bool_expr) => {
                         assert_eq!(bool_expr.token.literal(), input.trim_end_matches(';'));
@@ -847,7 +1789,7 @@ mod parser_tests {
         for (input, expected_prefix, expected_value) in inputs {
             let statement = parse_first_statement(input).unwrap();
 
-            match *statement {
+            match statement {
                 Statement::ExpressionStatement(expr_stmt) => match expr_stmt.expression.as_ref() {
                     Expression::Prefixed(prefix_expr) => {
                         assert_eq!(prefix_expr.op.kind, expected_prefix);
@@ -881,7 +1823,7 @@ mod parser_tests {
         for (input, expected_left, expected_op, expected_right) in inputs {
             let statement = parse_first_statement(input).unwrap();
 
-            match *statement {
+            match statement {
                 Statement::ExpressionStatement(expr_stmt) => match expr_stmt.expression.as_ref() {
                     Expression::Infixed(infix_expr) => {
                         assert_eq!(infix_expr.op.kind, expected_op);
@@ -907,12 +1849,27 @@ mod parser_tests {
         }
     }
 
+    #[test]
+    fn test_logical_expressions() {
+        let tests = vec![
+            ("a || b && c", "(a||(b&&c))"),
+            ("a && b || c", "((a&&b)||c)"),
+            ("a == b && c != d", "((a==b)&&(c!=d))"),
+            ("!a || b < c", "((!a)||(b<c))"),
+        ];
+
+        for (input, expected) in tests {
+            let statement = parse_first_statement(input).unwrap();
+            assert_eq!(statement.to_string(), expected, "{input}");
+        }
+    }
+
     #[test]
     fn test_function_declaration() {
         let input = "fn add(x, y) { x + y; }";
         let statement = parse_first_statement(input).unwrap();
 
-        match *statement {
+        match statement {
             Statement::FunctionDeclaration(func_decl) => {
                 assert_eq!(func_decl.identifier.token.literal(), "add");
                 assert_eq!(func_decl.parameters.len(), 2);
@@ -931,7 +1888,7 @@ mod parser_tests {
         let input = "fn(x, y) { x + y; };";
         let statement = parse_first_statement(input).unwrap();
 
-        match *statement {
+        match statement {
             Statement::ExpressionStatement(expr_stmt) => {
                 match expr_stmt.expression.as_ref() {
                     Expression::Function(func_expr) => {
@@ -954,7 +1911,7 @@ mod parser_tests {
         let input = "add(1, 2 * 3);";
         let statement = parse_first_statement(input).unwrap();
 
-        match *statement {
+        match statement {
             Statement::ExpressionStatement(expr_stmt) => {
                 match expr_stmt.expression.as_ref() {
                     Expression::FunctionCall(call_expr) => {
@@ -970,7 +1927,7 @@ mod parser_tests {
                         assert_eq!(call_expr.arguments.len(), 2);
 
                         // First argument should be simple integer
-                        match call_expr.arguments[0].as_ref() {
+                        match &call_expr.arguments[0] {
                             Expression::Integer(int_expr) => {
                                 assert_eq!(int_expr.token.literal(), "1");
                             }
@@ -978,7 +1935,7 @@ mod parser_tests {
                         }
 
                         // Second argument should be an infix expression
-                        match call_expr.arguments[1].as_ref() {
+                        match &call_expr.arguments[1] {
                             Expression::Infixed(infix_expr) => {
                                 assert_eq!(infix_expr.op.kind, TokenKind::Asterisk);
                             }
@@ -1012,4 +1969,237 @@ mod parser_tests {
             );
         }
     }
+
+    #[test]
+    fn test_parsing_error_recovery() {
+        let input = "let a = ;\n\
+                     let b = 2 +;\n\
+                     print(b);\n\
+                     fn f(x y) { return x; }\n\
+                     let c = 3;\n\
+                     fn g() {\n\
+                         let d = ) + 1;\n\
+                         let e = 4;\n\
+                         return e;\n\
+                     }\n\
+                     if (a b) { 1; } else { 2; }\n\
+                     let h = 5;\n\
+                     }\n\
+                     let i = [1, 2;\n\
+                     let j = 6;\n\
+                     match 1 { 1 => 2 3 => 4 }\n\
+                     let m = {\"a\": 1 \"b\": 2}; let y = 1;\n\
+                     match [1,2] { [a, a] => a }";
+        let errors = Parser::new(create_lexer(input)).parse().unwrap_err();
+        let messages = errors
+            .iter()
+            .map(|err| err.to_string())
+            .collect::<Vec<String>>();
+
+        // one error for every broken statement, none for the statements around them
+        assert_eq!(
+            messages,
+            vec![
+                "Unexpected token: ; - line:1, col:9",
+                "Unexpected token: ; - line:2, col:12",
+                "Unexpected token: wanted ); got y - line:4, col:8",
+                "Unexpected token: ) - line:7, col:9",
+                "Unexpected token: wanted ); got b - line:11, col:7",
+                "Unexpected token: } - line:13, col:1",
+                "Unexpected token: wanted ]; got ; - line:14, col:14",
+                "Unexpected token: wanted }; got 3 - line:16, col:18",
+                "Unexpected token: wanted }; got b - line:17, col:17",
+                "Name bound twice in a pattern: a - line:18, col:19",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parsing_error_deduplication() {
+        // the statement and the blocks around it all run into the end of input,
+        // each kind of error is reported once
+        let errors = Parser::new(create_lexer("fn f() { fn g() { let a ="))
+            .parse()
+            .unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                ParserError::UnexpectedEOF,
+                ParserError::UnexpectedEOFWithExpectation(TokenKind::RightCurly),
+            ]
+        );
+
+        // tokens skipped while recovering aren't reported
+        let errors = Parser::new(create_lexer("while (true) { ) ) }\n) let x = 1;"))
+            .parse()
+            .unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                ParserError::InvalidToken(Token::new(TokenKind::RightParen, Position(1, 16))),
+                ParserError::InvalidToken(Token::new(TokenKind::RightParen, Position(2, 1))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_malformed_number_errors() {
+        let input = "let a = 1.2.3;\nlet b = a + 0..3.4.5;\nlet c = 4 * ;";
+        let errors = Parser::new(create_lexer(input)).parse().unwrap_err();
+
+        assert_eq!(
+            errors,
+            vec![
+                ParserError::MalformedNumber(Token::new(
+                    TokenKind::MalformedNumber("1.2.3".to_string()),
+                    Position(1, 9)
+                )),
+                ParserError::MalformedNumber(Token::new(
+                    TokenKind::MalformedNumber("3.4.5".to_string()),
+                    Position(2, 16)
+                )),
+                ParserError::InvalidToken(Token::new(TokenKind::SemiColon, Position(3, 13))),
+            ]
+        );
+        assert_eq!(
+            errors[0].to_string(),
+            "Malformed number literal: 1.2.3 - line:1, col:9"
+        );
+    }
+
+    #[test]
+    fn test_template_literal_expression() {
+        let input = r#""sum: ${a + b}, ${"x${1}"}";"#;
+        let statement = parse_first_statement(input).unwrap();
+
+        match statement {
+            Statement::ExpressionStatement(expr_stmt) => match expr_stmt.expression.as_ref() {
+                Expression::Template(template) => {
+                    assert_eq!(template.parts.len(), 4);
+                    assert_eq!(template.to_string(), "sum: ${(a+b)}, ${x${1}}");
+                    match &template.parts[1] {
+                        Expression::Infixed(infix) => {
+                            assert!(matches!(
+                                infix.left.as_ref(),
+                                Expression::Identifier(ident) if ident.token.position == Position(1, 9)
+                            ));
+                        }
+                        other => panic!("Expected infixed expression, got {other:?}"),
+                    }
+                }
+                other => panic!("Expected template expression, got {other:?}"),
+            },
+            _ => panic!("Expected expression statement"),
+        }
+    }
+
+    #[test]
+    fn test_template_literal_errors() {
+        let input = "let a = \"${}\";\nlet b = \"${1 +}\" + \"${1 2}\";\nlet c = \"${1.2.3}\";";
+        let errors = Parser::new(create_lexer(input)).parse().unwrap_err();
+        let messages = errors
+            .iter()
+            .map(|err| err.to_string())
+            .collect::<Vec<String>>();
+
+        assert_eq!(
+            messages,
+            vec![
+                "Incomplete interpolation: ${} - line:1, col:9",
+                "Incomplete interpolation: ${1 +} - line:2, col:9",
+                "Unexpected token: 2 - line:2, col:25",
+                "Malformed number literal: 1.2.3 - line:3, col:12",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_doc_comments() {
+        let input = "/// Adds.\n/// Two lines.\nfn add(a, b) { /// inner\nlet c = a; c + b }\n\
+                     /// not attached\n1;\nlet x = 1;\n/// The answer. // still doc\nlet y = 42; ///";
+        let program = Parser::new(create_lexer(input)).parse().unwrap();
+
+        let docs = program
+            .statements
+            .iter()
+            .map(|stmt| match stmt {
+                Statement::FunctionDeclaration(func) => {
+                    match &func.body.statements[0] {
+                        Statement::LetStatement(let_stmt) => {
+                            assert_eq!(let_stmt.doc.as_deref(), Some("inner"))
+                        }
+                        other => panic!("Expected let statement, got {other:?}"),
+                    }
+                    func.doc.clone()
+                }
+                Statement::LetStatement(let_stmt) => let_stmt.doc.clone(),
+                _ => None,
+            })
+            .collect::<Vec<Option<String>>>();
+
+        assert_eq!(
+            docs,
+            vec![
+                Some("Adds.\nTwo lines.".to_string()),
+                None,
+                None,
+                Some("The answer. // still doc".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unterminated_comment() {
+        let input = "let a = 1; /* open /* nested */\nlet b = 2;";
+        let errors = Parser::new(create_lexer(input)).parse().unwrap_err();
+
+        assert_eq!(
+            errors,
+            vec![ParserError::UnterminatedComment(Token::spanning(
+                TokenKind::UnterminatedComment,
+                Position(1, 12),
+                Position(2, 10)
+            ))]
+        );
+
+        let errors = Parser::new(create_lexer("let a = /*")).parse().unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                ParserError::UnterminatedComment(Token::spanning(
+                    TokenKind::UnterminatedComment,
+                    Position(1, 9),
+                    Position(1, 10)
+                )),
+                ParserError::UnexpectedEOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_string_literal_errors() {
+        let input = "let a = \"\\q\" + 1;\nlet b = \"open;\nlet c = * 2;";
+        let errors = Parser::new(create_lexer(input)).parse().unwrap_err();
+
+        assert_eq!(
+            errors,
+            vec![
+                ParserError::InvalidEscape(Token::spanning(
+                    TokenKind::InvalidEscape("\\q".to_string()),
+                    Position(1, 10),
+                    Position(1, 11)
+                )),
+                ParserError::UnterminatedString(Token::spanning(
+                    TokenKind::UnterminatedString("open;".to_string()),
+                    Position(2, 9),
+                    Position(2, 14)
+                )),
+                ParserError::InvalidToken(Token::new(TokenKind::Asterisk, Position(3, 9))),
+            ]
+        );
+        assert_eq!(
+            errors[1].to_string(),
+            "Unterminated string: \"open; - line:2, col:9"
+        );
+    }
 }
@@ -4,10 +4,16 @@ use crate::lexer::{Token, TokenKind};
 #[derive(Debug, PartialEq, PartialOrd)]
 pub enum Precedence {
     Lowest,
+    /// ||
+    Or,
+    /// &&
+    And,
     /// ==, !=
     Equals,
     /// >, <, <=, >=
     LessOrGreater,
+    /// a..b, a..=b
+    Range,
     /// +
     Sum,
     /// *, /
@@ -23,6 +29,8 @@ pub enum Precedence {
 impl Precedence {
     pub fn from_token(token: Token) -> Self {
         match token.kind {
+            TokenKind::OrOr => Self::Or,
+            TokenKind::AndAnd => Self::And,
             TokenKind::EqualsEquals | TokenKind::BangEquals => Self::Equals,
 
             TokenKind::GreaterThan
@@ -30,6 +38,8 @@ impl Precedence {
             | TokenKind::GreaterThanEquals
             | TokenKind::LessThanEquals => Self::LessOrGreater,
 
+            TokenKind::DotDot | TokenKind::DotDotEquals => Self::Range,
+
             TokenKind::Plus | TokenKind::Minus => Self::Sum,
             TokenKind::Asterisk | TokenKind::Slash => Self::Product,
             TokenKind::LeftParen => Self::Call,
@@ -1,18 +1,13 @@
-use std::{io::Write, rc::Rc};
+use std::io::Write;
 
-use crate::{
-    eval::{Eval, ExecutionEnvironment},
-    lexer::Lexer,
-    parser::Parser,
-};
+use tsykr::Engine;
 
 const PROMPT: &str = "> ";
 
 /// Run the REPL. Yoohoo!!!
-pub fn run() {
+pub fn run(engine: Engine) {
     let stdin = std::io::stdin();
     let mut stdout = std::io::stdout();
-    let env = ExecutionEnvironment::new_global();
 
     println!("Starting tsykr REPL...\n");
 
@@ -21,15 +16,18 @@ pub fn run() {
         stdout.flush().unwrap();
         let mut buf = String::new();
 
-        stdin.read_line(&mut buf).unwrap();
+        // end of input
+        if stdin.read_line(&mut buf).unwrap() == 0 {
+            println!();
+            break;
+        }
 
         if buf == "\n" {
             continue;
         }
 
         if buf == "?\n" {
-            println!("{:?}", env.as_ptr());
-            println!("{:#?}", env.borrow());
+            println!("{:#?}", engine);
             continue;
         }
 
@@ -39,26 +37,18 @@ pub fn run() {
             buf = buf.strip_prefix("?").unwrap().to_string();
         }
 
-        let lexer = Lexer::new(buf);
-        let mut parser = Parser::new(lexer);
-
-        match parser.parse() {
-            Ok(program) => match program.eval(Rc::clone(&env)) {
-                Ok(value) => {
-                    if debug_print {
-                        println!("{:?}", value)
-                    } else {
-                        println!("{}", value.to_string())
-                    }
+        match engine.eval(&buf) {
+            Ok(value) => {
+                if debug_print {
+                    println!("{:?}", value)
+                } else {
+                    println!("{value}")
                 }
-                Err(err) => eprintln!("Evaluation error: {err}"),
-            },
-            Err(errs) => {
-                eprintln!("Parser errors:");
-                for err in errs {
-                    eprintln!("\t{err}");
+            }
+            Err(err) => {
+                for diagnostic in err.diagnostics() {
+                    eprintln!("{}", diagnostic.render(&buf));
                 }
-                eprintln!()
             }
         }
     }